@@ -16,7 +16,7 @@ const result = await Bun.build({
   entrypoints: paths,
   bundle: true,
   clean: true,
-  external: ['@std/*', 'module:*'],
+  external: ['@std/*', 'module:*', 'node:*'],
   bundle: true,
   minfiy: true,
   define: {
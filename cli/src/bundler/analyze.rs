@@ -0,0 +1,555 @@
+use oxc_allocator::Allocator;
+use oxc_ast::{
+    ast::{
+        Argument, BindingPattern, BindingPatternKind, CallExpression, Class, ClassElement,
+        Declaration, ExportDefaultDeclarationKind, Expression, IdentifierReference,
+        ImportDeclarationSpecifier, ImportExpression, MetaProperty, ModuleExportName,
+        ObjectProperty, Statement,
+    },
+    syntax_directed_operations::BoundNames,
+    visit::walk,
+    Visit,
+};
+use oxc_parser::Parser;
+use oxc_semantic::{SemanticBuilder, SymbolId, SymbolTable};
+use oxc_span::{GetSpan, SourceType, Span};
+use rustc_hash::FxHashMap;
+
+/// Name used for `export default <expression>`, it has no binding of its own.
+pub const DEFAULT_BINDING: &str = "__ike_default";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportName {
+    Named(String),
+    Default,
+    Namespace,
+}
+
+/// A binding created by a static import, `local` is the name in the importing module.
+#[derive(Debug, Clone)]
+pub struct ImportBinding {
+    pub name: ImportName,
+    pub local: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub specifier: String,
+    pub bindings: Vec<ImportBinding>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LocalExport {
+    pub exported: String,
+    pub local: String,
+    /// Statement that only declares this export without side effects, so it can be
+    /// dropped when nothing imports it.
+    pub removable: Option<Span>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ReExport {
+    /// `export { imported as exported } from "specifier"`
+    Named {
+        specifier: String,
+        imported: String,
+        exported: String,
+    },
+    /// `export * from "specifier"`
+    Star { specifier: String },
+    /// `export * as exported from "specifier"`
+    Namespace { specifier: String, exported: String },
+}
+
+/// A use of an imported binding, rewritten to read the export of the bundled module so
+/// the binding stays live.
+#[derive(Debug, Clone)]
+pub struct ImportReference {
+    pub span: Span,
+    pub local: String,
+    /// `{ local }` in an object literal, which needs a key once rewritten.
+    pub shorthand: bool,
+}
+
+/// Replaces `span` of the transpiled code with `text`.
+#[derive(Debug, Clone)]
+pub struct Edit {
+    pub span: Span,
+    pub text: String,
+}
+
+/// Imports, exports and requires of a transpiled module, with the edits that strip the
+/// module syntax so the code can run inside a block of the bundle.
+#[derive(Debug, Default)]
+pub struct ModuleInfo {
+    pub imports: Vec<Import>,
+    pub exports: Vec<LocalExport>,
+    pub reexports: Vec<ReExport>,
+    /// `import("specifier")` calls with a string literal.
+    pub dynamic_imports: Vec<(Span, String)>,
+    /// `require("specifier")` calls with a string literal.
+    pub requires: Vec<String>,
+    /// `import.meta` expressions.
+    pub import_metas: Vec<Span>,
+    pub import_references: Vec<ImportReference>,
+    /// How often every name is referenced outside of export lists.
+    pub references: FxHashMap<String, usize>,
+    pub edits: Vec<Edit>,
+}
+
+impl ModuleInfo {
+    /// Every specifier the module depends on, in source order.
+    pub fn specifiers(&self) -> Vec<&str> {
+        self.imports
+            .iter()
+            .map(|import| import.specifier.as_str())
+            .chain(self.reexport_specifiers())
+            .chain(self.dynamic_imports.iter().map(|(_, spec)| spec.as_str()))
+            .chain(self.requires.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Specifiers the module needs before it runs, everything but dynamic imports.
+    pub fn static_specifiers(&self) -> Vec<&str> {
+        self.imports
+            .iter()
+            .map(|import| import.specifier.as_str())
+            .chain(self.reexport_specifiers())
+            .chain(self.requires.iter().map(String::as_str))
+            .collect()
+    }
+
+    fn reexport_specifiers(&self) -> impl Iterator<Item = &str> {
+        self.reexports.iter().map(|reexport| match reexport {
+            ReExport::Named { specifier, .. }
+            | ReExport::Star { specifier }
+            | ReExport::Namespace { specifier, .. } => specifier.as_str(),
+        })
+    }
+}
+
+/// Analyzes transpiled JavaScript, an ES module or a CommonJS script.
+pub fn analyze(code: &str, is_module: bool) -> Result<ModuleInfo, String> {
+    let allocator = Allocator::default();
+    let source_type = SourceType::default().with_module(is_module);
+    let ret = Parser::new(&allocator, code, source_type).parse();
+
+    if let Some(error) = ret.errors.into_iter().next() {
+        return Err(error.to_string());
+    }
+
+    let semantic = SemanticBuilder::new(code, source_type)
+        .build(&ret.program)
+        .semantic;
+    let import_symbols = ret
+        .program
+        .body
+        .iter()
+        .filter_map(|statement| match statement {
+            Statement::ImportDeclaration(decl) => decl.specifiers.as_ref(),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|specifier| {
+            let local = specifier.local();
+            Some((local.symbol_id.get()?, local.name.to_string()))
+        })
+        .collect();
+
+    let mut info = ModuleInfo::default();
+    let mut collector = Collector {
+        info: &mut info,
+        is_module,
+        symbols: semantic.symbols(),
+        import_symbols,
+        shorthand: false,
+    };
+    // A hashbang is only valid at the start of the bundle
+    if let Some(hashbang) = &ret.program.hashbang {
+        collector.remove(hashbang.span);
+    }
+    for statement in &ret.program.body {
+        collector.statement(statement);
+    }
+
+    Ok(info)
+}
+
+struct Collector<'i> {
+    info: &'i mut ModuleInfo,
+    is_module: bool,
+    symbols: &'i SymbolTable,
+    import_symbols: FxHashMap<SymbolId, String>,
+    /// Whether the identifier being visited is the value of a shorthand property.
+    shorthand: bool,
+}
+
+impl<'i> Collector<'i> {
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::ImportDeclaration(decl) => {
+                let bindings = decl
+                    .specifiers
+                    .iter()
+                    .flatten()
+                    .map(|specifier| match specifier {
+                        ImportDeclarationSpecifier::ImportSpecifier(specifier) => ImportBinding {
+                            name: ImportName::Named(export_name(&specifier.imported)),
+                            local: specifier.local.name.to_string(),
+                        },
+                        ImportDeclarationSpecifier::ImportDefaultSpecifier(specifier) => {
+                            ImportBinding {
+                                name: ImportName::Default,
+                                local: specifier.local.name.to_string(),
+                            }
+                        }
+                        ImportDeclarationSpecifier::ImportNamespaceSpecifier(specifier) => {
+                            ImportBinding {
+                                name: ImportName::Namespace,
+                                local: specifier.local.name.to_string(),
+                            }
+                        }
+                    })
+                    .collect();
+
+                self.info.imports.push(Import {
+                    specifier: decl.source.value.to_string(),
+                    bindings,
+                });
+                self.remove(decl.span);
+            }
+            Statement::ExportAllDeclaration(decl) => {
+                let specifier = decl.source.value.to_string();
+                self.info.reexports.push(match &decl.exported {
+                    Some(exported) => ReExport::Namespace {
+                        specifier,
+                        exported: export_name(exported),
+                    },
+                    None => ReExport::Star { specifier },
+                });
+                self.remove(decl.span);
+            }
+            Statement::ExportNamedDeclaration(decl) => {
+                if let Some(source) = &decl.source {
+                    for specifier in &decl.specifiers {
+                        self.info.reexports.push(ReExport::Named {
+                            specifier: source.value.to_string(),
+                            imported: export_name(&specifier.local),
+                            exported: export_name(&specifier.exported),
+                        });
+                    }
+                    self.remove(decl.span);
+                    return;
+                }
+
+                let Some(declaration) = &decl.declaration else {
+                    for specifier in &decl.specifiers {
+                        self.info.exports.push(LocalExport {
+                            exported: export_name(&specifier.exported),
+                            local: export_name(&specifier.local),
+                            removable: None,
+                        });
+                    }
+                    self.remove(decl.span);
+                    return;
+                };
+
+                let names = declared_names(declaration);
+                let removable =
+                    (names.len() == 1 && is_pure_declaration(declaration)).then_some(decl.span);
+                for name in names {
+                    self.info.exports.push(LocalExport {
+                        exported: name.clone(),
+                        local: name,
+                        removable,
+                    });
+                }
+
+                // Keep the declaration, drop the `export` keyword
+                self.info.edits.push(Edit {
+                    span: Span::new(decl.span.start, declaration.span().start),
+                    text: String::new(),
+                });
+                self.visit_statement(statement);
+            }
+            Statement::ExportDefaultDeclaration(decl) => {
+                let declaration = &decl.declaration;
+                let named = match declaration {
+                    ExportDefaultDeclarationKind::FunctionDeclaration(function) => {
+                        function.id.as_ref().map(|id| (id.name.to_string(), true))
+                    }
+                    ExportDefaultDeclarationKind::ClassDeclaration(class) => class
+                        .id
+                        .as_ref()
+                        .map(|id| (id.name.to_string(), is_pure_class(class))),
+                    _ => None,
+                };
+
+                match named {
+                    Some((name, pure)) => {
+                        self.info.exports.push(LocalExport {
+                            exported: "default".to_string(),
+                            local: name,
+                            removable: pure.then_some(decl.span),
+                        });
+                        self.info.edits.push(Edit {
+                            span: Span::new(decl.span.start, declaration.span().start),
+                            text: String::new(),
+                        });
+                    }
+                    None => {
+                        let pure = match declaration {
+                            ExportDefaultDeclarationKind::FunctionDeclaration(_) => true,
+                            ExportDefaultDeclarationKind::ClassDeclaration(class) => {
+                                is_pure_class(class)
+                            }
+                            kind => kind.as_expression().is_some_and(is_pure_expression),
+                        };
+
+                        self.info.exports.push(LocalExport {
+                            exported: "default".to_string(),
+                            local: DEFAULT_BINDING.to_string(),
+                            removable: pure.then_some(decl.span),
+                        });
+                        self.info.edits.push(Edit {
+                            span: Span::new(decl.span.start, declaration.span().start),
+                            text: format!("const {DEFAULT_BINDING} = "),
+                        });
+                        // The declaration may end without a semicolon
+                        self.info.edits.push(Edit {
+                            span: Span::new(declaration.span().end, declaration.span().end),
+                            text: ";".to_string(),
+                        });
+                    }
+                }
+
+                self.visit_statement(statement);
+            }
+            statement => self.visit_statement(statement),
+        }
+    }
+
+    fn remove(&mut self, span: Span) {
+        self.info.edits.push(Edit {
+            span,
+            text: String::new(),
+        });
+    }
+}
+
+impl<'a, 'i> Visit<'a> for Collector<'i> {
+    fn visit_identifier_reference(&mut self, ident: &IdentifierReference<'a>) {
+        *self
+            .info
+            .references
+            .entry(ident.name.to_string())
+            .or_default() += 1;
+
+        let symbol_id = ident
+            .reference_id
+            .get()
+            .and_then(|reference_id| self.symbols.get_reference(reference_id).symbol_id());
+        if let Some(local) = symbol_id.and_then(|symbol_id| self.import_symbols.get(&symbol_id)) {
+            self.info.import_references.push(ImportReference {
+                span: ident.span,
+                local: local.clone(),
+                shorthand: self.shorthand,
+            });
+        }
+    }
+
+    fn visit_object_property(&mut self, property: &ObjectProperty<'a>) {
+        let shorthand = std::mem::replace(&mut self.shorthand, property.shorthand);
+        walk::walk_object_property(self, property);
+        self.shorthand = shorthand;
+    }
+
+    fn visit_import_expression(&mut self, expr: &ImportExpression<'a>) {
+        if let Expression::StringLiteral(source) = &expr.source {
+            self.info
+                .dynamic_imports
+                .push((expr.span, source.value.to_string()));
+        }
+
+        walk::walk_import_expression(self, expr);
+    }
+
+    fn visit_meta_property(&mut self, meta: &MetaProperty<'a>) {
+        if self.is_module && meta.meta.name == "import" {
+            self.info.import_metas.push(meta.span);
+        }
+    }
+
+    fn visit_call_expression(&mut self, expr: &CallExpression<'a>) {
+        let is_require = matches!(
+            &expr.callee,
+            Expression::Identifier(ident) if ident.name.as_str() == "require"
+        );
+        if is_require && !self.is_module {
+            if let Some(Argument::StringLiteral(specifier)) = expr.arguments.first() {
+                self.info.requires.push(specifier.value.to_string());
+            }
+        }
+
+        walk::walk_call_expression(self, expr);
+    }
+}
+
+fn export_name(name: &ModuleExportName) -> String {
+    name.name().to_string()
+}
+
+fn is_pure_declaration(declaration: &Declaration) -> bool {
+    match declaration {
+        Declaration::FunctionDeclaration(_) => true,
+        Declaration::ClassDeclaration(class) => is_pure_class(class),
+        Declaration::VariableDeclaration(decl) => decl.declarations.iter().all(|declarator| {
+            is_identifier(&declarator.id)
+                && declarator.init.as_ref().map_or(true, is_pure_expression)
+        }),
+        _ => false,
+    }
+}
+
+fn is_identifier(pattern: &BindingPattern) -> bool {
+    matches!(pattern.kind, BindingPatternKind::BindingIdentifier(_))
+}
+
+fn declared_names(declaration: &Declaration) -> Vec<String> {
+    let mut names = Vec::new();
+    match declaration {
+        Declaration::VariableDeclaration(decl) => {
+            for declarator in &decl.declarations {
+                declarator
+                    .id
+                    .bound_names(&mut |ident| names.push(ident.name.to_string()));
+            }
+        }
+        Declaration::FunctionDeclaration(function) => {
+            names.extend(function.id.iter().map(|id| id.name.to_string()));
+        }
+        Declaration::ClassDeclaration(class) => {
+            names.extend(class.id.iter().map(|id| id.name.to_string()));
+        }
+        _ => {}
+    }
+
+    names
+}
+
+/// Classes whose definition can't run any code, e.g. through `static` initializers.
+fn is_pure_class(class: &Class) -> bool {
+    let pure_super = class.super_class.as_ref().map_or(true, |super_class| {
+        matches!(super_class, Expression::Identifier(_))
+    });
+    let pure_body = class.body.body.iter().all(|element| match element {
+        ClassElement::StaticBlock(_) => false,
+        ClassElement::PropertyDefinition(property) => {
+            !property.r#static || property.value.as_ref().map_or(true, is_pure_expression)
+        }
+        _ => true,
+    });
+
+    class.decorators.is_empty() && pure_super && pure_body
+}
+
+fn is_pure_expression(expression: &Expression) -> bool {
+    match expression {
+        Expression::BooleanLiteral(_)
+        | Expression::NullLiteral(_)
+        | Expression::NumericLiteral(_)
+        | Expression::BigIntLiteral(_)
+        | Expression::StringLiteral(_)
+        | Expression::Identifier(_)
+        | Expression::ArrowFunctionExpression(_)
+        | Expression::FunctionExpression(_) => true,
+        Expression::TemplateLiteral(template) => template.expressions.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze() {
+        let code = r#"import def, { a as b } from "./a.js";
+import * as ns from "@std/path";
+export { c } from "./c.js";
+export * from "./d.js";
+export const pure = () => b;
+export const effect = run();
+export function helper() { return import("./lazy.js"); }
+export default class {}
+"#;
+        let info = analyze(code, true).unwrap();
+
+        assert_eq!(
+            info.specifiers(),
+            vec!["./a.js", "@std/path", "./c.js", "./d.js", "./lazy.js"]
+        );
+        assert_eq!(
+            info.static_specifiers(),
+            vec!["./a.js", "@std/path", "./c.js", "./d.js"]
+        );
+        assert_eq!(info.imports[0].bindings[0].name, ImportName::Default);
+        assert_eq!(
+            info.imports[0].bindings[1].name,
+            ImportName::Named("a".to_string())
+        );
+        assert_eq!(info.imports[1].bindings[0].name, ImportName::Namespace);
+
+        let exports: Vec<(&str, bool)> = info
+            .exports
+            .iter()
+            .map(|export| (export.exported.as_str(), export.removable.is_some()))
+            .collect();
+        assert_eq!(
+            exports,
+            vec![
+                ("pure", true),
+                ("effect", false),
+                ("helper", true),
+                ("default", true)
+            ]
+        );
+        assert_eq!(info.references.get("b"), Some(&1));
+    }
+
+    #[test]
+    fn test_import_references() {
+        let code = r#"import { a } from "./a.js";
+console.log(a, { a });
+function shadow(a) { return a; }
+"#;
+        let info = analyze(code, true).unwrap();
+
+        let references: Vec<(&str, bool)> = info
+            .import_references
+            .iter()
+            .map(|reference| {
+                (
+                    &code[reference.span.start as usize..reference.span.end as usize],
+                    reference.shorthand,
+                )
+            })
+            .collect();
+        assert_eq!(references, vec![("a", false), ("a", true)]);
+    }
+
+    #[test]
+    fn test_analyze_commonjs() {
+        let info = analyze(
+            "const fs = require('node:fs');\nmodule.exports = require('./lib');\n",
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(info.requires, vec!["node:fs", "./lib"]);
+        assert!(info.edits.is_empty());
+
+        let info = analyze("#!/usr/bin/env node\nrequire('./cli');\n", false).unwrap();
+        assert_eq!(info.edits[0].span, Span::new(0, 19));
+    }
+}
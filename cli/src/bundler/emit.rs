@@ -0,0 +1,634 @@
+use super::{
+    analyze::{ImportName, ReExport},
+    BundleError, Dependency, GraphModule, ModuleGraph, ModuleKind,
+};
+use oxc_allocator::Allocator;
+use oxc_codegen::WhitespaceRemover;
+use oxc_mangler::ManglerBuilder;
+use oxc_parser::Parser;
+use oxc_span::{SourceType, Span};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{fmt::Write, path::Path};
+
+/// Helpers every bundle starts with. Modules are namespace objects filled with getters,
+/// CommonJS modules are loaded lazily on their first `require` and modules that are only
+/// imported dynamically on their first `import()`.
+const HELPERS: &str = r#"function __ike_namespace() {
+  return Object.create(null, { [Symbol.toStringTag]: { value: "Module" } });
+}
+function __ike_export(namespace, getters) {
+  for (const name in getters) {
+    Object.defineProperty(namespace, name, { get: getters[name], enumerable: true });
+  }
+}
+function __ike_export_star(namespace, from) {
+  for (const name of Object.keys(from)) {
+    if (name !== "default" && !(name in namespace)) {
+      Object.defineProperty(namespace, name, { get: () => from[name], enumerable: true });
+    }
+  }
+}
+function __ike_cjs(namespace, factory, requires, filename, dirname) {
+  const module = { exports: {}, filename, loaded: false };
+  let loading = false;
+  const require = (specifier) => {
+    if (!(specifier in requires)) {
+      throw new Error(`Cannot find module '${specifier}' from '${filename}'`);
+    }
+    return requires[specifier]();
+  };
+  return () => {
+    if (!loading) {
+      loading = true;
+      factory.call(module.exports, module, module.exports, require, filename, dirname);
+      module.loaded = true;
+      const exports = module.exports;
+      __ike_export(namespace, { default: () => exports });
+      if (exports !== null && (typeof exports === "object" || typeof exports === "function")) {
+        __ike_export_star(namespace, exports);
+      }
+    }
+    return module.exports;
+  };
+}
+function __ike_lazy(body) {
+  let promise;
+  return () => (promise ??= body());
+}
+async function __ike_import(inits, namespace) {
+  for (const init of inits) {
+    await init();
+  }
+  return namespace;
+}
+"#;
+
+/// Exports of a module that are imported somewhere in the bundle.
+#[derive(Debug, Clone, Default)]
+enum UsedExports {
+    #[default]
+    None,
+    Some(FxHashSet<String>),
+    All,
+}
+
+impl UsedExports {
+    fn contains(&self, name: &str) -> bool {
+        match self {
+            Self::None => false,
+            Self::Some(names) => names.contains(name),
+            Self::All => true,
+        }
+    }
+
+    /// Returns whether anything changed.
+    fn insert(&mut self, name: &str) -> bool {
+        match self {
+            Self::None => {
+                *self = Self::Some(FxHashSet::from_iter([name.to_string()]));
+                true
+            }
+            Self::Some(names) => names.insert(name.to_string()),
+            Self::All => false,
+        }
+    }
+
+    /// Returns whether anything changed.
+    fn insert_all(&mut self) -> bool {
+        let changed = !matches!(self, Self::All);
+        *self = Self::All;
+        changed
+    }
+
+    fn names(&self) -> Vec<String> {
+        match self {
+            Self::Some(names) => names.iter().cloned().collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Prints the modules of `graph` as a single ES module that exports what `entry` exports.
+pub fn emit(graph: &ModuleGraph, entry: usize, root: &Path) -> String {
+    let used = used_exports(graph, entry);
+    let mut out = String::new();
+
+    for (id, specifier) in graph.externals.iter().enumerate() {
+        writeln!(
+            out,
+            "import * as __ike_ext_{id} from {};",
+            string(specifier)
+        )
+        .unwrap();
+    }
+    out.push_str(HELPERS);
+
+    let mut eager = vec![false; graph.modules.len()];
+    for id in &graph.order {
+        eager[*id] = true;
+    }
+    let lazy: Vec<usize> = (0..graph.modules.len()).filter(|id| !eager[*id]).collect();
+
+    for id in graph.order.iter().chain(&lazy) {
+        writeln!(out, "const __ike_{id} = __ike_namespace();").unwrap();
+    }
+    // CommonJS modules only run once required, so they can be defined up front
+    for id in graph.order.iter().chain(&lazy) {
+        let module = &graph.modules[*id];
+        if module.kind == ModuleKind::CommonJs {
+            emit_commonjs(&mut out, graph, *id, root);
+        }
+    }
+    // So are the modules that only run once imported dynamically
+    for id in &lazy {
+        if graph.modules[*id].kind == ModuleKind::Esm {
+            emit_esm(&mut out, graph, *id, &used[*id], &eager, root);
+        }
+    }
+
+    for id in &graph.order {
+        let module = &graph.modules[*id];
+        match module.kind {
+            ModuleKind::Esm => emit_esm(&mut out, graph, *id, &used[*id], &eager, root),
+            ModuleKind::CommonJs => writeln!(out, "__ike_load_{id}();").unwrap(),
+        }
+    }
+
+    emit_entry_exports(&mut out, graph, entry);
+    out
+}
+
+/// Re-parses the bundle and prints it without whitespace, comments and with short names.
+pub fn minify(code: &str) -> Result<String, BundleError> {
+    let allocator = Allocator::default();
+    let source_type = SourceType::default().with_module(true);
+    let ret = Parser::new(&allocator, code, source_type).parse();
+
+    if let Some(error) = ret.errors.into_iter().next() {
+        return Err(BundleError::FailedToMinify(error.to_string()));
+    }
+
+    let mangler = ManglerBuilder::default().build(&ret.program);
+    let printed = WhitespaceRemover::new()
+        .with_mangler(Some(mangler))
+        .build(&ret.program);
+
+    Ok(printed.source_text)
+}
+
+/// Walks the imports until no module uses more exports of another one. The entry and
+/// anything imported as a namespace or dynamically keep all of their exports.
+fn used_exports(graph: &ModuleGraph, entry: usize) -> Vec<UsedExports> {
+    let mut used = vec![UsedExports::default(); graph.modules.len()];
+    used[entry].insert_all();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for (id, module) in graph.modules.iter().enumerate() {
+            let target = |spec: &str| match module.dependencies.get(spec) {
+                Some(Dependency::Module(target)) => Some(*target),
+                _ => None,
+            };
+
+            for import in &module.info.imports {
+                let Some(target) = target(&import.specifier) else {
+                    continue;
+                };
+                for binding in &import.bindings {
+                    changed |= match &binding.name {
+                        ImportName::Named(name) => used[target].insert(name),
+                        ImportName::Default => used[target].insert("default"),
+                        ImportName::Namespace => used[target].insert_all(),
+                    };
+                }
+            }
+
+            for (_, spec) in &module.info.dynamic_imports {
+                if let Some(target) = target(spec) {
+                    changed |= used[target].insert_all();
+                }
+            }
+            if module.kind == ModuleKind::CommonJs {
+                for spec in &module.info.requires {
+                    if let Some(target) = target(spec) {
+                        changed |= used[target].insert_all();
+                    }
+                }
+            }
+
+            for reexport in &module.info.reexports {
+                match reexport {
+                    ReExport::Named {
+                        specifier,
+                        imported,
+                        exported,
+                    } => {
+                        if let Some(target) = target(specifier) {
+                            if used[id].contains(exported) {
+                                changed |= used[target].insert(imported);
+                            }
+                        }
+                    }
+                    ReExport::Namespace {
+                        specifier,
+                        exported,
+                    } => {
+                        if let Some(target) = target(specifier) {
+                            if used[id].contains(exported) {
+                                changed |= used[target].insert_all();
+                            }
+                        }
+                    }
+                    // The names may come from any of the stars, so every one of them
+                    // keeps them
+                    ReExport::Star { specifier } => {
+                        let Some(target) = target(specifier) else {
+                            continue;
+                        };
+                        if matches!(used[id], UsedExports::All) {
+                            changed |= used[target].insert_all();
+                        } else {
+                            for name in used[id].names() {
+                                changed |= used[target].insert(&name);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    used
+}
+
+/// Prints an ES module as a block, or as an initializer that runs it once if it isn't one
+/// of the `eager` modules.
+fn emit_esm(
+    out: &mut String,
+    graph: &ModuleGraph,
+    id: usize,
+    used: &UsedExports,
+    eager: &[bool],
+    root: &Path,
+) {
+    let module = &graph.modules[id];
+    let info = &module.info;
+
+    // What every imported binding reads from
+    let mut imports = FxHashMap::default();
+    for import in &info.imports {
+        let namespace = namespace(module, &import.specifier);
+        for binding in &import.bindings {
+            let expression = match &binding.name {
+                ImportName::Named(name) => member(&namespace, name),
+                ImportName::Default => member(&namespace, "default"),
+                ImportName::Namespace => namespace.clone(),
+            };
+            imports.insert(binding.local.as_str(), expression);
+        }
+    }
+
+    writeln!(out, "// {}", display_key(&module.key, root)).unwrap();
+    if eager[id] {
+        out.push_str("{\n");
+    } else {
+        writeln!(out, "const __ike_init_{id} = __ike_lazy(async () => {{").unwrap();
+    }
+    // Every module keeps an `import.meta` of its own instead of sharing the bundle's
+    if !info.import_metas.is_empty() {
+        writeln!(out, "const __ike_meta_{id} = Object.create(null);").unwrap();
+    }
+
+    let mut getters = Vec::new();
+    for export in &info.exports {
+        if used.contains(&export.exported) {
+            let value = imports
+                .get(export.local.as_str())
+                .cloned()
+                .unwrap_or_else(|| export.local.clone());
+            getters.push(format!("{}: () => {value}", property_key(&export.exported)));
+        }
+    }
+    for reexport in &info.reexports {
+        match reexport {
+            ReExport::Named {
+                specifier,
+                imported,
+                exported,
+            } if used.contains(exported) => {
+                let value = member(&namespace(module, specifier), imported);
+                getters.push(format!("{}: () => {value}", property_key(exported)));
+            }
+            ReExport::Namespace {
+                specifier,
+                exported,
+            } if used.contains(exported) => {
+                let value = namespace(module, specifier);
+                getters.push(format!("{}: () => {value}", property_key(exported)));
+            }
+            _ => {}
+        }
+    }
+    if !getters.is_empty() {
+        writeln!(
+            out,
+            "__ike_export(__ike_{id}, {{ {} }});",
+            getters.join(", ")
+        )
+        .unwrap();
+    }
+    for reexport in &info.reexports {
+        if let ReExport::Star { specifier } = reexport {
+            writeln!(
+                out,
+                "__ike_export_star(__ike_{id}, {});",
+                namespace(module, specifier)
+            )
+            .unwrap();
+        }
+    }
+
+    let mut edits: Vec<(Span, String)> = info
+        .edits
+        .iter()
+        .map(|edit| (edit.span, edit.text.clone()))
+        .collect();
+
+    // Exports nothing imports, declared without side effects and unused in the module
+    for export in &info.exports {
+        let Some(span) = export.removable else {
+            continue;
+        };
+        let kept = info
+            .exports
+            .iter()
+            .any(|other| other.local == export.local && used.contains(&other.exported));
+        let referenced = info
+            .references
+            .get(&export.local)
+            .is_some_and(|count| *count > 0);
+        if !kept && !referenced {
+            edits.push((span, String::new()));
+        }
+    }
+
+    for reference in &info.import_references {
+        let Some(expression) = imports.get(reference.local.as_str()) else {
+            continue;
+        };
+        let text = if reference.shorthand {
+            format!("{}: {expression}", reference.local)
+        } else {
+            expression.clone()
+        };
+        edits.push((reference.span, text));
+    }
+
+    for span in &info.import_metas {
+        edits.push((*span, format!("__ike_meta_{id}")));
+    }
+
+    // Modules that already ran in `order` only have their namespace waited for, the
+    // others run first, along with whatever they import that didn't run yet
+    for (span, spec) in &info.dynamic_imports {
+        let text = match module.dependencies.get(spec) {
+            Some(Dependency::Module(target)) if eager[*target] => {
+                match graph.modules[*target].kind {
+                    ModuleKind::Esm => format!("Promise.resolve(__ike_{target})"),
+                    ModuleKind::CommonJs => format!(
+                        "Promise.resolve().then(() => (__ike_load_{target}(), __ike_{target}))"
+                    ),
+                }
+            }
+            Some(Dependency::Module(target)) => {
+                let mut visited = eager.to_vec();
+                let mut pending = Vec::new();
+                graph.sort(*target, &mut visited, &mut pending);
+
+                let inits: Vec<String> = pending
+                    .iter()
+                    .map(|id| match graph.modules[*id].kind {
+                        ModuleKind::Esm => format!("__ike_init_{id}"),
+                        ModuleKind::CommonJs => format!("() => void __ike_load_{id}()"),
+                    })
+                    .collect();
+                format!("__ike_import([{}], __ike_{target})", inits.join(", "))
+            }
+            Some(Dependency::External(external)) => {
+                format!("import({})", string(&graph.externals[*external]))
+            }
+            None => continue,
+        };
+        edits.push((*span, text));
+    }
+
+    out.push_str(&apply_edits(&module.code, edits));
+    out.push_str(if eager[id] { "\n}\n" } else { "\n});\n" });
+}
+
+fn emit_commonjs(out: &mut String, graph: &ModuleGraph, id: usize, root: &Path) {
+    let module = &graph.modules[id];
+    let filename = display_key(&module.key, root);
+    let dirname = Path::new(&filename)
+        .parent()
+        .map(|parent| parent.display().to_string())
+        .unwrap_or_default();
+
+    let mut requires: Vec<String> = Vec::new();
+    let mut seen = FxHashSet::default();
+    for spec in &module.info.requires {
+        if !seen.insert(spec.as_str()) {
+            continue;
+        }
+        let value = match module.dependencies.get(spec) {
+            Some(Dependency::Module(target)) => match graph.modules[*target].kind {
+                ModuleKind::Esm => format!("__ike_{target}"),
+                ModuleKind::CommonJs => format!("__ike_load_{target}()"),
+            },
+            Some(Dependency::External(external)) => format!("__ike_ext_{external}"),
+            None => continue,
+        };
+        requires.push(format!("{}: () => {value}", string(spec)));
+    }
+
+    let edits = module
+        .info
+        .edits
+        .iter()
+        .map(|edit| (edit.span, edit.text.clone()))
+        .collect();
+
+    writeln!(out, "// {filename}").unwrap();
+    writeln!(
+        out,
+        "const __ike_load_{id} = __ike_cjs(__ike_{id}, function (module, exports, require, __filename, __dirname) {{"
+    )
+    .unwrap();
+    out.push_str(&apply_edits(&module.code, edits));
+    writeln!(
+        out,
+        "\n}}, {{ {} }}, {}, {});",
+        requires.join(", "),
+        string(&filename),
+        string(&dirname)
+    )
+    .unwrap();
+}
+
+/// `export { a, b as c }` of the entry, read from its namespace once it ran.
+fn emit_entry_exports(out: &mut String, graph: &ModuleGraph, entry: usize) {
+    let mut names = Vec::new();
+    export_names(graph, entry, &mut names, &mut FxHashSet::default());
+    if graph.modules[entry].kind == ModuleKind::CommonJs {
+        names.push("default".to_string());
+    }
+
+    if names.is_empty() {
+        return;
+    }
+
+    let bindings: Vec<String> = names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| format!("{}: __ike_entry_{index}", property_key(name)))
+        .collect();
+    let specifiers: Vec<String> = names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| format!("__ike_entry_{index} as {}", property_key(name)))
+        .collect();
+
+    writeln!(out, "const {{ {} }} = __ike_{entry};", bindings.join(", ")).unwrap();
+    writeln!(out, "export {{ {} }};", specifiers.join(", ")).unwrap();
+}
+
+/// Names an ES module exports, following `export *` through the bundled modules.
+fn export_names(
+    graph: &ModuleGraph,
+    id: usize,
+    names: &mut Vec<String>,
+    visited: &mut FxHashSet<usize>,
+) {
+    if !visited.insert(id) {
+        return;
+    }
+
+    let module = &graph.modules[id];
+    let mut add = |name: &str| {
+        if !names.iter().any(|existing| existing == name) {
+            names.push(name.to_string());
+        }
+    };
+    for export in &module.info.exports {
+        add(&export.exported);
+    }
+    let mut stars = Vec::new();
+    for reexport in &module.info.reexports {
+        match reexport {
+            ReExport::Named { exported, .. } | ReExport::Namespace { exported, .. } => {
+                add(exported)
+            }
+            ReExport::Star { specifier } => stars.push(specifier),
+        }
+    }
+
+    for specifier in stars {
+        if let Some(Dependency::Module(target)) = module.dependencies.get(specifier) {
+            let mut star_names = Vec::new();
+            export_names(graph, *target, &mut star_names, visited);
+            for name in star_names.into_iter().filter(|name| name != "default") {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+    }
+}
+
+/// Applies the edits in source order. Edits inside of a removed range are dropped.
+pub fn apply_edits(code: &str, mut edits: Vec<(Span, String)>) -> String {
+    edits.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+
+    let mut result = String::with_capacity(code.len());
+    let mut position = 0;
+    for (span, text) in edits {
+        let (start, end) = (span.start as usize, span.end as usize);
+        if start < position {
+            continue;
+        }
+
+        result.push_str(&code[position..start]);
+        result.push_str(&text);
+        position = end;
+    }
+    result.push_str(&code[position..]);
+
+    result
+}
+
+/// Object a specifier imported by `module` evaluates to.
+fn namespace(module: &GraphModule, specifier: &str) -> String {
+    match module.dependencies.get(specifier) {
+        Some(Dependency::Module(target)) => format!("__ike_{target}"),
+        Some(Dependency::External(external)) => format!("__ike_ext_{external}"),
+        // Unreachable for ES modules, their imports fail to bundle when unresolved
+        None => "undefined".to_string(),
+    }
+}
+
+fn member(object: &str, name: &str) -> String {
+    if is_identifier_name(name) {
+        format!("{object}.{name}")
+    } else {
+        format!("{object}[{}]", string(name))
+    }
+}
+
+fn property_key(name: &str) -> String {
+    if is_identifier_name(name) {
+        name.to_string()
+    } else {
+        string(name)
+    }
+}
+
+fn is_identifier_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|char| char.is_alphabetic() || char == '_' || char == '$')
+        && chars.all(|char| char.is_alphanumeric() || char == '_' || char == '$')
+}
+
+/// JSON strings are valid JS string literals.
+fn string(text: &str) -> String {
+    serde_json::to_string(text).unwrap()
+}
+
+/// Paths relative to the root, so bundles don't contain where they were built.
+fn display_key(key: &str, root: &Path) -> String {
+    let path = Path::new(key);
+    match path.strip_prefix(root) {
+        Ok(relative) => relative.display().to_string(),
+        Err(_) => key.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_edits() {
+        let code = "export const a = 1;\nexport const b = a;\n";
+        let edits = vec![
+            (Span::new(0, 7), String::new()),
+            (Span::new(0, 19), String::new()),
+            (Span::new(20, 27), String::new()),
+        ];
+
+        assert_eq!(apply_edits(code, edits), "\nconst b = a;\n");
+    }
+}
@@ -0,0 +1,380 @@
+pub mod analyze;
+pub mod emit;
+
+use crate::{
+    remote::RemoteCache,
+    runtime::{
+        commonjs::is_commonjs,
+        data_module::{split_import_type, ImportType},
+        modules::{IkeModuleLoader, ResolvedSpecifier},
+    },
+    transpiler::{transpile_file, transpile_source},
+};
+use analyze::{analyze, ModuleInfo};
+use rustc_hash::FxHashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum BundleError {
+    #[error("Failed to resolve <cyan>{0}<r> from <cyan>{1}<r>: {2}")]
+    FailedToResolve(String, String, String),
+    #[error("Failed to load <cyan>{0}<r>: {1}")]
+    FailedToLoad(String, String),
+    #[error("Failed to parse <cyan>{0}<r>: {1}")]
+    FailedToParse(String, String),
+    #[error("Failed to minify the bundle: {0}")]
+    FailedToMinify(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BundleOptions {
+    pub minify: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleKind {
+    Esm,
+    CommonJs,
+}
+
+/// Where an import of a bundled module points to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dependency {
+    /// Index of a module in the bundle.
+    Module(usize),
+    /// Index of an external specifier, imported by the bundle as it is.
+    External(usize),
+}
+
+#[derive(Debug)]
+pub struct GraphModule {
+    /// Path, URL or data module key the module was loaded from.
+    pub key: String,
+    pub kind: ModuleKind,
+    /// Transpiled JavaScript.
+    pub code: String,
+    pub info: ModuleInfo,
+    /// Specifier as written in the code to what it resolved to.
+    pub dependencies: FxHashMap<String, Dependency>,
+}
+
+/// Every module reachable from an entry, with builtins (`@std/*`, `node:*`) and
+/// runtime modules (`module:*`) left external.
+#[derive(Debug, Default)]
+pub struct ModuleGraph {
+    pub modules: Vec<GraphModule>,
+    pub externals: Vec<String>,
+    /// Modules the bundle runs when it loads, dependencies before their importers.
+    /// Modules that are only imported dynamically are left out, they run on their first
+    /// `import()`.
+    pub order: Vec<usize>,
+}
+
+impl ModuleGraph {
+    /// Depth-first post order over everything `id` imports statically, so every module
+    /// runs after its dependencies. Cycles run in the order they were reached, like the
+    /// runtime does. Modules already `visited` are skipped.
+    pub fn sort(&self, id: usize, visited: &mut [bool], order: &mut Vec<usize>) {
+        if visited[id] {
+            return;
+        }
+        visited[id] = true;
+
+        let module = &self.modules[id];
+        let mut dependencies: Vec<usize> = module
+            .info
+            .static_specifiers()
+            .into_iter()
+            .filter_map(|spec| match module.dependencies.get(spec) {
+                Some(Dependency::Module(dependency)) => Some(*dependency),
+                _ => None,
+            })
+            .collect();
+        dependencies.dedup();
+
+        for dependency in dependencies {
+            self.sort(dependency, visited, order);
+        }
+        order.push(id);
+    }
+}
+
+pub struct Bundler {
+    loader: IkeModuleLoader,
+    remote: RemoteCache,
+    root: PathBuf,
+    graph: ModuleGraph,
+    ids: FxHashMap<String, usize>,
+    external_ids: FxHashMap<String, usize>,
+}
+
+impl Bundler {
+    pub fn new(root: PathBuf) -> Result<Self, BundleError> {
+        let loader = IkeModuleLoader::new(&root).map_err(|err| {
+            BundleError::FailedToLoad(root.display().to_string(), err.to_string())
+        })?;
+
+        Ok(Self {
+            loader,
+            remote: RemoteCache::default(),
+            root,
+            graph: ModuleGraph::default(),
+            ids: FxHashMap::default(),
+            external_ids: FxHashMap::default(),
+        })
+    }
+
+    /// Bundles `entry` and everything it imports into a single ES module.
+    pub fn bundle(mut self, entry: &Path, options: &BundleOptions) -> Result<String, BundleError> {
+        let entry = self.add_file(entry.to_path_buf(), None)?;
+
+        let mut visited = vec![false; self.graph.modules.len()];
+        let mut order = Vec::new();
+        self.graph.sort(entry, &mut visited, &mut order);
+        self.graph.order = order;
+
+        let code = emit::emit(&self.graph, entry, &self.root);
+        if options.minify {
+            return emit::minify(&code);
+        }
+
+        Ok(code)
+    }
+
+    fn add_file(
+        &mut self,
+        file: PathBuf,
+        import_type: Option<ImportType>,
+    ) -> Result<usize, BundleError> {
+        let display = file.display().to_string();
+        let import_type = import_type.or_else(|| ImportType::from_extension(&file));
+        let key = match import_type {
+            Some(import_type) => import_type.module_key(&file),
+            None => display.clone(),
+        };
+        if let Some(id) = self.ids.get(&key) {
+            return Ok(*id);
+        }
+
+        let failed = |err: &dyn std::fmt::Display| {
+            BundleError::FailedToLoad(display.clone(), err.to_string())
+        };
+        let (kind, code) = if let Some(import_type) = import_type {
+            let content = std::fs::read(&file).map_err(|err| failed(&err))?;
+            let code = import_type
+                .module_source(&content, &file)
+                .map_err(|err| failed(&err))?;
+            (ModuleKind::Esm, code)
+        } else if is_commonjs(&file) {
+            let code = match file.extension().and_then(|ext| ext.to_str()) {
+                Some("ts" | "cts" | "tsx") => {
+                    transpile_file(&file).map_err(|err| failed(&err))?.code
+                }
+                _ => std::fs::read_to_string(&file).map_err(|err| failed(&err))?,
+            };
+            (ModuleKind::CommonJs, code)
+        } else {
+            let code = transpile_file(&file).map_err(|err| failed(&err))?.code;
+            (ModuleKind::Esm, code)
+        };
+
+        self.add_module(key, &file, kind, code)
+    }
+
+    fn add_remote(
+        &mut self,
+        url: url::Url,
+        import_type: Option<ImportType>,
+    ) -> Result<usize, BundleError> {
+        let import_type = import_type.or_else(|| ImportType::from_extension(Path::new(url.path())));
+        let key = match import_type {
+            Some(import_type) => import_type.module_key(Path::new(url.as_str())),
+            None => url.to_string(),
+        };
+        if let Some(id) = self.ids.get(&key) {
+            return Ok(*id);
+        }
+
+        let failed = |err: &dyn std::fmt::Display| {
+            BundleError::FailedToLoad(url.to_string(), err.to_string())
+        };
+        let remote = self
+            .remote
+            .load(&url, |_| true)
+            .map_err(|err| failed(&err))?;
+        // Relative imports resolve against the URL after redirects
+        let path = PathBuf::from(remote.url.as_str());
+
+        let code = match import_type {
+            Some(import_type) => import_type
+                .module_source(remote.source.as_bytes(), &path)
+                .map_err(|err| failed(&err))?,
+            None => {
+                transpile_source(&remote.virtual_path(), &remote.source)
+                    .map_err(|err| failed(&err))?
+                    .code
+            }
+        };
+
+        self.add_module(key, &path, ModuleKind::Esm, code)
+    }
+
+    fn add_module(
+        &mut self,
+        key: String,
+        path: &Path,
+        kind: ModuleKind,
+        code: String,
+    ) -> Result<usize, BundleError> {
+        let info = analyze(&code, kind == ModuleKind::Esm)
+            .map_err(|err| BundleError::FailedToParse(key.clone(), err))?;
+        let specifiers: Vec<String> = info.specifiers().into_iter().map(String::from).collect();
+
+        let id = self.graph.modules.len();
+        self.ids.insert(key.clone(), id);
+        self.graph.modules.push(GraphModule {
+            key,
+            kind,
+            code,
+            info,
+            dependencies: FxHashMap::default(),
+        });
+
+        for specifier in specifiers {
+            if self.graph.modules[id].dependencies.contains_key(&specifier) {
+                continue;
+            }
+
+            let dependency = match self.resolve(&specifier, path) {
+                Ok(dependency) => dependency,
+                // A missing module may be guarded by a `try`, it throws once required
+                Err(_) if kind == ModuleKind::CommonJs => continue,
+                Err(err) => return Err(err),
+            };
+            self.graph.modules[id]
+                .dependencies
+                .insert(specifier, dependency);
+        }
+
+        Ok(id)
+    }
+
+    fn resolve(&mut self, specifier: &str, referrer: &Path) -> Result<Dependency, BundleError> {
+        let failed = |err: &dyn std::fmt::Display| {
+            BundleError::FailedToResolve(
+                specifier.to_string(),
+                referrer.display().to_string(),
+                err.to_string(),
+            )
+        };
+        let (import_type, spec) = split_import_type(specifier).map_err(|err| failed(&err))?;
+
+        if spec.starts_with("module:") {
+            return Ok(self.external(&spec));
+        }
+
+        let resolved = self
+            .loader
+            .resolve_specifier(&spec, Some(referrer))
+            .map_err(|err| failed(&err))?;
+        match resolved {
+            ResolvedSpecifier::Builtin(name) => Ok(self.external(&name)),
+            ResolvedSpecifier::Remote(url) => {
+                self.add_remote(url, import_type).map(Dependency::Module)
+            }
+            ResolvedSpecifier::File(file) => {
+                self.add_file(file, import_type).map(Dependency::Module)
+            }
+        }
+    }
+
+    fn external(&mut self, specifier: &str) -> Dependency {
+        let next = self.graph.externals.len();
+        let id = *self
+            .external_ids
+            .entry(specifier.to_string())
+            .or_insert(next);
+        if id == next {
+            self.graph.externals.push(specifier.to_string());
+        }
+
+        Dependency::External(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use boa_engine::{builtins::promise::PromiseState, js_string, Context, Module, Source};
+    use std::rc::Rc;
+
+    #[test]
+    fn test_bundle() {
+        let dir = std::env::temp_dir().join(format!("ike-bundle-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let files = [
+            (
+                "main.ts",
+                "import { used } from './lib.ts';\nimport { twice, label } from './reexports.ts';\nimport legacy from './legacy.cjs';\nimport { greeting } from 'module:test/greeting.js';\n\nconst ran = typeof globalThis.lazyRuns;\nconst lazy = await import('./lazy.ts');\nawait import('./lazy.ts');\nexport const result = [used(), twice(2), label, legacy.add(1, 2), greeting, lazy.value, import.meta === lazy.meta, ran, globalThis.lazyRuns].join(',');\n",
+            ),
+            (
+                "lib.ts",
+                "export const used = (): string => 'used';\nexport const unused = () => 'unused';\nexport function dropped() {\n  return 'dropped';\n}\n",
+            ),
+            (
+                "reexports.ts",
+                "export * from './math.ts';\nexport { label } from './labels.ts';\n",
+            ),
+            ("math.ts", "export const twice = (x: number) => x * 2;\n"),
+            ("labels.ts", "export const label = 'label';\n"),
+            ("legacy.cjs", "exports.add = (a, b) => a + b;\n"),
+            // Only imported dynamically, so neither of them runs before the `import()`
+            (
+                "lazy.ts",
+                "export { value } from './lazy-value.ts';\nexport const meta = import.meta;\nglobalThis.lazyRuns = (globalThis.lazyRuns ?? 0) + 1;\n",
+            ),
+            ("lazy-value.ts", "export const value = 'lazy';\n"),
+        ];
+        for (name, source) in files {
+            std::fs::write(dir.join(name), source).unwrap();
+        }
+
+        let code = Bundler::new(dir.clone())
+            .unwrap()
+            .bundle(&dir.join("main.ts"), &BundleOptions::default())
+            .unwrap();
+        assert!(!code.contains("'unused'") && !code.contains("\"unused\""));
+        assert!(!code.contains("dropped"));
+        assert!(code.contains("import * as __ike_ext_0 from \"module:test/greeting.js\";"));
+
+        // The bundle runs on its own, only the external is provided by the loader
+        let loader = Rc::new(IkeModuleLoader::new(&dir).unwrap());
+        let ctx = &mut Context::builder()
+            .module_loader(loader.clone())
+            .build()
+            .unwrap();
+        let greeting = Source::from_bytes(b"export const greeting = 'hello';");
+        loader.insert(
+            PathBuf::from("module:test/greeting.js"),
+            Module::parse(greeting, None, ctx).unwrap(),
+        );
+
+        let bundle = dir.join("bundle.js");
+        let source = Source::from_bytes(code.as_bytes()).with_path(&bundle);
+        let module = Module::parse(source, None, ctx).unwrap();
+        let promise = module.load_link_evaluate(ctx);
+        ctx.run_jobs();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(promise.state(), PromiseState::Fulfilled(_)));
+        let result = module
+            .namespace(ctx)
+            .get(js_string!("result"), ctx)
+            .unwrap();
+        assert_eq!(
+            result.to_string(ctx).unwrap().to_std_string_escaped(),
+            "used,4,label,3,hello,lazy,false,undefined,1"
+        );
+    }
+}
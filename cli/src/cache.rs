@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+
+/// Environment variable that overrides the global cache location.
+pub const CACHE_DIR_ENV: &str = "IKE_CACHE_DIR";
+
+/// Returns the directory ike stores global caches in.
+///
+/// Defaults to the platform cache directory (`%LOCALAPPDATA%\ike` on Windows,
+/// `~/Library/Caches/ike` on macOS, `$XDG_CACHE_HOME/ike` or `~/.cache/ike` elsewhere).
+pub fn cache_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os(CACHE_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(dir);
+    }
+
+    let home = || {
+        std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(PathBuf::from)
+    };
+
+    let base = if cfg!(windows) {
+        std::env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library").join("Caches"))
+    } else {
+        std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".cache")))
+    };
+
+    base.unwrap_or_else(std::env::temp_dir).join("ike")
+}
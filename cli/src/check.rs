@@ -0,0 +1,531 @@
+use crate::{
+    cache::cache_dir, globals::VERSION, resolver::MODULES_DIR, runtime::errors::print_code_frame,
+    transpiler::code_frame, which::which,
+};
+use ike_logger::{elog, escape, Logger};
+use ike_toml::{CompilerConfig, EsTarget, JsxRuntime};
+use regex::Regex;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::LazyLock,
+};
+use thiserror::Error;
+
+/// Declarations of the runtime, written to disk with the same layout as in the repository
+/// so the relative imports between them keep working.
+const DECLARATIONS: [(&str, &str); 12] = [
+    (
+        "packages/types/src/index.d.ts",
+        include_str!("../../packages/types/src/index.d.ts"),
+    ),
+    (
+        "packages/types/src/console.d.ts",
+        include_str!("../../packages/types/src/console.d.ts"),
+    ),
+    (
+        "packages/types/src/modules/_internal_.d.ts",
+        include_str!("../../packages/types/src/modules/_internal_.d.ts"),
+    ),
+    (
+        "packages/types/src/modules/assert.d.ts",
+        include_str!("../../packages/types/src/modules/assert.d.ts"),
+    ),
+    (
+        "packages/types/src/modules/buffer.d.ts",
+        include_str!("../../packages/types/src/modules/buffer.d.ts"),
+    ),
+    (
+        "packages/types/src/modules/format.d.ts",
+        include_str!("../../packages/types/src/modules/format.d.ts"),
+    ),
+    (
+        "packages/types/src/modules/inspect.d.ts",
+        include_str!("../../packages/types/src/modules/inspect.d.ts"),
+    ),
+    (
+        "packages/types/src/modules/path.d.ts",
+        include_str!("../../packages/types/src/modules/path.d.ts"),
+    ),
+    (
+        "packages/types/src/modules/test.d.ts",
+        include_str!("../../packages/types/src/modules/test.d.ts"),
+    ),
+    (
+        "packages/types/src/modules/uuid.d.ts",
+        include_str!("../../packages/types/src/modules/uuid.d.ts"),
+    ),
+    (
+        "modules/web/lib.web.d.ts",
+        include_str!("../../modules/web/lib.web.d.ts"),
+    ),
+    (
+        "modules/fs/lib.fs.d.ts",
+        include_str!("../../modules/fs/lib.fs.d.ts"),
+    ),
+];
+
+/// Modules ike loads that have no declarations yet, typed as `any` instead of failing.
+const UNTYPED_MODULES: &str =
+    "declare module 'node:*';\ndeclare module 'https://*';\ndeclare module 'http://*';\n";
+
+/// `file(line,column): error TS1234: message`, as printed by `tsc --pretty false`.
+static DIAGNOSTIC: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:(?P<file>.+)\((?P<line>\d+),(?P<column>\d+)\): )?(?P<category>error|warning|message) (?P<code>TS\d+): (?P<message>.*)$").unwrap()
+});
+
+#[derive(Debug, Error)]
+pub enum CheckError {
+    #[error("TypeScript is needed to type-check, install it with <cyan>ike add -D typescript<r>")]
+    TypeScriptNotFound,
+    #[error("<cyan>node<r> is needed to run the TypeScript installed in <cyan>ike_modules<r> but it wasn't found in PATH")]
+    NodeNotFound,
+    #[error("Failed to prepare type declarations: {0}")]
+    FailedToWriteDeclarations(String),
+    #[error("Failed to run <cyan>{0}<r>: {1}")]
+    FailedToRun(PathBuf, String),
+    #[error("Found {0} type error{}", if *.0 == 1 { "" } else { "s" })]
+    TypeErrors(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub file: Option<PathBuf>,
+    /// One based line, zero for diagnostics without a location.
+    pub line: usize,
+    pub column: usize,
+    pub is_error: bool,
+    pub code: String,
+    pub message: String,
+}
+
+/// Type-checks TypeScript with `tsc` against the declarations of the runtime.
+#[derive(Debug, Clone)]
+pub struct Checker {
+    root: PathBuf,
+    config: CompilerConfig,
+    /// `[imports]` of ike.toml, mapped to `paths` so tsc resolves the aliases too.
+    imports: HashMap<String, String>,
+    /// Directory relative import targets are resolved against, the one of ike.toml.
+    imports_dir: PathBuf,
+}
+
+impl Checker {
+    pub fn new(root: PathBuf, config: CompilerConfig) -> Self {
+        Self {
+            root,
+            config,
+            imports: HashMap::new(),
+            imports_dir: PathBuf::new(),
+        }
+    }
+
+    pub fn with_imports(mut self, imports: HashMap<String, String>, dir: PathBuf) -> Self {
+        self.imports = imports;
+        self.imports_dir = dir;
+        self
+    }
+
+    /// Checks `files` and everything they import, or every file of the project when
+    /// no files are given.
+    pub fn check(&self, files: &[PathBuf]) -> Result<Vec<Diagnostic>, CheckError> {
+        let tsc = self.tsc().ok_or(CheckError::TypeScriptNotFound)?;
+        let dir = self.write_declarations()?;
+
+        let tsconfig_path = dir.join(format!("tsconfig.{}.json", std::process::id()));
+        let tsconfig = serde_json::to_string_pretty(&self.tsconfig(&dir, files))
+            .map_err(|e| CheckError::FailedToWriteDeclarations(e.to_string()))?;
+        std::fs::write(&tsconfig_path, tsconfig)
+            .map_err(|e| CheckError::FailedToWriteDeclarations(e.to_string()))?;
+
+        // TypeScript installed with `ike add` has no bin link, its script runs with node
+        let mut command = if tsc.extension().is_some_and(|extension| extension == "js") {
+            let node =
+                which("node", None, Some(self.root.clone())).ok_or(CheckError::NodeNotFound)?;
+            let mut command = Command::new(node);
+            command.arg(&tsc);
+            command
+        } else {
+            Command::new(&tsc)
+        };
+
+        let output = command
+            .arg("--project")
+            .arg(&tsconfig_path)
+            .arg("--pretty")
+            .arg("false")
+            .current_dir(&self.root)
+            .output();
+        let _ = std::fs::remove_file(&tsconfig_path);
+
+        let output = output.map_err(|e| CheckError::FailedToRun(tsc.clone(), e.to_string()))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let diagnostics = parse_diagnostics(&stdout);
+
+        // tsc exits with an error for broken configs too, those print no diagnostics
+        if !output.status.success() && diagnostics.is_empty() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = if stderr.trim().is_empty() {
+                stdout.trim().to_string()
+            } else {
+                stderr.trim().to_string()
+            };
+
+            return Err(CheckError::FailedToRun(tsc, reason));
+        }
+
+        Ok(diagnostics)
+    }
+
+    /// `tsc` of the project from node_modules or ike_modules, falling back to one on
+    /// the PATH.
+    fn tsc(&self) -> Option<PathBuf> {
+        let bin = if cfg!(windows) { "tsc.cmd" } else { "tsc" };
+        let local = self.root.ancestors().find_map(|dir| {
+            [
+                dir.join("node_modules").join(".bin").join(bin),
+                dir.join(MODULES_DIR).join("typescript/lib/tsc.js"),
+            ]
+            .into_iter()
+            .find(|tsc| tsc.is_file())
+        });
+        if local.is_some() {
+            return local;
+        }
+
+        which("tsc", None, Some(self.root.clone()))
+    }
+
+    /// `paths` resolving bare specifiers to installed packages and the `[imports]`
+    /// aliases to their targets. Targets are absolute, so `baseUrl` doesn't matter.
+    fn paths(&self) -> serde_json::Map<String, Value> {
+        let path = |path: &Path| path.to_string_lossy().replace('\\', "/");
+        let modules_dir = self
+            .root
+            .ancestors()
+            .map(|dir| dir.join(MODULES_DIR))
+            .find(|dir| dir.is_dir());
+
+        let target = |target: &str| {
+            if target.starts_with("./") || target.starts_with("../") || target.starts_with('/') {
+                Some(path(&self.imports_dir.join(target)))
+            } else if target.contains(':') {
+                // URLs and builtins like `node:fs` are declared by ike.d.ts
+                None
+            } else {
+                modules_dir.as_ref().map(|dir| path(&dir.join(target)))
+            }
+        };
+
+        let mut paths = serde_json::Map::new();
+        let mut imports: Vec<_> = self.imports.iter().collect();
+        imports.sort();
+        for (key, value) in imports {
+            let Some(value) = target(value) else {
+                continue;
+            };
+            // Prefix entries map everything that starts with them
+            match key.strip_suffix('/') {
+                Some(prefix) => paths.insert(format!("{prefix}/*"), json!([format!("{value}*")])),
+                None => paths.insert(key.clone(), json!([value])),
+            };
+        }
+        if let Some(modules_dir) = &modules_dir {
+            paths.insert("*".into(), json!([format!("{}/*", path(modules_dir))]));
+        }
+
+        paths
+    }
+
+    /// Writes the declarations once per ike version and returns their directory.
+    fn write_declarations(&self) -> Result<PathBuf, CheckError> {
+        let dir = cache_dir().join("types").join(VERSION);
+        let failed = |e: std::io::Error| CheckError::FailedToWriteDeclarations(e.to_string());
+
+        for (name, content) in DECLARATIONS
+            .into_iter()
+            .chain([("ike.d.ts", UNTYPED_MODULES)])
+        {
+            let path = dir.join(name);
+            if path.is_file() {
+                continue;
+            }
+
+            std::fs::create_dir_all(path.parent().unwrap_or(&dir)).map_err(failed)?;
+            std::fs::write(&path, content).map_err(failed)?;
+        }
+
+        Ok(dir)
+    }
+
+    fn tsconfig(&self, dir: &Path, files: &[PathBuf]) -> Value {
+        let path = |path: &Path| path.to_string_lossy().replace('\\', "/");
+        let root = path(&self.root);
+
+        let mut compiler_options = json!({
+            "target": "ESNext",
+            "lib": ["ESNext"],
+            "module": "ESNext",
+            "moduleResolution": "bundler",
+            "moduleDetection": "force",
+            "allowJs": true,
+            "allowImportingTsExtensions": true,
+            "resolveJsonModule": true,
+            "noEmit": true,
+            "strict": true,
+            "skipLibCheck": true,
+            "types": [],
+            "jsx": "react-jsx",
+        });
+        let options = compiler_options.as_object_mut().unwrap();
+
+        if self.config.jsx == Some(JsxRuntime::Classic) {
+            options.insert("jsx".into(), json!("react"));
+        }
+        if let Some(source) = &self.config.jsx_import_source {
+            options.insert("jsxImportSource".into(), json!(source));
+        }
+        if let Some(pragma) = &self.config.jsx_pragma {
+            options.insert("jsxFactory".into(), json!(pragma));
+        }
+        if let Some(pragma_frag) = &self.config.jsx_pragma_frag {
+            options.insert("jsxFragmentFactory".into(), json!(pragma_frag));
+        }
+        if let Some(target) = self.config.target {
+            options.insert("target".into(), json!(target_name(target)));
+        }
+
+        let paths = self.paths();
+        if !paths.is_empty() {
+            options.insert("baseUrl".into(), json!(root));
+            options.insert("paths".into(), Value::Object(paths));
+        }
+
+        let mut declarations = vec![
+            path(&dir.join("packages/types/src/index.d.ts")),
+            path(&dir.join("packages/types/src/modules/uuid.d.ts")),
+            path(&dir.join("ike.d.ts")),
+        ];
+
+        if files.is_empty() {
+            json!({
+                "compilerOptions": compiler_options,
+                "files": declarations,
+                "include": [
+                    format!("{root}/**/*.ts"),
+                    format!("{root}/**/*.mts"),
+                    format!("{root}/**/*.cts"),
+                    format!("{root}/**/*.tsx"),
+                ],
+                "exclude": [
+                    format!("{root}/**/node_modules"),
+                    format!("{root}/**/ike_modules"),
+                    format!("{root}/**/target"),
+                ],
+            })
+        } else {
+            declarations.extend(files.iter().map(|file| path(file)));
+
+            json!({
+                "compilerOptions": compiler_options,
+                "files": declarations,
+            })
+        }
+    }
+}
+
+fn target_name(target: EsTarget) -> &'static str {
+    match target {
+        EsTarget::Es2022 => "ES2022",
+        EsTarget::Es2023 => "ES2023",
+        EsTarget::Es2024 => "ES2024",
+        EsTarget::EsNext => "ESNext",
+    }
+}
+
+/// Parses the output of `tsc --pretty false`. Indented lines continue the message of the
+/// diagnostic before them.
+pub fn parse_diagnostics(output: &str) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+    for line in output.lines() {
+        if line.starts_with(' ') {
+            if let Some(last) = diagnostics.last_mut() {
+                last.message.push('\n');
+                last.message.push_str(line);
+            }
+            continue;
+        }
+
+        let Some(captures) = DIAGNOSTIC.captures(line.trim_end()) else {
+            continue;
+        };
+        let number = |name: &str| {
+            captures
+                .name(name)
+                .and_then(|value| value.as_str().parse().ok())
+                .unwrap_or(0)
+        };
+
+        diagnostics.push(Diagnostic {
+            file: captures
+                .name("file")
+                .map(|file| PathBuf::from(file.as_str())),
+            line: number("line"),
+            column: number("column"),
+            is_error: &captures["category"] == "error",
+            code: captures["code"].to_string(),
+            message: captures["message"].to_string(),
+        });
+    }
+
+    diagnostics
+}
+
+/// Prints a diagnostic with the code frame of its location, paths relative to `root`.
+pub fn print_diagnostic(diagnostic: &Diagnostic, root: &Path) {
+    let (label, color) = if diagnostic.is_error {
+        ("error", "red")
+    } else {
+        ("warning", "yellow")
+    };
+    let mut lines = diagnostic.message.lines();
+
+    elog!(
+        "<r><{}>{}<r><d>({})<r>: {}",
+        color,
+        label,
+        diagnostic.code,
+        escape(lines.next().unwrap_or_default())
+    );
+    for line in lines {
+        elog!("  {}", escape(line));
+    }
+
+    let Some(file) = &diagnostic.file else {
+        return;
+    };
+    let file = root.join(file);
+    let display = file.strip_prefix(root).unwrap_or(&file);
+    elog!(
+        "  <d>at {}:{}:{}<r>",
+        escape(&display.display().to_string()),
+        diagnostic.line,
+        diagnostic.column
+    );
+
+    if let Ok(source_text) = std::fs::read_to_string(&file) {
+        let frame = code_frame(&source_text, diagnostic.line, diagnostic.column, 1);
+        print_code_frame(&frame, "");
+    }
+}
+
+/// Prints `diagnostics` and fails when any of them is an error.
+pub fn report(diagnostics: &[Diagnostic], root: &Path) -> Result<(), CheckError> {
+    for diagnostic in diagnostics {
+        print_diagnostic(diagnostic, root);
+        elog!("");
+    }
+
+    match diagnostics.iter().filter(|d| d.is_error).count() {
+        0 => Ok(()),
+        errors => Err(CheckError::TypeErrors(errors)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let output = "src/main.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+src/main.ts(8,1): error TS2345: Argument of type 'number' is not assignable to parameter of type 'string'.\n  Details about the error.\n\
+error TS5023: Unknown compiler option 'foo'.\n";
+
+        let diagnostics = parse_diagnostics(output);
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(
+            diagnostics[0],
+            Diagnostic {
+                file: Some(PathBuf::from("src/main.ts")),
+                line: 3,
+                column: 7,
+                is_error: true,
+                code: "TS2322".to_string(),
+                message: "Type 'string' is not assignable to type 'number'.".to_string(),
+            }
+        );
+        assert_eq!(
+            diagnostics[1].message,
+            "Argument of type 'number' is not assignable to parameter of type 'string'.\n  Details about the error."
+        );
+        assert_eq!(diagnostics[2].file, None);
+        assert_eq!(diagnostics[2].code, "TS5023");
+    }
+
+    #[test]
+    fn test_tsconfig_paths() {
+        let dir = std::env::temp_dir().join(format!("ike-check-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("ike_modules/greet")).unwrap();
+
+        let imports = HashMap::from([
+            ("@/".to_string(), "./src/".to_string()),
+            ("greeting".to_string(), "greet".to_string()),
+            ("fs".to_string(), "node:fs".to_string()),
+        ]);
+        let checker =
+            Checker::new(dir.clone(), CompilerConfig::default()).with_imports(imports, dir.clone());
+        let root = dir.to_string_lossy().replace('\\', "/");
+
+        let tsconfig = checker.tsconfig(&dir, &[]);
+        let options = &tsconfig["compilerOptions"];
+        assert_eq!(options["baseUrl"], json!(root));
+        assert_eq!(
+            options["paths"],
+            json!({
+                "@/*": [format!("{root}/./src/*")],
+                "greeting": [format!("{root}/ike_modules/greet")],
+                "*": [format!("{root}/ike_modules/*")],
+            })
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_check() {
+        let dir = std::env::temp_dir().join(format!("ike-check-run-test-{}", std::process::id()));
+        let checker = Checker::new(dir.clone(), CompilerConfig::default())
+            .with_imports(HashMap::from([("@/".into(), "./src/".into())]), dir.clone());
+
+        // Only runs where TypeScript is installed
+        if checker.tsc().is_none() {
+            return;
+        }
+
+        let files = [
+            ("ike_modules/greet/package.json", r#"{ "name": "greet", "types": "index.d.ts" }"#),
+            ("ike_modules/greet/index.d.ts", "export function greet(name: string): string;"),
+            ("src/answer.ts", "export const answer: number = 42;"),
+            (
+                "main.ts",
+                "import { greet } from 'greet';\nimport { answer } from '@/answer.ts';\n\nconst message: number = greet(String(answer));\n",
+            ),
+        ];
+        for (name, content) in files {
+            let path = dir.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        let diagnostics = checker.check(&[]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(diagnostics[0].code, "TS2322");
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (4, 7));
+    }
+}
@@ -0,0 +1,44 @@
+use super::{cli::Cli, run_command::resolve_entry};
+use crate::{
+    bundler::{BundleOptions, Bundler},
+    format::format_size,
+};
+use anyhow::Result;
+use ike_fs::FsError::FileNotFound;
+use ike_logger::{log, Logger};
+use std::{io::Write, path::PathBuf};
+
+pub fn bundle_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
+    let entry = cli.root.join(resolve_entry(cli.clone(), sub_matches)?);
+    if !entry.exists() {
+        return Err(FileNotFound(entry).into());
+    }
+
+    let options = BundleOptions {
+        minify: sub_matches.get_flag("minify"),
+    };
+    let code = Bundler::new(cli.root.clone())?.bundle(&entry, &options)?;
+
+    // Without an output file the bundle goes to stdout, so it can be piped
+    let Some(output) = sub_matches.get_one::<String>("output") else {
+        std::io::stdout().write_all(code.as_bytes())?;
+        return Ok(());
+    };
+
+    let output = cli.root.join(PathBuf::from(output));
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&output, &code)?;
+
+    let elapsed = chrono::Utc::now() - cli.start_timestamp;
+    log!(
+        success,
+        "Bundled <cyan>{}<r> <d>({})<r> <d>[{}ms]<r>",
+        output.display(),
+        format_size(code.len()),
+        elapsed.num_milliseconds()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use super::cli::Cli;
+use crate::{cache::cache_dir, remote::RemoteCache, transpile_cache::TranspileCache};
+use anyhow::Result;
+use ike_logger::{log, Logger};
+
+pub fn cache_command(_cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
+    match sub_matches.subcommand() {
+        Some(("clean", clean_matches)) => clean(clean_matches.get_flag("all")),
+        _ => {
+            log!("{}", cache_dir().display());
+            Ok(())
+        }
+    }
+}
+
+/// Removes transpiled and remote modules, everything else too with `--all`.
+fn clean(all: bool) -> Result<()> {
+    if all {
+        let dir = cache_dir();
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)?;
+        }
+
+        log!(success, "Removed <cyan>{}<r>", dir.display());
+        return Ok(());
+    }
+
+    let modules = TranspileCache::default().clean()?;
+    let remote = RemoteCache::default().clean()?;
+
+    log!(
+        success,
+        "Removed {} transpiled module{} and {} remote module{}",
+        modules,
+        if modules == 1 { "" } else { "s" },
+        remote,
+        if remote == 1 { "" } else { "s" }
+    );
+
+    Ok(())
+}
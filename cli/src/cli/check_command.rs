@@ -0,0 +1,35 @@
+use super::cli::Cli;
+use crate::check::{report, Checker};
+use anyhow::Result;
+use ike_logger::{log, Logger};
+use std::path::PathBuf;
+
+pub fn check_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
+    let files: Vec<PathBuf> = sub_matches
+        .get_many::<String>("files")
+        .unwrap_or_default()
+        .map(|file| cli.root.join(file))
+        .collect();
+
+    check_files(&cli, &files)?;
+    log!(success, "No type errors found");
+
+    Ok(())
+}
+
+/// Type-checks `files` (the whole project when empty) and fails on type errors, used by
+/// `ike check` and the `--check` flag of `ike run` and `ike test`.
+pub fn check_files(cli: &Cli, files: &[PathBuf]) -> Result<()> {
+    let imports = cli
+        .pkg
+        .as_ref()
+        .map(|pkg| pkg.toml.imports.clone())
+        .unwrap_or_default();
+    let checker = Checker::new(cli.root.clone(), cli.compiler_config())
+        .with_imports(imports, cli.manifest_dir());
+    let diagnostics = checker.check(files)?;
+
+    report(&diagnostics, &cli.root)?;
+
+    Ok(())
+}
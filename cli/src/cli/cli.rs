@@ -1,12 +1,30 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
-use super::{run_command::run_command, style, test_command::test_command};
-use crate::error::IkeError::FailedToParseRoot;
+use super::{
+    bundle_command::bundle_command,
+    cache_command::cache_command,
+    check_command::check_command,
+    compile_command::compile_command,
+    fmt_command::fmt_command,
+    install_command::{add_command, install_command, remove_command},
+    lint_command::lint_command,
+    repl_command::repl_command,
+    run_command::run_command,
+    style,
+    task_command::task_command,
+    test_command::test_command,
+};
+use crate::dotenv::load_env_files;
+use crate::error::IkeError::{FailedToParseRoot, NoMatchingMembers, NotInWorkspace};
 use crate::fs::normalize_path;
+use crate::import_map::{set_import_map, ImportMap};
+use crate::remote::{set_cache_setting, CacheSetting};
+use crate::transpiler::set_compiler_config;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use clap::{Arg, Command};
-use ike_toml::IkeToml;
+use clap::{Arg, ArgAction, Command};
+use ike_core::permissions::{set_permissions, PermissionKind, Permissions};
+use ike_toml::{CompilerConfig, FmtConfig, IkeToml, LintSeverity, PermissionGrant, Workspace};
 
 #[derive(Clone, Debug)]
 pub struct Cli {
@@ -47,7 +65,19 @@ impl Cli {
                         .help("name of the script.")
                         .required(false)
                         .num_args(1)])
-                    .args(Self::global_args()),
+                    .args(Self::global_args())
+                    .args(Self::check_args())
+                    .args(Self::module_args())
+                    .args(Self::env_args())
+                    .args(Self::permission_args()),
+            )
+            .subcommand(
+                Command::new("repl")
+                    .about("Start an interactive REPL, the same as running ike without arguments")
+                    .args(Self::global_args())
+                    .args(Self::module_args())
+                    .args(Self::env_args())
+                    .args(Self::permission_args()),
             )
             .subcommand(
                 Command::new("test")
@@ -57,8 +87,161 @@ impl Cli {
                         .required(false)
                         .short('p')
                         .long("pattern")])
+                    .args(Self::global_args())
+                    .args(Self::check_args())
+                    .args(Self::filter_args())
+                    .args(Self::module_args())
+                    .args(Self::env_args())
+                    .args(Self::permission_args()),
+            )
+            .subcommand(
+                Command::new("check")
+                    .about("Type-check TypeScript files")
+                    .args([Arg::new("files")
+                        .help("Files to check, the whole project when none are given")
+                        .num_args(0..)])
+                    .args(Self::global_args()),
+            )
+            .subcommand(
+                Command::new("bundle")
+                    .about("Bundle a module and its imports into a single file")
+                    .args([
+                        Arg::new("entry")
+                            .help("Module to bundle, the main of ike.toml when not given")
+                            .required(false)
+                            .num_args(1),
+                        Arg::new("output")
+                            .short('o')
+                            .long("output")
+                            .help("File to write the bundle to, stdout when not given")
+                            .value_name("FILE"),
+                        Arg::new("minify")
+                            .long("minify")
+                            .help("Remove whitespace and shorten names")
+                            .action(ArgAction::SetTrue),
+                    ])
+                    .args(Self::global_args())
+                    .args(Self::module_args()),
+            )
+            .subcommand(
+                Command::new("compile")
+                    .about("Compile a module into a standalone executable")
+                    .args([
+                        Arg::new("entry")
+                            .help("Module to compile, the main of ike.toml when not given")
+                            .required(false)
+                            .num_args(1),
+                        Arg::new("output")
+                            .short('o')
+                            .long("output")
+                            .help("Path of the executable, named after the package when not given")
+                            .value_name("FILE"),
+                        Arg::new("minify")
+                            .long("minify")
+                            .help("Minify the embedded bundle")
+                            .action(ArgAction::SetTrue),
+                    ])
+                    .args(Self::global_args())
+                    .args(Self::module_args())
+                    .args(Self::permission_args()),
+            )
+            .subcommand(
+                Command::new("fmt")
+                    .about("Format scripts, JSON files and ike.toml")
+                    .args([
+                        Arg::new("paths")
+                            .help("Files or directories to format, the whole project when none are given")
+                            .num_args(0..),
+                        Arg::new("check")
+                            .long("check")
+                            .help("Fail when files aren't formatted instead of writing them")
+                            .action(ArgAction::SetTrue),
+                    ])
                     .args(Self::global_args()),
             )
+            .subcommand(
+                Command::new("lint")
+                    .about("Lint scripts for likely mistakes")
+                    .args([
+                        Arg::new("paths")
+                            .help("Files or directories to lint, the whole project when none are given")
+                            .num_args(0..),
+                        Arg::new("json")
+                            .long("json")
+                            .help("Print the problems as JSON to stdout")
+                            .action(ArgAction::SetTrue),
+                    ])
+                    .args(Self::global_args()),
+            )
+            .subcommand(
+                Command::new("task")
+                    .about("Run a task from ike.toml, or list them when no name is given")
+                    .args([
+                        Arg::new("name").help("name of the task.").required(false),
+                        Arg::new("args")
+                            .help("Arguments passed to the task")
+                            .num_args(0..)
+                            .last(true),
+                    ])
+                    .args(Self::global_args())
+                    .args(Self::filter_args()),
+            )
+            .subcommand(
+                Command::new("install")
+                    .about("Install the dependencies listed in ike.toml")
+                    .visible_alias("i")
+                    .args([Arg::new("frozen")
+                        .long("frozen")
+                        .help("Fail instead of updating ike.lock when it's out of date")
+                        .action(ArgAction::SetTrue)])
+                    .args(Self::global_args())
+                    .args(Self::filter_args())
+                    .args(Self::registry_args()),
+            )
+            .subcommand(
+                Command::new("add")
+                    .about("Add dependencies to ike.toml and install them")
+                    .args([
+                        Arg::new("packages")
+                            .help("Packages to add, optionally with a version range (name@^1.0.0)")
+                            .required(true)
+                            .num_args(1..),
+                        Arg::new("dev")
+                            .short('D')
+                            .long("dev")
+                            .help("Add to dev-dependencies")
+                            .action(ArgAction::SetTrue),
+                        Arg::new("path")
+                            .long("path")
+                            .help("Add a local package from a path instead of the registry")
+                            .value_name("DIR"),
+                    ])
+                    .args(Self::global_args())
+                    .args(Self::registry_args()),
+            )
+            .subcommand(
+                Command::new("remove")
+                    .about("Remove dependencies from ike.toml")
+                    .visible_alias("rm")
+                    .args([Arg::new("packages")
+                        .help("Packages to remove")
+                        .required(true)
+                        .num_args(1..)])
+                    .args(Self::global_args())
+                    .args(Self::registry_args()),
+            )
+            .subcommand(
+                Command::new("cache")
+                    .about("Manage the global cache, prints its location without a subcommand")
+                    .subcommand(
+                        Command::new("clean")
+                            .about("Remove transpiled and downloaded modules from the cache")
+                            .args([Arg::new("all")
+                                .long("all")
+                                .help("Remove the whole cache, including installed packages")
+                                .action(ArgAction::SetTrue)]),
+                    ),
+            )
             .next_display_order(800)
             .allow_external_subcommands(true)
             .styles(styles)
@@ -71,6 +254,255 @@ impl Cli {
             .help("Root directory of the project")]
     }
 
+    pub fn check_args() -> Vec<Arg> {
+        vec![Arg::new("check")
+            .long("check")
+            .help("Type-check before running")
+            .action(ArgAction::SetTrue)]
+    }
+
+    pub fn module_args() -> Vec<Arg> {
+        vec![
+            Arg::new("import_map")
+                .long("import-map")
+                .help("Load an import map from a JSON file, on top of the imports in ike.toml")
+                .value_name("FILE"),
+            Arg::new("reload")
+                .long("reload")
+                .help("Download remote modules again, ignoring the cache")
+                .action(ArgAction::SetTrue),
+            Arg::new("cached_only")
+                .long("cached-only")
+                .help("Only use cached remote modules, fail instead of downloading")
+                .conflicts_with("reload")
+                .action(ArgAction::SetTrue),
+        ]
+    }
+
+    pub fn filter_args() -> Vec<Arg> {
+        vec![Arg::new("filter")
+            .short('F')
+            .long("filter")
+            .help("Only use the workspace members whose name or path matches a glob")
+            .value_name("PATTERN")
+            .value_delimiter(',')
+            .action(ArgAction::Append)]
+    }
+
+    pub fn registry_args() -> Vec<Arg> {
+        vec![Arg::new("registry")
+            .long("registry")
+            .help("URL of the npm-compatible registry (defaults to $IKE_REGISTRY or npm)")
+            .value_name("URL")]
+    }
+
+    pub fn env_args() -> Vec<Arg> {
+        vec![
+            Arg::new("env_file")
+                .long("env-file")
+                .help("Load environment variables from a file (defaults to .env)")
+                .value_name("FILE")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value(".env")
+                .action(ArgAction::Append),
+            Arg::new("env_override")
+                .long("env-override")
+                .help("Allow env files to override variables that are already set")
+                .action(ArgAction::SetTrue),
+        ]
+    }
+
+    pub fn permission_args() -> Vec<Arg> {
+        let mut args = vec![
+            Arg::new("allow_all")
+                .short('A')
+                .long("allow-all")
+                .help("Allow all permissions")
+                .action(ArgAction::SetTrue),
+            Arg::new("no_prompt")
+                .long("no-prompt")
+                .help("Always throw if a required permission wasn't passed")
+                .action(ArgAction::SetTrue),
+        ];
+
+        for kind in PermissionKind::ALL {
+            let help = match kind {
+                PermissionKind::Read => {
+                    "Allow file system read access, optionally limited to paths"
+                }
+                PermissionKind::Write => {
+                    "Allow file system write access, optionally limited to paths"
+                }
+                PermissionKind::Net => "Allow network access, optionally limited to hosts",
+                PermissionKind::Env => {
+                    "Allow environment access, optionally limited to variable names"
+                }
+                PermissionKind::Run => "Allow running subprocesses, optionally limited to commands",
+            };
+
+            let name = &kind.flag()[2..];
+            args.push(
+                Arg::new(name)
+                    .long(name)
+                    .help(help)
+                    .value_name("LIST")
+                    .num_args(0..)
+                    .require_equals(true)
+                    .value_delimiter(',')
+                    .default_missing_value("")
+                    .action(ArgAction::Append),
+            );
+        }
+
+        args
+    }
+
+    pub fn parse_permissions(&self, matches: &clap::ArgMatches) -> Permissions {
+        let mut permissions = if matches.get_flag("allow_all") {
+            Permissions::allow_all()
+        } else {
+            Permissions::default()
+        };
+        permissions.prompt = !matches.get_flag("no_prompt");
+
+        for kind in PermissionKind::ALL {
+            let values = match matches.get_many::<String>(&kind.flag()[2..]) {
+                Some(values) => values.collect::<Vec<_>>(),
+                None => continue,
+            };
+
+            // A bare `--allow-read` (without a list) grants access to everything
+            if values.iter().all(|value| value.is_empty()) {
+                permissions.grant(kind, None);
+            } else {
+                for value in values.into_iter().filter(|value| !value.is_empty()) {
+                    permissions.grant(kind, Some(value));
+                }
+            }
+        }
+
+        if let Some(pkg) = &self.pkg {
+            let manifest_dir = self.manifest_dir();
+            let declared = &pkg.toml.permissions;
+
+            for (kind, grant) in [
+                (PermissionKind::Read, &declared.read),
+                (PermissionKind::Write, &declared.write),
+                (PermissionKind::Net, &declared.net),
+                (PermissionKind::Env, &declared.env),
+                (PermissionKind::Run, &declared.run),
+            ] {
+                match grant {
+                    PermissionGrant::None => {}
+                    PermissionGrant::All => permissions.grant(kind, None),
+                    PermissionGrant::List(entries) => {
+                        for entry in entries {
+                            // Paths in ike.toml are relative to the manifest, not the cwd
+                            let entry = match kind {
+                                PermissionKind::Read | PermissionKind::Write => {
+                                    manifest_dir.join(entry).to_string_lossy().to_string()
+                                }
+                                _ => entry.clone(),
+                            };
+
+                            permissions.grant(kind, Some(&entry));
+                        }
+                    }
+                }
+            }
+        }
+
+        permissions
+    }
+
+    pub fn manifest_dir(&self) -> PathBuf {
+        self.pkg
+            .as_ref()
+            .and_then(|pkg| pkg.file_path.as_ref())
+            .and_then(|path| path.parent())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.root.clone())
+    }
+
+    /// Import map from the `[imports]` of ike.toml, extended by the one passed with
+    /// `--import-map`.
+    pub fn parse_import_map(&self, matches: &clap::ArgMatches) -> Result<ImportMap> {
+        let mut import_map = match &self.pkg {
+            Some(pkg) => ImportMap::from_manifest(pkg)?,
+            None => ImportMap::default(),
+        };
+
+        if let Some(path) = matches.get_one::<String>("import_map") {
+            let path = std::env::current_dir()?.join(path);
+            import_map.extend(ImportMap::from_file(&path)?);
+        }
+
+        Ok(import_map)
+    }
+
+    pub fn parse_cache_setting(&self, matches: &clap::ArgMatches) -> CacheSetting {
+        if matches.get_flag("reload") {
+            CacheSetting::Reload
+        } else if matches.get_flag("cached_only") {
+            CacheSetting::Only
+        } else {
+            CacheSetting::Use
+        }
+    }
+
+    /// `[compiler]` options of ike.toml, the transpiler defaults without a manifest.
+    pub fn compiler_config(&self) -> CompilerConfig {
+        self.pkg
+            .as_ref()
+            .map(|pkg| pkg.toml.compiler.clone())
+            .unwrap_or_default()
+    }
+
+    /// `[fmt]` options of ike.toml, the formatter defaults without a manifest.
+    pub fn fmt_config(&self) -> FmtConfig {
+        self.pkg
+            .as_ref()
+            .map(|pkg| pkg.toml.fmt.clone())
+            .unwrap_or_default()
+    }
+
+    /// `[lint]` rule severities of ike.toml, empty without a manifest.
+    pub fn lint_config(&self) -> HashMap<String, LintSeverity> {
+        self.pkg
+            .as_ref()
+            .map(|pkg| pkg.toml.lint.clone())
+            .unwrap_or_default()
+    }
+
+    /// Workspace the current package belongs to, if any.
+    pub fn workspace(&self) -> Result<Option<Workspace>> {
+        match &self.pkg {
+            Some(pkg) => Ok(pkg.workspace()?),
+            None => Ok(None),
+        }
+    }
+
+    pub fn parse_filters(&self, matches: &clap::ArgMatches) -> Vec<String> {
+        matches
+            .get_many::<String>("filter")
+            .map(|filters| filters.cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Workspace members selected with `--filter`. Fails when there's no workspace or
+    /// when nothing matches, so a typo never silently does nothing.
+    pub fn filtered_members(&self, filters: &[String]) -> Result<Vec<IkeToml>> {
+        let workspace = self.workspace()?.ok_or(NotInWorkspace)?;
+        let members: Vec<IkeToml> = workspace.filter(filters)?.into_iter().cloned().collect();
+
+        if members.is_empty() {
+            return Err(NoMatchingMembers(filters.join(",")).into());
+        }
+
+        Ok(members)
+    }
+
     pub fn set_root(mut self, root: PathBuf) -> Self {
         self.root = root;
         self
@@ -92,22 +524,131 @@ impl Cli {
         Ok(root)
     }
 
+    /// Loads env files declared in ike.toml followed by the ones passed with `--env-file`.
+    pub fn load_env(&self, matches: &clap::ArgMatches) -> Result<()> {
+        let mut files = Vec::new();
+        let mut override_existing = matches.get_flag("env_override");
+
+        if let Some(pkg) = &self.pkg {
+            let manifest_dir = self.manifest_dir();
+
+            files.extend(
+                pkg.toml
+                    .env
+                    .env_files
+                    .iter()
+                    .map(|file| manifest_dir.join(file)),
+            );
+            override_existing |= pkg.toml.env.r#override;
+        }
+
+        if let Some(env_files) = matches.get_many::<String>("env_file") {
+            let cwd = std::env::current_dir()?;
+            files.extend(env_files.map(|file| cwd.join(file)));
+        }
+
+        load_env_files(&files, override_existing)
+    }
+
+    /// Sets up everything commands that run code share: the root and manifest, env
+    /// files, permissions, the import map, the cache setting and the compiler options.
+    pub fn prepare_runtime(self, matches: &clap::ArgMatches) -> Result<Cli> {
+        let root = self.parse_root(matches)?;
+        let pkg = IkeToml::find_nearest_from(root.clone());
+        let cli = self.set_root(root).set_pkg(pkg);
+
+        cli.load_env(matches)?;
+        set_permissions(cli.parse_permissions(matches));
+        set_import_map(cli.parse_import_map(matches)?);
+        set_cache_setting(cli.parse_cache_setting(matches));
+        set_compiler_config(cli.compiler_config());
+
+        Ok(cli)
+    }
+
     pub async fn run(self) -> Result<()> {
-        let matches = Self::construct_cli().get_matches();
+        let mut matches = Self::construct_cli().get_matches();
+        // `ike` on its own starts the REPL
+        if matches.subcommand().is_none() {
+            matches = Self::construct_cli().get_matches_from(["ike", "repl"]);
+        }
 
         match matches.subcommand() {
             Some(("run", sub_matches)) => {
+                let cli = self.prepare_runtime(sub_matches)?;
+
+                run_command(cli, sub_matches)?
+            }
+            Some(("repl", sub_matches)) => {
+                let cli = self.prepare_runtime(sub_matches)?;
+
+                repl_command(cli, sub_matches)?
+            }
+            Some(("test", sub_matches)) => {
+                let cli = self.prepare_runtime(sub_matches)?;
+
+                test_command(cli, sub_matches)?
+            }
+            Some(("check", sub_matches)) => {
                 let root = self.parse_root(sub_matches)?;
                 let pkg = IkeToml::find_nearest_from(root.clone());
+                let cli = self.set_root(root).set_pkg(pkg);
 
-                run_command(self.set_root(root.clone()).set_pkg(pkg), sub_matches)?
+                check_command(cli, sub_matches)?
             }
-            Some(("test", sub_matches)) => {
+            Some(("bundle", sub_matches)) => {
+                let root = self.parse_root(sub_matches)?;
+                let pkg = IkeToml::find_nearest_from(root.clone());
+                let cli = self.set_root(root).set_pkg(pkg);
+                set_import_map(cli.parse_import_map(sub_matches)?);
+                set_cache_setting(cli.parse_cache_setting(sub_matches));
+                set_compiler_config(cli.compiler_config());
+
+                bundle_command(cli, sub_matches)?
+            }
+            Some(("compile", sub_matches)) => {
+                let root = self.parse_root(sub_matches)?;
+                let pkg = IkeToml::find_nearest_from(root.clone());
+                let cli = self.set_root(root).set_pkg(pkg);
+                set_import_map(cli.parse_import_map(sub_matches)?);
+                set_cache_setting(cli.parse_cache_setting(sub_matches));
+                set_compiler_config(cli.compiler_config());
+
+                compile_command(cli, sub_matches)?
+            }
+            Some(("fmt", sub_matches)) => {
+                let root = self.parse_root(sub_matches)?;
+                let pkg = IkeToml::find_nearest_from(root.clone());
+                let cli = self.set_root(root).set_pkg(pkg);
+
+                fmt_command(cli, sub_matches)?
+            }
+            Some(("lint", sub_matches)) => {
+                let root = self.parse_root(sub_matches)?;
+                let pkg = IkeToml::find_nearest_from(root.clone());
+                let cli = self.set_root(root).set_pkg(pkg);
+
+                lint_command(cli, sub_matches)?
+            }
+            Some(("task", sub_matches)) => {
+                let root = self.parse_root(sub_matches)?;
+                let pkg = IkeToml::find_nearest_from(root.clone());
+                let cli = self.set_root(root).set_pkg(pkg);
+
+                task_command(cli, sub_matches)?
+            }
+            Some((command @ ("install" | "add" | "remove"), sub_matches)) => {
                 let root = self.parse_root(sub_matches)?;
                 let pkg = IkeToml::find_nearest_from(root.clone());
+                let cli = self.set_root(root).set_pkg(pkg);
 
-                test_command(self.set_root(root.clone()).set_pkg(pkg), sub_matches)?
+                match command {
+                    "install" => install_command(cli, sub_matches)?,
+                    "add" => add_command(cli, sub_matches)?,
+                    _ => remove_command(cli, sub_matches)?,
+                }
             }
+            Some(("cache", sub_matches)) => cache_command(self, sub_matches)?,
             _ => {}
         };
 
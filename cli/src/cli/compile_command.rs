@@ -0,0 +1,62 @@
+use super::{cli::Cli, run_command::resolve_entry};
+use crate::{
+    bundler::{BundleOptions, Bundler},
+    format::format_size,
+    standalone::{Metadata, Standalone},
+};
+use anyhow::Result;
+use ike_fs::FsError::FileNotFound;
+use ike_logger::{log, Logger};
+
+pub fn compile_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
+    let entry = cli.root.join(resolve_entry(cli.clone(), sub_matches)?);
+    if !entry.exists() {
+        return Err(FileNotFound(entry).into());
+    }
+
+    let package = cli.pkg.as_ref().map(|pkg| &pkg.toml.package);
+    let name = match package {
+        Some(package) => package.name.clone(),
+        None => entry
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "main".to_string()),
+    };
+    let version = package
+        .map(|package| package.version.clone())
+        .unwrap_or_else(|| "0.0.0".to_string());
+
+    let options = BundleOptions {
+        minify: sub_matches.get_flag("minify"),
+    };
+    let code = Bundler::new(cli.root.clone())?.bundle(&entry, &options)?;
+
+    let metadata = Metadata::new(name.clone(), version, &cli.parse_permissions(sub_matches));
+    let standalone = Standalone { metadata, code };
+
+    let output = match sub_matches.get_one::<String>("output") {
+        Some(output) => cli.root.join(output),
+        // Scoped package names can't be file names
+        None => cli.root.join(name.rsplit('/').next().unwrap_or(&name)),
+    };
+    let output = if cfg!(windows) && output.extension().is_none() {
+        output.with_extension("exe")
+    } else {
+        output
+    };
+    standalone.write(&output)?;
+
+    let size = std::fs::metadata(&output)
+        .map(|meta| meta.len())
+        .unwrap_or(0);
+    let elapsed = chrono::Utc::now() - cli.start_timestamp;
+    log!(
+        success,
+        "Compiled <cyan>{}<r> <d>({})<r> <d>[{}ms]<r>",
+        output.display(),
+        format_size(size as usize),
+        elapsed.num_milliseconds()
+    );
+
+    Ok(())
+}
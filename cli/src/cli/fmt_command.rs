@@ -0,0 +1,88 @@
+use super::cli::Cli;
+use crate::{
+    error::IkeError::{FailedToFormat, UnformattedFiles},
+    formatter::{format_source, is_formattable, FormatError},
+    fs::walk_files,
+    runtime::errors::print_code_frame,
+};
+use anyhow::Result;
+use ike_fs::FsError::FileNotFound;
+use ike_logger::{elog, log, Logger};
+use std::path::PathBuf;
+
+pub fn fmt_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
+    let check = sub_matches.get_flag("check");
+    let config = cli.fmt_config();
+
+    let paths: Vec<PathBuf> = match sub_matches.get_many::<String>("paths") {
+        Some(paths) => paths.map(|path| cli.root.join(path)).collect(),
+        None => vec![cli.root.clone()],
+    };
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            files.extend(walk_files(&path, &is_formattable)?);
+        } else if path.exists() {
+            files.push(path);
+        } else {
+            return Err(FileNotFound(path).into());
+        }
+    }
+    files.sort();
+
+    let mut changed = 0;
+    let mut failed = 0;
+    for file in &files {
+        let source = std::fs::read_to_string(file)?;
+        let formatted = match format_source(file, &source, &config) {
+            Ok(formatted) => formatted,
+            Err(FormatError::Syntax(err)) => {
+                elog!(error, "{}", err);
+                print_code_frame(&err.code_frame, "");
+                failed += 1;
+                continue;
+            }
+            Err(err) => {
+                elog!(error, "{}", err);
+                failed += 1;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+
+        changed += 1;
+        if check {
+            elog!(error, "Not formatted: <cyan>{}<r>", file.display());
+        } else {
+            std::fs::write(file, formatted)?;
+        }
+    }
+
+    let elapsed = chrono::Utc::now() - cli.start_timestamp;
+    if check && changed > 0 {
+        return Err(UnformattedFiles(changed).into());
+    }
+    if failed > 0 {
+        return Err(FailedToFormat(failed).into());
+    }
+
+    if check {
+        log!(
+            success,
+            "Checked {} file(s) <d>[{}ms]<r>",
+            files.len(),
+            elapsed.num_milliseconds()
+        );
+    } else {
+        log!(
+            success,
+            "Formatted {changed} of {} file(s) <d>[{}ms]<r>",
+            files.len(),
+            elapsed.num_milliseconds()
+        );
+    }
+
+    Ok(())
+}
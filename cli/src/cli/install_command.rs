@@ -0,0 +1,189 @@
+use super::cli::Cli;
+use crate::error::IkeError::{NoMatchingMembers, NotInWorkspace};
+use crate::install::{
+    lockfile::{Lockfile, LOCKFILE_NAME},
+    manifest::{add_dependency, remove_dependency, DependencyEntry},
+    parse_package_spec, print_summary,
+    registry::Registry,
+    store::PackageStore,
+    InstallError, Installer,
+};
+use anyhow::Result;
+use ike_logger::{log, new_line, Logger};
+use ike_toml::IkeToml;
+use std::path::{Path, PathBuf};
+
+pub fn install_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
+    let manifest_path = manifest_path(&cli)?;
+    let frozen = sub_matches.get_flag("frozen");
+    let filters = cli.parse_filters(sub_matches);
+    let installer = installer(&cli, &manifest_path, sub_matches, frozen)?;
+
+    install(&cli, installer, &manifest_path, frozen, &filters)
+}
+
+pub fn add_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
+    let manifest_path = manifest_path(&cli)?;
+    let mut installer = installer(&cli, &manifest_path, sub_matches, false)?;
+    let dev = sub_matches.get_flag("dev");
+    let path = sub_matches.get_one::<String>("path");
+
+    for spec in sub_matches
+        .get_many::<String>("packages")
+        .unwrap_or_default()
+    {
+        let (name, range) = parse_package_spec(spec);
+
+        let entry = match path {
+            Some(path) => DependencyEntry::Path(path.clone()),
+            None => {
+                let version = installer
+                    .registry()
+                    .resolve(name, range.unwrap_or("latest"))?;
+
+                // Like npm, pin to the compatible range of the resolved version by default
+                match range {
+                    Some(range) if !range.is_empty() && range != "latest" => {
+                        DependencyEntry::Version(range.to_string())
+                    }
+                    _ => DependencyEntry::Version(format!("^{}", version.version)),
+                }
+            }
+        };
+
+        add_dependency(&manifest_path, name, entry, dev)?;
+    }
+
+    install(&cli, installer, &manifest_path, false, &[])
+}
+
+pub fn remove_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
+    let manifest_path = manifest_path(&cli)?;
+    let installer = installer(&cli, &manifest_path, sub_matches, false)?;
+
+    for name in sub_matches
+        .get_many::<String>("packages")
+        .unwrap_or_default()
+    {
+        if !remove_dependency(&manifest_path, name)? {
+            return Err(InstallError::DependencyNotFound(name.clone()).into());
+        }
+
+        log!("<red>-<r> {}", name);
+    }
+
+    install(&cli, installer, &manifest_path, false, &[])
+}
+
+/// Installs the package, or every member of its workspace (all of them, or the ones
+/// matching `filters`) into the workspace root.
+fn install(
+    cli: &Cli,
+    installer: Installer,
+    manifest_path: &Path,
+    frozen: bool,
+    filters: &[String],
+) -> Result<()> {
+    // Re-read the manifests, `add` and `remove` may have changed them
+    let manifest = IkeToml::from_file(manifest_path.to_path_buf())?;
+    let workspace = cli.workspace()?;
+    let project_dir = project_dir(cli, manifest_path);
+
+    let (all, selected): (Vec<&IkeToml>, Vec<&IkeToml>) = match &workspace {
+        Some(workspace) => {
+            let all: Vec<&IkeToml> = std::iter::once(&workspace.root)
+                .chain(&workspace.members)
+                .collect();
+            let selected = if filters.is_empty() {
+                all.clone()
+            } else {
+                workspace.filter(filters)?
+            };
+
+            (all, selected)
+        }
+        None if !filters.is_empty() => return Err(NotInWorkspace.into()),
+        None => (vec![&manifest], vec![&manifest]),
+    };
+
+    if selected.is_empty() {
+        return Err(NoMatchingMembers(filters.join(",")).into());
+    }
+
+    let members = workspace
+        .iter()
+        .flat_map(|workspace| &workspace.members)
+        .filter_map(|member| {
+            Some((
+                member.toml.package.name.clone(),
+                member.dir()?.to_path_buf(),
+            ))
+        })
+        .collect();
+    let partial = selected.len() < all.len();
+
+    let mut installer = installer.with_members(members).partial(partial);
+    let packages = installer.install(&selected)?;
+
+    if !frozen {
+        let lockfile_path = project_dir.join(LOCKFILE_NAME);
+        let previous = Lockfile::read(&lockfile_path)?;
+        let mut lockfile = Lockfile::new(&all, &packages, &project_dir);
+
+        if let Some(previous) = previous.as_ref().filter(|_| partial) {
+            lockfile.retain_from(previous);
+        }
+
+        if previous.as_ref() != Some(&lockfile) {
+            lockfile.write(&lockfile_path)?;
+        }
+    }
+
+    print_summary(&packages);
+    new_line!();
+
+    let elapsed = chrono::Utc::now() - cli.start_timestamp;
+    log!(
+        success,
+        "{} package{} installed <d>[{}ms]<r>",
+        packages.len(),
+        if packages.len() == 1 { "" } else { "s" },
+        elapsed.num_milliseconds()
+    );
+
+    Ok(())
+}
+
+fn manifest_path(cli: &Cli) -> Result<PathBuf, InstallError> {
+    cli.pkg
+        .as_ref()
+        .and_then(|pkg| pkg.file_path.clone())
+        .ok_or_else(|| InstallError::ManifestNotFound(cli.root.clone()))
+}
+
+fn installer(
+    cli: &Cli,
+    manifest_path: &Path,
+    sub_matches: &clap::ArgMatches,
+    frozen: bool,
+) -> Result<Installer> {
+    let registry = Registry::from_flag(sub_matches.get_one::<String>("registry"));
+    let project_dir = project_dir(cli, manifest_path);
+    let lockfile = Lockfile::read(&project_dir.join(LOCKFILE_NAME))?;
+
+    Ok(
+        Installer::new(registry, PackageStore::default(), project_dir)
+            .with_lockfile(lockfile, frozen),
+    )
+}
+
+/// Directory dependencies are installed into, the workspace root for workspace members.
+fn project_dir(cli: &Cli, manifest_path: &Path) -> PathBuf {
+    cli.pkg
+        .as_ref()
+        .and_then(|pkg| pkg.workspace_root.as_deref())
+        .unwrap_or(manifest_path)
+        .parent()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| cli.root.clone())
+}
@@ -0,0 +1,116 @@
+use super::cli::Cli;
+use crate::{
+    error::IkeError::LintErrors,
+    fs::walk_files,
+    globals::ALLOWED_EXTENSIONS,
+    linter::{Diagnostic, Linter},
+    runtime::errors::print_code_frame,
+};
+use anyhow::Result;
+use ike_fs::FsError::FileNotFound;
+use ike_logger::{elog, escape, log, Logger};
+use ike_toml::LintSeverity;
+use std::path::{Path, PathBuf};
+
+pub fn lint_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
+    let json = sub_matches.get_flag("json");
+    let linter = Linter::new(&cli.lint_config(), &cli.compiler_config())?;
+
+    let paths: Vec<PathBuf> = match sub_matches.get_many::<String>("paths") {
+        Some(paths) => paths.map(|path| cli.root.join(path)).collect(),
+        None => vec![cli.root.clone()],
+    };
+    let is_script = |path: &Path| {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ALLOWED_EXTENSIONS.contains(&ext))
+    };
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            files.extend(walk_files(&path, &is_script)?);
+        } else if path.exists() {
+            files.push(path);
+        } else {
+            return Err(FileNotFound(path).into());
+        }
+    }
+    files.sort();
+
+    let mut diagnostics = Vec::new();
+    for file in &files {
+        let source = std::fs::read_to_string(file)?;
+        match linter.lint(file, &source) {
+            Ok(file_diagnostics) => diagnostics.extend(file_diagnostics),
+            Err(err) => diagnostics.push(Diagnostic::syntax_error(err)),
+        }
+    }
+    for diagnostic in &mut diagnostics {
+        if let Ok(relative) = diagnostic.file.strip_prefix(&cli.root) {
+            diagnostic.file = relative.to_path_buf();
+        }
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == LintSeverity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diagnostics)?);
+    } else {
+        for diagnostic in &diagnostics {
+            print_diagnostic(diagnostic);
+            elog!("");
+        }
+    }
+
+    if errors > 0 {
+        return Err(LintErrors(errors, warnings).into());
+    }
+
+    let elapsed = chrono::Utc::now() - cli.start_timestamp;
+    if json {
+        return Ok(());
+    }
+    if warnings > 0 {
+        log!(
+            warn,
+            "Found {warnings} warning(s) in {} file(s) <d>[{}ms]<r>",
+            files.len(),
+            elapsed.num_milliseconds()
+        );
+    } else {
+        log!(
+            success,
+            "No problems found in {} file(s) <d>[{}ms]<r>",
+            files.len(),
+            elapsed.num_milliseconds()
+        );
+    }
+
+    Ok(())
+}
+
+fn print_diagnostic(diagnostic: &Diagnostic) {
+    let (label, color) = match diagnostic.severity {
+        LintSeverity::Error => ("error", "red"),
+        _ => ("warning", "yellow"),
+    };
+
+    elog!(
+        "<r><{}>{}<r><d>({})<r>: {}",
+        color,
+        label,
+        diagnostic.rule,
+        escape(&diagnostic.message)
+    );
+    elog!(
+        "  <d>at {}:{}:{}<r>",
+        escape(&diagnostic.file.display().to_string()),
+        diagnostic.line,
+        diagnostic.column
+    );
+    print_code_frame(&diagnostic.code_frame, "");
+}
@@ -1,4 +1,13 @@
+pub mod bundle_command;
+pub mod cache_command;
+pub mod check_command;
 pub mod cli;
+pub mod compile_command;
+pub mod fmt_command;
+pub mod install_command;
+pub mod lint_command;
+pub mod repl_command;
 pub mod run_command;
 pub mod style;
+pub mod task_command;
 pub mod test_command;
@@ -0,0 +1,16 @@
+use super::cli::Cli;
+use crate::repl::Repl;
+use anyhow::Result;
+use ike_logger::{elog, Logger};
+
+pub fn repl_command(cli: Cli, _sub_matches: &clap::ArgMatches) -> Result<()> {
+    let repl = match Repl::new(&cli.root) {
+        Ok(repl) => repl,
+        Err(e) => {
+            elog!(error, "{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    repl.run()
+}
@@ -4,13 +4,14 @@ use anyhow::Result;
 use ike_fs::FsError::FileNotFound;
 use ike_logger::{elog, Logger};
 
+use super::check_command::check_files;
 use super::cli::Cli;
 use crate::error::IkeError::CouldNotResolveEntry;
 use crate::{fs::is_file, runtime::runtime::start_runtime};
 
 pub fn run_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
     let cli_entry = resolve_entry(cli.clone(), sub_matches)?;
-    let entry = parse_entry(&cli_entry, cli.root)?;
+    let entry = parse_entry(&cli_entry, cli.root.clone())?;
 
     if entry.is_file {
         if entry.path.is_none() {
@@ -22,6 +23,10 @@ pub fn run_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
             return Err(FileNotFound(entry_path).into());
         }
 
+        if sub_matches.get_flag("check") {
+            check_files(&cli, &[entry_path.clone()])?;
+        }
+
         match start_runtime(&entry_path, None) {
             Ok(_) => {}
             Err(e) => {
@@ -36,7 +41,7 @@ pub fn run_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
     Ok(())
 }
 
-fn resolve_entry(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<String> {
+pub fn resolve_entry(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<String> {
     if let Some(entry) = sub_matches.get_one::<String>("entry") {
         Ok(entry.to_string())
     } else if let Some(pkg_entry) = cli
@@ -0,0 +1,131 @@
+use super::cli::Cli;
+use crate::error::IkeError::{ManifestNotFound, TaskFailed, TaskNotFound};
+use anyhow::Result;
+use ike_logger::{log, new_line, Logger};
+use ike_toml::IkeToml;
+use std::path::Path;
+use std::process::Command;
+
+pub fn task_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
+    let filters = cli.parse_filters(sub_matches);
+    let pkg = cli
+        .pkg
+        .clone()
+        .ok_or_else(|| ManifestNotFound(cli.root.clone()))?;
+
+    // A workspace root without the task runs it in every member that defines it
+    let packages = if !filters.is_empty() {
+        cli.filtered_members(&filters)?
+    } else if pkg.is_workspace_root() {
+        let mut packages = vec![pkg.clone()];
+        if let Some(workspace) = cli.workspace()? {
+            packages.extend(workspace.members);
+        }
+        packages
+    } else {
+        vec![pkg]
+    };
+
+    let Some(name) = sub_matches.get_one::<String>("name") else {
+        list_tasks(&packages);
+        return Ok(());
+    };
+    let args: Vec<&String> = sub_matches
+        .get_many::<String>("args")
+        .unwrap_or_default()
+        .collect();
+
+    let root_defines = filters.is_empty() && packages[0].toml.tasks.contains_key(name);
+    let targets: Vec<&IkeToml> = if root_defines {
+        vec![&packages[0]]
+    } else {
+        packages
+            .iter()
+            .filter(|package| package.toml.tasks.contains_key(name))
+            .collect()
+    };
+
+    if targets.is_empty() {
+        return Err(TaskNotFound(name.clone()).into());
+    }
+
+    for package in targets {
+        let dir = package.dir().unwrap_or(cli.root.as_path());
+        run_task(package, name, &package.toml.tasks[name], &args, dir)?;
+    }
+
+    Ok(())
+}
+
+fn run_task(
+    package: &IkeToml,
+    name: &str,
+    command: &str,
+    args: &[&String],
+    dir: &Path,
+) -> Result<()> {
+    let command = args.iter().fold(command.to_string(), |command, arg| {
+        format!("{command} {}", shell_quote(arg))
+    });
+
+    let label = match package.toml.package.name.as_str() {
+        "" => name.to_string(),
+        package_name => format!("{package_name}:{name}"),
+    };
+    log!(info, "<cyan>{}<r> <d>{}<r>", label, command);
+
+    #[cfg(windows)]
+    let status = Command::new("cmd")
+        .args(["/C", &command])
+        .current_dir(dir)
+        .status()?;
+    #[cfg(not(windows))]
+    let status = Command::new("sh")
+        .args(["-c", &command])
+        .current_dir(dir)
+        .status()?;
+
+    if !status.success() {
+        return Err(TaskFailed(label, status.code().unwrap_or(1)).into());
+    }
+
+    Ok(())
+}
+
+fn list_tasks(packages: &[IkeToml]) {
+    for package in packages {
+        if package.toml.tasks.is_empty() {
+            continue;
+        }
+
+        if packages.len() > 1 {
+            let name = match package.toml.package.name.as_str() {
+                "" => "(root)",
+                name => name,
+            };
+            log!("<cyan>{}<r>", name);
+        }
+
+        let mut tasks: Vec<_> = package.toml.tasks.iter().collect();
+        tasks.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (name, command) in tasks {
+            log!("  {} <d>{}<r>", name, command);
+        }
+        new_line!();
+    }
+}
+
+/// Quotes an argument for `sh -c` unless it's made of characters that are always safe.
+fn shell_quote(arg: &str) -> String {
+    let is_safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c));
+
+    if is_safe || cfg!(windows) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
@@ -1,18 +1,18 @@
 use crate::{
+    fs::walk_files,
     globals::{ALLOWED_EXTENSIONS, VERSION},
     testing::tests::run_tests,
 };
-use std::path::Path;
-use std::{fs::read_dir, path::PathBuf};
+use std::path::{Path, PathBuf};
 
+use super::check_command::check_files;
 use super::cli::Cli;
-use crate::error::IkeError::FailedToConvertPath;
 use anyhow::Result;
 use ike_logger::{log, new_line, Logger};
 
 // TODO: pattern handling
 pub fn test_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
-    let root = cli.root;
+    let root = cli.root.clone();
 
     log!(info, "<cyan>{}<r> <d>{}<r>", VERSION, root.display());
 
@@ -32,7 +32,33 @@ pub fn test_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
             .join(", ")
     );
 
-    let glob_result = Scanner::scan(root.clone(), patterns)?;
+    // With --filter only the selected workspace members are scanned
+    let filters = cli.parse_filters(sub_matches);
+    let dirs = if filters.is_empty() {
+        vec![root.clone()]
+    } else {
+        let members = cli.filtered_members(&filters)?;
+
+        log!(
+            info,
+            "<d>members: <r>{}",
+            members
+                .iter()
+                .map(|member| format!("<cyan>{}</r>", member.toml.package.name))
+                .collect::<Vec<String>>()
+                .join(", ")
+        );
+
+        members
+            .iter()
+            .filter_map(|member| member.dir().map(PathBuf::from))
+            .collect()
+    };
+
+    let mut glob_result = Vec::new();
+    for dir in dirs {
+        glob_result.extend(Scanner::scan(dir, patterns.clone())?);
+    }
 
     if glob_result.is_empty() {
         new_line!();
@@ -43,6 +69,10 @@ pub fn test_command(cli: Cli, sub_matches: &clap::ArgMatches) -> Result<()> {
     }
     new_line!();
 
+    if sub_matches.get_flag("check") && !glob_result.is_empty() {
+        check_files(&cli, &glob_result)?;
+    }
+
     run_tests(glob_result, root).unwrap();
 
     Ok(())
@@ -72,36 +102,12 @@ impl Scanner {
     }
 
     pub fn scan(dir: PathBuf, patterns: Vec<&str>) -> Result<Vec<PathBuf>> {
-        let mut paths = Vec::new();
-
-        for file in read_dir(dir)? {
-            let file = file?;
-            let path = file.path();
-
-            if let Some(path_str) = path.to_str() {
-                if path_str.contains(".git")
-                    || path_str.contains("node_modules")
-                    || path_str.contains("target")
-                {
-                    continue;
-                }
-            } else {
-                return Err(FailedToConvertPath.into());
-            }
-
-            if path.is_dir() {
-                paths.extend(Self::scan(path, patterns.clone())?);
-            } else if patterns.is_empty() && Self::is_test_file(&path) {
-                paths.push(path);
-            } else if Self::is_test_file(&path)
-                && patterns
-                    .iter()
-                    .any(|p| path.file_stem().unwrap().to_str().unwrap().contains(p))
-            {
-                paths.push(path);
-            }
-        }
-
-        Ok(paths)
+        walk_files(&dir, &|path| {
+            Self::is_test_file(path)
+                && (patterns.is_empty()
+                    || patterns
+                        .iter()
+                        .any(|p| path.file_stem().unwrap().to_str().unwrap().contains(p)))
+        })
     }
 }
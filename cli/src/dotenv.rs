@@ -0,0 +1,434 @@
+use crate::error::IkeError::FailedToParseEnvFile;
+use anyhow::Result;
+use ike_fs::read_to_string;
+use indexmap::IndexMap;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum DotenvError {
+    #[error("line {0}: expected <cyan>KEY=VALUE<r>")]
+    ExpectedAssignment(usize),
+    #[error("line {0}: invalid variable name {1:?}")]
+    InvalidKey(usize, String),
+    #[error("line {0}: unterminated {1} quote")]
+    UnterminatedQuote(usize, char),
+}
+
+/// Loads env files in order and applies them to the process environment.
+///
+/// Later files take precedence over earlier ones. Variables that were already
+/// set before loading are left untouched unless `override_existing` is true, and
+/// expansions see the same values the variables end up with.
+pub fn load_env_files(files: &[PathBuf], override_existing: bool) -> Result<()> {
+    let mut merged: IndexMap<String, String> = IndexMap::new();
+
+    for file in files {
+        let source = read_to_string(file)?;
+        let entries = parse(&source, |key, local| {
+            let loaded = local
+                .map(str::to_string)
+                .or_else(|| merged.get(key).cloned());
+            let existing = std::env::var(key).ok();
+
+            if override_existing {
+                loaded.or(existing)
+            } else {
+                existing.or(loaded)
+            }
+        })
+        .map_err(|e| FailedToParseEnvFile(file.clone(), e.to_string()))?;
+
+        merged.extend(entries);
+    }
+
+    for (key, value) in merged {
+        if override_existing || std::env::var_os(&key).is_none() {
+            std::env::set_var(key, value);
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses the contents of a `.env` file.
+///
+/// Supports `export` prefixes, comments, single/double/backtick quotes, multiline
+/// quoted values and `$VAR`, `${VAR}` and `${VAR:-default}` expansion. Variables
+/// are resolved through `lookup`, which also gets the value the file itself assigned
+/// to the variable so far and decides which one wins.
+pub fn parse<F>(source: &str, lookup: F) -> Result<Vec<(String, String)>, DotenvError>
+where
+    F: Fn(&str, Option<&str>) -> Option<String>,
+{
+    let mut parser = DotenvParser::new(source);
+    let mut entries: Vec<(String, String)> = Vec::new();
+
+    while let Some((key, raw, quote, line)) = parser.next_entry()? {
+        let value = match quote {
+            Some('\'') | Some('`') => raw,
+            Some(_) => interpolate(&raw, true, line, &entries, &lookup)?,
+            None => interpolate(&raw, false, line, &entries, &lookup)?,
+        };
+
+        entries.push((key, value));
+    }
+
+    Ok(entries)
+}
+
+struct DotenvParser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl DotenvParser {
+    fn new(source: &str) -> Self {
+        Self {
+            chars: source.chars().collect(),
+            pos: 0,
+            line: 1,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_inline_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.bump();
+        }
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(c) = self.bump() {
+            if c == '\n' {
+                break;
+            }
+        }
+    }
+
+    fn read_line(&mut self) -> String {
+        let mut line = String::new();
+        while let Some(c) = self.peek() {
+            if c == '\n' {
+                break;
+            }
+            line.push(c);
+            self.bump();
+        }
+        line
+    }
+
+    /// Returns the key, the raw (unexpanded) value, the quote the value was wrapped in
+    /// and the line the entry starts on.
+    #[allow(clippy::type_complexity)]
+    fn next_entry(&mut self) -> Result<Option<(String, String, Option<char>, usize)>, DotenvError> {
+        loop {
+            match self.peek() {
+                None => return Ok(None),
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('#') => self.skip_line(),
+                Some(_) => break,
+            }
+        }
+
+        let line = self.line;
+        let mut key = String::new();
+        while let Some(c) = self.peek() {
+            if c == '=' || c == '\n' {
+                break;
+            }
+            key.push(c);
+            self.bump();
+        }
+
+        if self.peek() != Some('=') {
+            return Err(DotenvError::ExpectedAssignment(line));
+        }
+        self.bump();
+
+        let mut key = key.trim();
+        if let Some(stripped) = key.strip_prefix("export") {
+            if stripped.starts_with([' ', '\t']) {
+                key = stripped.trim_start();
+            }
+        }
+
+        if !is_valid_key(key) {
+            return Err(DotenvError::InvalidKey(line, key.to_string()));
+        }
+
+        self.skip_inline_whitespace();
+
+        match self.peek() {
+            Some(quote @ ('\'' | '"' | '`')) => {
+                self.bump();
+                let value = self.read_quoted(quote, line)?;
+                // Anything after the closing quote can only be a comment
+                self.skip_line();
+
+                Ok(Some((key.to_string(), value, Some(quote), line)))
+            }
+            _ => {
+                let value = strip_inline_comment(&self.read_line());
+
+                Ok(Some((
+                    key.to_string(),
+                    value.trim().to_string(),
+                    None,
+                    line,
+                )))
+            }
+        }
+    }
+
+    fn read_quoted(&mut self, quote: char, line: usize) -> Result<String, DotenvError> {
+        let mut value = String::new();
+
+        loop {
+            match self.bump() {
+                None => return Err(DotenvError::UnterminatedQuote(line, quote)),
+                Some('\\') if quote == '"' => {
+                    // Escapes are resolved during interpolation, keep them intact for now
+                    value.push('\\');
+                    if let Some(next) = self.bump() {
+                        value.push(next);
+                    }
+                }
+                Some(c) if c == quote => break,
+                Some(c) => value.push(c),
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-')
+}
+
+fn strip_inline_comment(value: &str) -> String {
+    let mut previous = ' ';
+
+    for (i, c) in value.char_indices() {
+        if c == '#' && previous.is_whitespace() {
+            return value[..i].to_string();
+        }
+        previous = c;
+    }
+
+    value.to_string()
+}
+
+fn interpolate<F>(
+    raw: &str,
+    escapes: bool,
+    line: usize,
+    entries: &[(String, String)],
+    lookup: &F,
+) -> Result<String, DotenvError>
+where
+    F: Fn(&str, Option<&str>) -> Option<String>,
+{
+    let resolve = |name: &str| {
+        let local = entries
+            .iter()
+            .rev()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str());
+
+        lookup(name, local)
+    };
+
+    let mut result = String::new();
+    let mut chars = raw.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') if escapes => result.push('\n'),
+                Some('r') if escapes => result.push('\r'),
+                Some('t') if escapes => result.push('\t'),
+                Some('$') => result.push('$'),
+                Some(next) if escapes && (next == '"' || next == '\\') => result.push(next),
+                Some(next) => {
+                    result.push('\\');
+                    result.push(next);
+                }
+                None => result.push('\\'),
+            },
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+                let mut expr = String::new();
+                let mut closed = false;
+                for next in chars.by_ref() {
+                    if next == '}' {
+                        closed = true;
+                        break;
+                    }
+                    expr.push(next);
+                }
+
+                if !closed {
+                    return Err(DotenvError::UnterminatedQuote(line, '{'));
+                }
+
+                let (name, default) = match expr.split_once(":-") {
+                    Some((name, default)) => (name, Some(default)),
+                    None => (expr.as_str(), None),
+                };
+
+                match resolve(name).filter(|value| !value.is_empty()) {
+                    Some(value) => result.push_str(&value),
+                    None => result.push_str(default.unwrap_or_default()),
+                }
+            }
+            '$' if chars
+                .peek()
+                .is_some_and(|c| c.is_ascii_alphabetic() || *c == '_') =>
+            {
+                let mut name = String::new();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_ascii_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    name.push(next);
+                    chars.next();
+                }
+
+                result.push_str(&resolve(&name).unwrap_or_default());
+            }
+            _ => result.push(c),
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(source: &str) -> Vec<(String, String)> {
+        parse(source, |key, local| match (key, local) {
+            (_, Some(local)) => Some(local.to_string()),
+            ("HOME", None) => Some("/home/ike".to_string()),
+            _ => None,
+        })
+        .unwrap()
+    }
+
+    fn entry(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_parse_basic() {
+        let entries = parse_str("# comment\nA=1\n\nexport B = two # trailing\nC=");
+
+        assert_eq!(
+            entries,
+            vec![entry("A", "1"), entry("B", "two"), entry("C", "")]
+        );
+    }
+
+    #[test]
+    fn test_parse_quotes() {
+        let entries = parse_str(
+            "SINGLE='$HOME \\n'\nDOUBLE=\"a\\n\\\"b\\\"\"\nMULTI=\"line 1\nline 2\"\nHASH=\"a # b\"",
+        );
+
+        assert_eq!(
+            entries,
+            vec![
+                entry("SINGLE", "$HOME \\n"),
+                entry("DOUBLE", "a\n\"b\""),
+                entry("MULTI", "line 1\nline 2"),
+                entry("HASH", "a # b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_expansion() {
+        let entries = parse_str(
+            "DIR=${HOME}/app\nLOGS=$DIR/logs\nMISSING=${NOPE:-fallback}\nESCAPED=\"\\${HOME}\"",
+        );
+
+        assert_eq!(
+            entries,
+            vec![
+                entry("DIR", "/home/ike/app"),
+                entry("LOGS", "/home/ike/app/logs"),
+                entry("MISSING", "fallback"),
+                entry("ESCAPED", "${HOME}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let lookup = |_: &str, _: Option<&str>| None;
+
+        assert_eq!(
+            parse("A=1\nINVALID", lookup),
+            Err(DotenvError::ExpectedAssignment(2))
+        );
+        assert_eq!(
+            parse("1A=1", lookup),
+            Err(DotenvError::InvalidKey(1, "1A".to_string()))
+        );
+        assert_eq!(
+            parse("A=\"open", lookup),
+            Err(DotenvError::UnterminatedQuote(1, '"'))
+        );
+    }
+
+    #[test]
+    fn test_load_env_files_expansion() {
+        let dir = std::env::temp_dir().join(format!("ike-dotenv-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join(".env");
+        std::fs::write(
+            &file,
+            "IKE_DOTENV_HOST=localhost\nIKE_DOTENV_URL=http://${IKE_DOTENV_HOST}\n",
+        )
+        .unwrap();
+
+        // Variables of the process win, so expansions have to use them as well
+        std::env::set_var("IKE_DOTENV_HOST", "prod");
+        load_env_files(&[file.clone()], false).unwrap();
+        assert_eq!(std::env::var("IKE_DOTENV_HOST").unwrap(), "prod");
+        assert_eq!(std::env::var("IKE_DOTENV_URL").unwrap(), "http://prod");
+
+        load_env_files(&[file], true).unwrap();
+        assert_eq!(std::env::var("IKE_DOTENV_HOST").unwrap(), "localhost");
+        assert_eq!(std::env::var("IKE_DOTENV_URL").unwrap(), "http://localhost");
+
+        std::env::remove_var("IKE_DOTENV_HOST");
+        std::env::remove_var("IKE_DOTENV_URL");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
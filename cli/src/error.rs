@@ -1,11 +1,28 @@
+use std::path::PathBuf;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum IkeError {
     #[error("Could not resolve entry file. Please specify one as an <cyan>argument<r> or in the <cyan>main<r> field of the <cyan>ike.toml<r> file")]
     CouldNotResolveEntry,
-    #[error("Failed to convert path to string")]
-    FailedToConvertPath,
     #[error("Failed to parse project root")]
     FailedToParseRoot,
+    #[error("Failed to parse env file <cyan>{0}<r>: {1}")]
+    FailedToParseEnvFile(PathBuf, String),
+    #[error("<cyan>--filter<r> can only be used inside a workspace")]
+    NotInWorkspace,
+    #[error("No workspace member matches <cyan>{0}<r>")]
+    NoMatchingMembers(String),
+    #[error("Could not find an <cyan>ike.toml<r> in <cyan>{0}<r> or any parent directory")]
+    ManifestNotFound(PathBuf),
+    #[error("Task <cyan>{0}<r> is not defined in <cyan>ike.toml<r>")]
+    TaskNotFound(String),
+    #[error("Task <cyan>{0}<r> failed with exit code {1}")]
+    TaskFailed(String, i32),
+    #[error("{0} file(s) aren't formatted, run <cyan>ike fmt<r> to format them")]
+    UnformattedFiles(usize),
+    #[error("Failed to format {0} file(s)")]
+    FailedToFormat(usize),
+    #[error("Found {0} error(s) and {1} warning(s)")]
+    LintErrors(usize, usize),
 }
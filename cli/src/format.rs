@@ -17,3 +17,17 @@ pub fn format_time(time: Duration, include_brackets: bool) -> String {
         format!("{}ms", milliseconds)
     }
 }
+
+pub fn format_size(bytes: usize) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+
+    let size = bytes as f64;
+    if size >= MB {
+        format!("{:.2} MB", size / MB)
+    } else if size >= KB {
+        format!("{:.2} KB", size / KB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
@@ -0,0 +1,182 @@
+use super::FormatError;
+use ike_toml::FmtConfig;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'s> {
+    Open(char),
+    Close(char),
+    Comma,
+    Colon,
+    /// Strings, numbers and literals, printed as they are.
+    Value(&'s str),
+    /// `// comment` or `/* comment */`, with whether it's on the line of the token
+    /// before it.
+    Comment(&'s str, bool),
+}
+
+/// Prints JSON with every member on its own line. Comments are kept, so `tsconfig.json`
+/// style files work too.
+pub fn format(path: &Path, source: &str, config: &FmtConfig) -> Result<String, FormatError> {
+    let invalid = |message: &str| FormatError::InvalidJson(path.to_path_buf(), message.to_string());
+    let tokens = tokenize(source).map_err(|message| invalid(&message))?;
+
+    let indent = " ".repeat(config.indent_width);
+    let mut out = String::new();
+    let mut depth = 0usize;
+    let mut newline = false;
+
+    for (index, token) in tokens.iter().enumerate() {
+        let next = tokens[index + 1..]
+            .iter()
+            .find(|token| !matches!(token, Token::Comment(..)));
+
+        match *token {
+            Token::Comment(text, true) if !out.is_empty() => {
+                out.push(' ');
+                out.push_str(text);
+                newline = true;
+                continue;
+            }
+            Token::Close(_) => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| invalid("unbalanced brackets"))?;
+                // Empty objects and arrays stay on one line
+                newline = !matches!(tokens[index - 1], Token::Open(_));
+            }
+            _ => {}
+        }
+
+        if newline {
+            out.push('\n');
+            out.push_str(&indent.repeat(depth));
+            newline = false;
+        }
+
+        match *token {
+            Token::Open(bracket) => {
+                out.push(bracket);
+                depth += 1;
+                newline = !matches!(tokens.get(index + 1), Some(Token::Close(_)));
+            }
+            Token::Close(bracket) => out.push(bracket),
+            // Trailing commas aren't valid JSON
+            Token::Comma if matches!(next, Some(Token::Close(_)) | None) => {}
+            Token::Comma => {
+                out.push(',');
+                newline = true;
+            }
+            Token::Colon => out.push_str(": "),
+            Token::Value(text) => out.push_str(text),
+            Token::Comment(text, _) => {
+                out.push_str(text);
+                newline = true;
+            }
+        }
+    }
+
+    if depth != 0 {
+        return Err(invalid("unbalanced brackets"));
+    }
+    out.push('\n');
+
+    Ok(out)
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    let mut same_line = false;
+
+    while index < bytes.len() {
+        let start = index;
+        let token = match bytes[index] {
+            b'\n' => {
+                same_line = false;
+                index += 1;
+                continue;
+            }
+            byte if byte.is_ascii_whitespace() => {
+                index += 1;
+                continue;
+            }
+            b'{' | b'[' => {
+                index += 1;
+                Token::Open(bytes[start] as char)
+            }
+            b'}' | b']' => {
+                index += 1;
+                Token::Close(bytes[start] as char)
+            }
+            b',' => {
+                index += 1;
+                Token::Comma
+            }
+            b':' => {
+                index += 1;
+                Token::Colon
+            }
+            b'"' => {
+                index += 1;
+                while index < bytes.len() && bytes[index] != b'"' {
+                    index += if bytes[index] == b'\\' { 2 } else { 1 };
+                }
+                if index >= bytes.len() {
+                    return Err("unterminated string".to_string());
+                }
+                index += 1;
+                Token::Value(&source[start..index])
+            }
+            b'/' if bytes.get(index + 1) == Some(&b'/') => {
+                index = source[index..]
+                    .find('\n')
+                    .map_or(bytes.len(), |end| index + end);
+                Token::Comment(source[start..index].trim_end(), same_line)
+            }
+            b'/' if bytes.get(index + 1) == Some(&b'*') => {
+                let end = source[index + 2..]
+                    .find("*/")
+                    .ok_or_else(|| "unterminated comment".to_string())?;
+                index += end + 4;
+                Token::Comment(&source[start..index], same_line)
+            }
+            byte if byte.is_ascii_alphanumeric()
+                || byte == b'-'
+                || byte == b'+'
+                || byte == b'.' =>
+            {
+                while index < bytes.len()
+                    && (bytes[index].is_ascii_alphanumeric() || b"-+.".contains(&bytes[index]))
+                {
+                    index += 1;
+                }
+                Token::Value(&source[start..index])
+            }
+            byte => return Err(format!("unexpected character '{}'", byte as char)),
+        };
+
+        same_line = true;
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_json() {
+        let config = FmtConfig::default();
+        let source = "{\"b\":1,\"a\":[1,2, {}],\n// note\n\"c\": {\"d\": \"x,y\"} // end\n}";
+
+        assert_eq!(
+            format(Path::new("a.json"), source, &config).unwrap(),
+            "{\n  \"b\": 1,\n  \"a\": [\n    1,\n    2,\n    {}\n  ],\n  // note\n  \"c\": {\n    \"d\": \"x,y\"\n  } // end\n}\n"
+        );
+        assert!(format(Path::new("a.json"), "{\"a\": 1", &config).is_err());
+    }
+}
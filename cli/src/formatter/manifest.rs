@@ -0,0 +1,146 @@
+use super::{finish_lines, FormatError};
+use ike_toml::FmtConfig;
+use std::path::Path;
+
+/// Where a line of TOML ends, carried over to the next one.
+#[derive(Debug, Default, Clone, Copy)]
+struct LineState {
+    /// Open brackets and braces of multi-line arrays.
+    depth: usize,
+    /// Delimiter of the multi-line string the line ends in.
+    string: Option<&'static str>,
+}
+
+/// Normalizes the whitespace of an ike.toml, the content is left untouched.
+pub fn format(path: &Path, source: &str, config: &FmtConfig) -> Result<String, FormatError> {
+    let parse = |text: &str| {
+        text.parse::<toml::Table>()
+            .map_err(|err| FormatError::InvalidToml(path.to_path_buf(), err.message().to_string()))
+    };
+    let table = parse(source)?;
+
+    let indent = " ".repeat(config.indent_width);
+    let mut state = LineState::default();
+    let mut lines = Vec::new();
+
+    for line in source.lines() {
+        let start = state;
+        state = scan_line(line, start);
+
+        // Multi-line strings are content
+        if start.string.is_some() {
+            lines.push((line.to_string(), true));
+            continue;
+        }
+
+        let mut text = if state.string.is_some() {
+            line.trim_start().to_string()
+        } else {
+            line.trim().to_string()
+        };
+        if start.depth == 0 {
+            text = normalize_key_value(&text);
+        }
+
+        let mut level = start.depth;
+        if text.starts_with([']', '}']) {
+            level = level.saturating_sub(1);
+        }
+        if text.is_empty() {
+            lines.push((text, false));
+        } else {
+            lines.push((format!("{}{text}", indent.repeat(level)), false));
+        }
+    }
+
+    let result = finish_lines(lines);
+    if parse(&result)? != table {
+        return Err(FormatError::ChangedMeaning(path.to_path_buf()));
+    }
+
+    Ok(result)
+}
+
+fn scan_line(line: &str, mut state: LineState) -> LineState {
+    let mut rest = line;
+
+    while !rest.is_empty() {
+        if let Some(delimiter) = state.string {
+            match find_closing(rest, delimiter) {
+                Some(end) => {
+                    rest = &rest[end + delimiter.len()..];
+                    state.string = None;
+                }
+                None => return state,
+            }
+            continue;
+        }
+
+        if rest.starts_with('#') {
+            break;
+        }
+        if let Some(delimiter) = ["\"\"\"", "'''"]
+            .into_iter()
+            .find(|delimiter| rest.starts_with(delimiter))
+        {
+            state.string = Some(delimiter);
+            rest = &rest[3..];
+            continue;
+        }
+
+        let char = rest.chars().next().unwrap();
+        rest = &rest[char.len_utf8()..];
+        match char {
+            '"' | '\'' => {
+                let delimiter = if char == '"' { "\"" } else { "'" };
+                rest = find_closing(rest, delimiter).map_or("", |end| &rest[end + 1..]);
+            }
+            '[' | '{' => state.depth += 1,
+            ']' | '}' => state.depth = state.depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    state
+}
+
+/// Index of `delimiter` in `text`, skipping escapes in basic strings.
+fn find_closing(text: &str, delimiter: &str) -> Option<usize> {
+    let mut index = 0;
+    while index < text.len() {
+        if text[index..].starts_with('\\') && delimiter.starts_with('"') {
+            index += 2;
+            continue;
+        }
+        if text[index..].starts_with(delimiter) {
+            return Some(index);
+        }
+        index += text[index..].chars().next().map_or(1, char::len_utf8);
+    }
+
+    None
+}
+
+/// `key="value"` to `key = "value"`, table headers and comments are left as they are.
+fn normalize_key_value(line: &str) -> String {
+    if line.starts_with(['[', '#']) {
+        return line.to_string();
+    }
+
+    // The first `=` outside of a quoted key
+    let mut quote = None;
+    for (index, char) in line.char_indices() {
+        match (quote, char) {
+            (None, '"' | '\'') => quote = Some(char),
+            (Some(open), _) if open == char => quote = None,
+            (None, '=') => {
+                let key = line[..index].trim_end();
+                let value = line[index + 1..].trim_start();
+                return format!("{key} = {value}");
+            }
+            _ => {}
+        }
+    }
+
+    line.to_string()
+}
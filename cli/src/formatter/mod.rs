@@ -0,0 +1,81 @@
+mod json;
+mod manifest;
+mod script;
+
+use crate::{globals::ALLOWED_EXTENSIONS, transpiler::SyntaxError};
+use ike_toml::FmtConfig;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum FormatError {
+    #[error(transparent)]
+    Syntax(#[from] SyntaxError),
+    #[error("Invalid JSON in <cyan>{0}<r>: {1}")]
+    InvalidJson(PathBuf, String),
+    #[error("Invalid TOML in <cyan>{0}<r>: {1}")]
+    InvalidToml(PathBuf, String),
+    #[error("Formatting <cyan>{0}<r> would change its meaning, the file was left as it is. Please report this as a bug")]
+    ChangedMeaning(PathBuf),
+}
+
+/// Whether `ike fmt` formats the file: scripts, JSON files and ike.toml.
+pub fn is_formattable(path: &Path) -> bool {
+    if path.file_name().is_some_and(|name| name == "ike.toml") {
+        return true;
+    }
+    // Lock files are generated, reformatting them only produces noise
+    if path
+        .file_name()
+        .is_some_and(|name| name == "package-lock.json")
+    {
+        return false;
+    }
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => true,
+        Some(ext) => ALLOWED_EXTENSIONS.contains(&ext),
+        None => false,
+    }
+}
+
+/// Formats the content of `path`, which has to be [formattable](is_formattable).
+pub fn format_source(path: &Path, source: &str, config: &FmtConfig) -> Result<String, FormatError> {
+    // Everything is formatted with \n line endings
+    let source = source.replace("\r\n", "\n");
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => json::format(path, &source, config),
+        Some("toml") => manifest::format(path, &source, config),
+        _ => script::format(path, &source, config),
+    }
+}
+
+/// Removes blank lines at the start, keeps at most one in a row and ends the text
+/// with a single newline. Lines marked as content, like the ones of multi-line
+/// strings, are kept as they are.
+fn finish_lines(lines: Vec<(String, bool)>) -> String {
+    let mut result = String::new();
+    let mut blank = true;
+
+    for (line, content) in lines {
+        if line.trim().is_empty() && !content {
+            if !blank {
+                result.push('\n');
+            }
+            blank = true;
+        } else {
+            result.push_str(&line);
+            result.push('\n');
+            blank = false;
+        }
+    }
+
+    let len = result.trim_end_matches('\n').len();
+    result.truncate(len);
+    if !result.is_empty() {
+        result.push('\n');
+    }
+
+    result
+}
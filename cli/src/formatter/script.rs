@@ -0,0 +1,852 @@
+use super::{finish_lines, FormatError};
+use crate::{bundler::emit::apply_edits, transpiler::SyntaxError};
+use ike_toml::{FmtConfig, QuoteStyle};
+use oxc_allocator::Allocator;
+use oxc_ast::{
+    ast::{
+        Class, ClassElement, Declaration, ExportDefaultDeclarationKind, Expression,
+        ImportDeclarationSpecifier, JSXAttribute, JSXAttributeValue, JSXElement, JSXFragment,
+        JSXText, Program, PropertyKey, Statement, StringLiteral, SwitchCase, TSTemplateLiteralType,
+        TemplateLiteral,
+    },
+    visit::walk,
+    Trivias, Visit,
+};
+use oxc_codegen::CodeGenerator;
+use oxc_parser::Parser;
+use oxc_span::{GetSpan, SourceType, Span};
+use std::{cmp::Reverse, path::Path};
+
+/// Formats a script in three steps: semicolons, quotes, import lists and closing
+/// brackets are edited on the original, the edited code is indented by its brackets
+/// and the result has to print like the original.
+///
+/// Other line breaks are kept as written, long expressions aren't wrapped and only
+/// import and export lists are fitted into `line_width`.
+pub fn format(path: &Path, source: &str, config: &FmtConfig) -> Result<String, FormatError> {
+    let source_type = SourceType::from_path(path).unwrap_or_default();
+    let changed_meaning = || FormatError::ChangedMeaning(path.to_path_buf());
+
+    let allocator = Allocator::default();
+    let ret = Parser::new(&allocator, source, source_type).parse();
+    if let Some(error) = ret.errors.into_iter().next() {
+        return Err(SyntaxError::new(path, source, error).into());
+    }
+    let expected = print(&ret.program);
+    let edits = Collector::collect(source, &ret.program, &ret.trivias).edits(config);
+    let edited = apply_edits(source, edits);
+
+    let allocator = Allocator::default();
+    let ret = Parser::new(&allocator, &edited, source_type).parse();
+    if !ret.errors.is_empty() {
+        return Err(changed_meaning());
+    }
+    let formatted = Collector::collect(&edited, &ret.program, &ret.trivias).reindent(config);
+
+    let allocator = Allocator::default();
+    let ret = Parser::new(&allocator, &formatted, source_type).parse();
+    if !ret.errors.is_empty() || print(&ret.program) != expected {
+        return Err(changed_meaning());
+    }
+
+    Ok(formatted)
+}
+
+/// The program without whitespace, equal for code that only differs in formatting.
+fn print(program: &Program) -> String {
+    CodeGenerator::new()
+        .build(program)
+        .source_text
+        .chars()
+        .filter(|char| !char.is_whitespace())
+        .collect()
+}
+
+/// Source that isn't code, brackets in it don't count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Skip {
+    Comment,
+    /// Strings, templates and regular expressions, lines starting in them are kept.
+    Literal,
+    /// JSX text, its indentation is insignificant.
+    Text,
+}
+
+/// Lines between `open` and `close` are indented one level deeper than the line of
+/// `open`.
+#[derive(Debug, Clone, Copy)]
+struct Pair {
+    open: usize,
+    close: usize,
+    /// Bodies without braces and switch cases end before `close`. Brackets and JSX
+    /// tags end at it, a line starting with `close` is on the level of `open`.
+    body: bool,
+}
+
+impl Pair {
+    fn end(&self) -> usize {
+        if self.body {
+            self.close
+        } else {
+            self.close + 1
+        }
+    }
+}
+
+/// The named specifiers of an import or export, `{ a, b as c }`.
+#[derive(Debug)]
+struct List {
+    statement: Span,
+    braces: Span,
+    specifiers: Vec<Span>,
+}
+
+#[derive(Debug)]
+struct Collector<'s> {
+    source: &'s str,
+    /// Sorted by start, without spans nested in others.
+    skipped: Vec<(Span, Skip)>,
+    regions: Vec<Pair>,
+    /// Statements and class members that end with an optional semicolon.
+    statements: Vec<Span>,
+    /// String literals whose quotes may change.
+    strings: Vec<Span>,
+    lists: Vec<List>,
+}
+
+impl<'s> Collector<'s> {
+    fn collect(source: &'s str, program: &Program, trivias: &Trivias) -> Self {
+        let mut collector = Self {
+            source,
+            skipped: Vec::new(),
+            regions: Vec::new(),
+            statements: Vec::new(),
+            strings: Vec::new(),
+            lists: Vec::new(),
+        };
+
+        for comment in trivias.comments() {
+            let span = comment_span(source, comment.span);
+            collector.skipped.push((span, Skip::Comment));
+        }
+        if let Some(hashbang) = &program.hashbang {
+            collector.skipped.push((hashbang.span, Skip::Comment));
+        }
+        collector.visit_program(program);
+
+        // Comments in templates are part of the template
+        collector.skipped.sort_by_key(|(span, _)| span.start);
+        let mut skipped: Vec<(Span, Skip)> = Vec::with_capacity(collector.skipped.len());
+        for (span, skip) in collector.skipped.drain(..) {
+            if skipped
+                .last()
+                .is_some_and(|(last, _)| span.start < last.end)
+            {
+                continue;
+            }
+            skipped.push((span, skip));
+        }
+        collector.skipped = skipped;
+
+        collector
+    }
+
+    /// The skipped span `offset` is in.
+    fn skip_at(&self, offset: usize) -> Option<(Span, Skip)> {
+        let index = self
+            .skipped
+            .partition_point(|(span, _)| span.start as usize <= offset);
+        let (span, skip) = *self.skipped.get(index.checked_sub(1)?)?;
+
+        (offset < span.end as usize).then_some((span, skip))
+    }
+
+    /// Start of the first code after `offset`, skipping whitespace and comments.
+    fn next_code(&self, mut offset: usize) -> Option<usize> {
+        loop {
+            offset = self.source.len() - self.source[offset..].trim_start().len();
+            if offset == self.source.len() {
+                return None;
+            }
+
+            match self.skip_at(offset) {
+                Some((span, Skip::Comment)) => offset = span.end as usize,
+                _ => return Some(offset),
+            }
+        }
+    }
+
+    /// End of the last code before `offset`, skipping whitespace and comments.
+    fn previous_code(&self, mut offset: usize) -> Option<usize> {
+        loop {
+            offset = self.source[..offset].trim_end().len();
+            if offset == 0 {
+                return None;
+            }
+
+            match self.skip_at(offset - 1) {
+                Some((span, Skip::Comment)) => offset = span.start as usize,
+                _ => return Some(offset),
+            }
+        }
+    }
+
+    /// Index of the last non whitespace character before `offset`.
+    fn last_char_before(&self, offset: u32) -> usize {
+        self.source[..offset as usize]
+            .trim_end()
+            .len()
+            .saturating_sub(1)
+    }
+
+    /// A statement body without braces, indented below its keyword.
+    fn body(&mut self, body: &Statement) {
+        if matches!(body, Statement::BlockStatement(_)) {
+            return;
+        }
+
+        let span = body.span();
+        self.regions.push(Pair {
+            open: self.last_char_before(span.start),
+            close: span.end as usize,
+            body: true,
+        });
+    }
+
+    fn list(&mut self, statement: Span, specifiers: Vec<Span>) {
+        let (Some(first), Some(last)) = (specifiers.first(), specifiers.last()) else {
+            return;
+        };
+        let Some(open) = self.source[..first.start as usize].rfind('{') else {
+            return;
+        };
+        let Some(close) = self.source[last.end as usize..].find('}') else {
+            return;
+        };
+
+        let close = last.end as usize + close + 1;
+        self.lists.push(List {
+            statement,
+            braces: Span::new(open as u32, close as u32),
+            specifiers,
+        });
+    }
+
+    fn edits(&self, config: &FmtConfig) -> Vec<(Span, String)> {
+        let semicolons = self
+            .statements
+            .iter()
+            .filter_map(|statement| self.semicolon(*statement, config.semicolons));
+        let lists = self.lists.iter().filter_map(|list| self.wrap(list, config));
+        let quotes = self
+            .strings
+            .iter()
+            .filter_map(|string| requote(self.source, *string, config.quotes));
+
+        let closes = self
+            .pairs()
+            .into_iter()
+            .filter_map(|pair| self.close_on_own_line(pair));
+
+        semicolons
+            .chain(lists)
+            .chain(quotes)
+            .chain(closes)
+            .collect()
+    }
+
+    /// Moves the closing bracket of a pair whose opening bracket ends its line onto a
+    /// line of its own, `[\n1,\n2]` becomes `[\n1,\n2\n]`.
+    fn close_on_own_line(&self, pair: Pair) -> Option<(Span, String)> {
+        let bytes = self.source.as_bytes();
+        if pair.body
+            || !matches!(bytes[pair.open], b'(' | b'[' | b'{')
+            || !matches!(bytes[pair.close], b')' | b']' | b'}')
+        {
+            return None;
+        }
+
+        let after_open = &self.source[pair.open + 1..];
+        let opens_line = after_open[..after_open.find('\n')?].trim().is_empty();
+        let line_start = self.source[..pair.close].rfind('\n')?;
+        if !opens_line
+            || line_start < pair.open
+            || self.source[line_start..pair.close].trim().is_empty()
+        {
+            return None;
+        }
+
+        let close = pair.close as u32;
+        Some((Span::new(close, close), "\n".to_string()))
+    }
+
+    /// Adds or removes the semicolon at the end of `statement`.
+    fn semicolon(&self, statement: Span, semicolons: bool) -> Option<(Span, String)> {
+        let end = statement.end as usize;
+        let existing = if self.source[..end].ends_with(';') {
+            Some(end - 1)
+        } else {
+            let next = self.source.len() - self.source[end..].trim_start_matches([' ', '\t']).len();
+            self.source[next..].starts_with(';').then_some(next)
+        };
+
+        match existing {
+            None if semicolons => Some((Span::new(statement.end, statement.end), ";".to_string())),
+            Some(position) if !semicolons && self.can_remove_semicolon(position) => Some((
+                Span::new(position as u32, position as u32 + 1),
+                String::new(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Whether the statement still ends at the semicolon at `position` without it.
+    fn can_remove_semicolon(&self, position: usize) -> bool {
+        let Some(next) = self.next_code(position + 1) else {
+            return true;
+        };
+
+        if !self.source[position..next].contains('\n') {
+            return self.source[next..].starts_with('}');
+        }
+        // These would continue the statement on the next line
+        !self.source[next..].starts_with(['(', '[', '`', '+', '-', '/', '*', '<', '.', ','])
+    }
+
+    /// Puts an import or export list on one line, or one specifier per line when it
+    /// doesn't fit in the line width.
+    fn wrap(&self, list: &List, config: &FmtConfig) -> Option<(Span, String)> {
+        let (start, end) = (list.braces.start as usize, list.braces.end as usize);
+        // Comments in the list can't be moved around
+        if self.skipped.iter().any(|(span, skip)| {
+            *skip == Skip::Comment && (start..end).contains(&(span.start as usize))
+        }) {
+            return None;
+        }
+
+        let mut specifiers = Vec::new();
+        let mut position = start + 1;
+        for span in &list.specifiers {
+            let text = self.source[position..span.end as usize].trim();
+            specifiers.push(text.trim_start_matches(',').trim_start());
+            position = span.end as usize;
+        }
+
+        let inline = format!("{{ {} }}", specifiers.join(", "));
+        let line_start = self.source[..start]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        let rest = self.source[end..list.statement.end as usize].trim_end_matches(';');
+        let width = self.source[line_start..start].chars().count()
+            + inline.chars().count()
+            + rest.chars().count()
+            + usize::from(config.semicolons);
+
+        let text = if width <= config.line_width {
+            inline
+        } else {
+            format!("{{\n{},\n}}", specifiers.join(",\n"))
+        };
+
+        (text != self.source[start..end]).then_some((list.braces, text))
+    }
+
+    /// Every bracket pair and region, sorted by where they open, outer ones first.
+    fn pairs(&self) -> Vec<Pair> {
+        let bytes = self.source.as_bytes();
+        let mut pairs = self.regions.clone();
+        let mut open = Vec::new();
+        let mut skipped = self.skipped.iter().peekable();
+        let mut index = 0;
+
+        while index < bytes.len() {
+            if let Some((span, _)) = skipped.next_if(|(span, _)| span.start as usize <= index) {
+                index = index.max(span.end as usize);
+                continue;
+            }
+
+            match bytes[index] {
+                b'(' | b'[' | b'{' => open.push(index),
+                b')' | b']' | b'}' => {
+                    if let Some(start) = open.pop() {
+                        pairs.push(Pair {
+                            open: start,
+                            close: index,
+                            body: false,
+                        });
+                    }
+                }
+                _ => {}
+            }
+            index += 1;
+        }
+
+        pairs.sort_by_key(|pair| (pair.open, Reverse(pair.close)));
+        pairs
+    }
+
+    /// Whether the line starting at `offset` continues an expression, like `.then()`
+    /// or `? a : b`.
+    fn starts_with_operator(&self, offset: usize) -> bool {
+        if self.skip_at(offset).is_some() {
+            return false;
+        }
+
+        let text = &self.source[offset..];
+        (text.starts_with('.') && !text.starts_with("..."))
+            || text.starts_with(['?', ':', '&', '|'])
+    }
+
+    /// Whether the code ending at `end` is an operator waiting for its right side.
+    fn ends_with_operator(&self, end: usize) -> bool {
+        if self.skip_at(end - 1).is_some() {
+            return false;
+        }
+
+        let text = &self.source[..end];
+        if ["++", "--", "..."].iter().any(|op| text.ends_with(op)) {
+            return false;
+        }
+        text.ends_with("=>")
+            || text.ends_with(['=', '&', '|', '?', '+', '-', '*', '/', '%', '^', '.'])
+    }
+
+    /// Indents every line by the brackets and regions it's in. Lines continuing an
+    /// expression get one more level.
+    fn reindent(&self, config: &FmtConfig) -> String {
+        let indent = " ".repeat(config.indent_width);
+        let pairs = self.pairs();
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(self.source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        let line_of = |offset: usize| line_starts.partition_point(|start| *start <= offset) - 1;
+
+        let mut levels = vec![0; line_starts.len()];
+        let mut lines = Vec::with_capacity(line_starts.len());
+        let mut stack: Vec<(Pair, usize)> = Vec::new();
+        let mut next_pair = 0;
+        let mut level = 0;
+
+        for (number, &start) in line_starts.iter().enumerate() {
+            let end = line_starts
+                .get(number + 1)
+                .map_or(self.source.len(), |next| next - 1);
+            let line = &self.source[start..end];
+            let text = line.trim();
+            let first = end - line.trim_start().len();
+            levels[number] = level;
+
+            match start
+                .checked_sub(1)
+                .and_then(|newline| self.skip_at(newline))
+            {
+                // The content of strings and templates
+                Some((_, Skip::Literal)) => {
+                    lines.push((line.to_string(), true));
+                    continue;
+                }
+                Some((span, Skip::Comment)) => {
+                    // ` * ` lines of doc comments follow the comment
+                    let line = if text.starts_with('*') {
+                        let level = levels[line_of(span.start as usize)];
+                        format!("{} {text}", indent.repeat(level))
+                    } else {
+                        line.trim_end().to_string()
+                    };
+                    lines.push((line, true));
+                    continue;
+                }
+                _ => {}
+            }
+            if text.is_empty() {
+                lines.push((String::new(), false));
+                continue;
+            }
+
+            while let Some(pair) = pairs.get(next_pair).filter(|pair| pair.open < first) {
+                while stack.last().is_some_and(|(top, _)| top.end() <= pair.open) {
+                    stack.pop();
+                }
+                stack.push((*pair, levels[line_of(pair.open)]));
+                next_pair += 1;
+            }
+            while stack.last().is_some_and(|(top, _)| top.end() <= first) {
+                stack.pop();
+            }
+
+            let previous = self.previous_code(start);
+            let continues = self.starts_with_operator(first)
+                || previous.is_some_and(|previous| self.ends_with_operator(previous));
+            level = match stack.last() {
+                Some((pair, base)) if !pair.body && pair.close == first => *base,
+                Some((pair, base)) => {
+                    // `foo(a &&` indents by the bracket already
+                    let opened_above =
+                        previous.map(|previous| line_of(previous - 1)) == Some(line_of(pair.open));
+                    base + 1 + usize::from(continues && !opened_above)
+                }
+                None => usize::from(continues),
+            };
+            levels[number] = level;
+
+            // Whitespace at the end of a line in a template is part of it
+            let text = match self.skip_at(end) {
+                Some((_, Skip::Literal)) => line.trim_start(),
+                _ => text,
+            };
+            lines.push((format!("{}{text}", indent.repeat(level)), false));
+        }
+
+        finish_lines(lines)
+    }
+}
+
+impl<'a> Visit<'a> for Collector<'_> {
+    fn visit_statement(&mut self, statement: &Statement<'a>) {
+        let optional_semicolon = match statement {
+            Statement::ExpressionStatement(_)
+            | Statement::VariableDeclaration(_)
+            | Statement::ReturnStatement(_)
+            | Statement::ThrowStatement(_)
+            | Statement::BreakStatement(_)
+            | Statement::ContinueStatement(_)
+            | Statement::DebuggerStatement(_)
+            | Statement::DoWhileStatement(_)
+            | Statement::ImportDeclaration(_)
+            | Statement::ExportAllDeclaration(_)
+            | Statement::TSTypeAliasDeclaration(_)
+            | Statement::TSImportEqualsDeclaration(_)
+            | Statement::TSExportAssignment(_) => true,
+            // Overloads and `declare function`
+            Statement::FunctionDeclaration(function) => function.body.is_none(),
+            Statement::ExportNamedDeclaration(decl) => match &decl.declaration {
+                None
+                | Some(
+                    Declaration::VariableDeclaration(_)
+                    | Declaration::TSTypeAliasDeclaration(_)
+                    | Declaration::TSImportEqualsDeclaration(_),
+                ) => true,
+                Some(Declaration::FunctionDeclaration(function)) => function.body.is_none(),
+                Some(_) => false,
+            },
+            Statement::ExportDefaultDeclaration(decl) => !matches!(
+                decl.declaration,
+                ExportDefaultDeclarationKind::FunctionDeclaration(_)
+                    | ExportDefaultDeclarationKind::ClassDeclaration(_)
+                    | ExportDefaultDeclarationKind::TSInterfaceDeclaration(_)
+            ),
+            _ => false,
+        };
+        if optional_semicolon {
+            self.statements.push(statement.span());
+        }
+
+        match statement {
+            Statement::ImportDeclaration(decl) => {
+                let specifiers = decl
+                    .specifiers
+                    .iter()
+                    .flatten()
+                    .filter_map(|specifier| match specifier {
+                        ImportDeclarationSpecifier::ImportSpecifier(specifier) => {
+                            Some(specifier.span)
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                self.list(decl.span, specifiers);
+            }
+            Statement::ExportNamedDeclaration(decl) if decl.declaration.is_none() => {
+                let specifiers = decl.specifiers.iter().map(|specifier| specifier.span);
+                self.list(decl.span, specifiers.collect());
+            }
+            Statement::IfStatement(stmt) => {
+                self.body(&stmt.consequent);
+                match &stmt.alternate {
+                    Some(Statement::IfStatement(_)) | None => {}
+                    Some(alternate) => self.body(alternate),
+                }
+            }
+            Statement::ForStatement(stmt) => self.body(&stmt.body),
+            Statement::ForInStatement(stmt) => self.body(&stmt.body),
+            Statement::ForOfStatement(stmt) => self.body(&stmt.body),
+            Statement::WhileStatement(stmt) => self.body(&stmt.body),
+            Statement::DoWhileStatement(stmt) => self.body(&stmt.body),
+            _ => {}
+        }
+
+        walk::walk_statement(self, statement);
+    }
+
+    fn visit_class(&mut self, class: &Class<'a>) {
+        for element in &class.body.body {
+            match element {
+                ClassElement::PropertyDefinition(property) => {
+                    // Without its semicolon `get;` turns the next member into a getter
+                    let modifier = property.value.is_none()
+                        && property.type_annotation.is_none()
+                        && matches!(&property.key, PropertyKey::StaticIdentifier(key)
+                            if matches!(key.name.as_str(), "get" | "set" | "static" | "async" | "accessor"));
+                    if !modifier {
+                        self.statements.push(property.span);
+                    }
+                }
+                ClassElement::MethodDefinition(method) if method.value.body.is_none() => {
+                    self.statements.push(method.span);
+                }
+                _ => {}
+            }
+        }
+
+        walk::walk_class(self, class);
+    }
+
+    fn visit_switch_case(&mut self, case: &SwitchCase<'a>) {
+        if let Some(first) = case.consequent.first() {
+            self.regions.push(Pair {
+                open: self.last_char_before(first.span().start),
+                close: case.span.end as usize,
+                body: true,
+            });
+        }
+
+        walk::walk_switch_case(self, case);
+    }
+
+    fn visit_jsx_element(&mut self, element: &JSXElement<'a>) {
+        let opening = &element.opening_element;
+        let end = opening.span.end as usize;
+
+        if !opening.attributes.is_empty() {
+            let close = if opening.self_closing {
+                end - 2
+            } else {
+                end - 1
+            };
+            self.regions.push(Pair {
+                open: opening.span.start as usize,
+                close,
+                body: false,
+            });
+        }
+        if let Some(closing) = &element.closing_element {
+            self.regions.push(Pair {
+                open: end - 1,
+                close: closing.span.start as usize,
+                body: false,
+            });
+        }
+
+        walk::walk_jsx_element(self, element);
+    }
+
+    fn visit_jsx_fragment(&mut self, fragment: &JSXFragment<'a>) {
+        self.regions.push(Pair {
+            open: fragment.opening_fragment.span.end as usize - 1,
+            close: fragment.closing_fragment.span.start as usize,
+            body: false,
+        });
+
+        walk::walk_jsx_fragment(self, fragment);
+    }
+
+    fn visit_jsx_text(&mut self, text: &JSXText<'a>) {
+        self.skipped.push((text.span, Skip::Text));
+    }
+
+    fn visit_jsx_attribute(&mut self, attribute: &JSXAttribute<'a>) {
+        // Quotes of attributes are left alone, JSX strings have no escapes
+        match &attribute.value {
+            Some(JSXAttributeValue::StringLiteral(value)) => {
+                self.skipped.push((value.span, Skip::Literal));
+            }
+            _ => walk::walk_jsx_attribute(self, attribute),
+        }
+    }
+
+    fn visit_string_literal(&mut self, literal: &StringLiteral<'a>) {
+        self.skipped.push((literal.span, Skip::Literal));
+        self.strings.push(literal.span);
+    }
+
+    fn visit_template_literal(&mut self, literal: &TemplateLiteral<'a>) {
+        // Expressions in templates are left as they are
+        self.skipped.push((literal.span, Skip::Literal));
+    }
+
+    fn visit_ts_template_literal_type(&mut self, literal: &TSTemplateLiteralType<'a>) {
+        self.skipped.push((literal.span, Skip::Literal));
+    }
+
+    fn visit_expression(&mut self, expr: &Expression<'a>) {
+        if let Expression::RegExpLiteral(literal) = expr {
+            self.skipped.push((literal.span, Skip::Literal));
+        }
+
+        walk::walk_expression(self, expr);
+    }
+}
+
+/// Span of a comment with its `//` or `/* */`, which the parser leaves out.
+fn comment_span(source: &str, span: Span) -> Span {
+    let (mut start, mut end) = (span.start as usize, span.end as usize);
+    if !source[start..].starts_with("//") && !source[start..].starts_with("/*") {
+        start = start.saturating_sub(2);
+    }
+    if source[start..].starts_with("/*") && !source[..end].ends_with("*/") {
+        end = (end + 2).min(source.len());
+    }
+
+    Span::new(start as u32, end as u32)
+}
+
+/// The string literal at `span` with the configured quotes, unless that needs more
+/// escapes than it has now.
+fn requote(source: &str, span: Span, style: QuoteStyle) -> Option<(Span, String)> {
+    let (quote, other) = match style {
+        QuoteStyle::Double => ('"', '\''),
+        QuoteStyle::Single => ('\'', '"'),
+    };
+    let raw = &source[span.start as usize..span.end as usize];
+    if !raw.starts_with(other) {
+        return None;
+    }
+    let content = &raw[1..raw.len() - 1];
+
+    let (mut quotes, mut others) = (0, 0);
+    let mut chars = content.chars();
+    while let Some(char) = chars.next() {
+        let char = match char {
+            '\\' => chars.next().unwrap_or_default(),
+            char => char,
+        };
+        if char == quote {
+            quotes += 1;
+        } else if char == other {
+            others += 1;
+        }
+    }
+    if quotes > others {
+        return None;
+    }
+
+    let mut text = String::from(quote);
+    let mut chars = content.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => match chars.next() {
+                Some(char) if char == other => text.push(char),
+                Some(char) => {
+                    text.push('\\');
+                    text.push(char);
+                }
+                None => text.push('\\'),
+            },
+            char if char == quote => {
+                text.push('\\');
+                text.push(char);
+            }
+            char => text.push(char),
+        }
+    }
+    text.push(quote);
+
+    Some((span, text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_script() {
+        let config = FmtConfig::default();
+        let source = "import {b,a} from 'x'\nfunction f(x) {\nif (x)\nreturn 'it\\'s'\n    return [\n1,\n    2]\n}\n\n\n";
+
+        assert_eq!(
+            format(Path::new("a.ts"), source, &config).unwrap(),
+            "import { b, a } from \"x\";\nfunction f(x) {\n  if (x)\n    return \"it's\";\n  return [\n    1,\n    2\n  ];\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_format_without_semicolons() {
+        let config = FmtConfig {
+            quotes: QuoteStyle::Single,
+            semicolons: false,
+            ..Default::default()
+        };
+        let source = "const a = \"b\";\nfoo();\n(bar)();\nconst c = 'say \"hi\"';\n";
+
+        assert_eq!(
+            format(Path::new("a.js"), source, &config).unwrap(),
+            "const a = 'b'\nfoo();\n(bar)()\nconst c = 'say \"hi\"'\n"
+        );
+    }
+
+    #[test]
+    fn test_format_indent_width() {
+        let config = FmtConfig {
+            indent_width: 4,
+            ..Default::default()
+        };
+        let source = "class A {\nrun() {\nreturn foo(\n1,\n2);\n}\n}\n";
+
+        assert_eq!(
+            format(Path::new("a.ts"), source, &config).unwrap(),
+            "class A {\n    run() {\n        return foo(\n            1,\n            2\n        );\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_format_line_width() {
+        let config = FmtConfig {
+            line_width: 30,
+            ..Default::default()
+        };
+        let source = "import {a,b} from \"x\";\nimport {alpha,beta,gamma,delta} from \"y\";\nimport {\nc,\n} from \"z\";\nconst long = [\"a long string\", \"that stays on the line\"];\n";
+
+        assert_eq!(
+            format(Path::new("a.ts"), source, &config).unwrap(),
+            "import { a, b } from \"x\";\nimport {\n  alpha,\n  beta,\n  gamma,\n  delta,\n} from \"y\";\nimport { c } from \"z\";\nconst long = [\"a long string\", \"that stays on the line\"];\n"
+        );
+    }
+
+    #[test]
+    fn test_format_quotes() {
+        let source = "const a = 'b';\nconst c = \"d\";\nconst e = 'say \"hi\"';\n";
+        let double = FmtConfig::default();
+        let single = FmtConfig {
+            quotes: QuoteStyle::Single,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            format(Path::new("a.js"), source, &double).unwrap(),
+            "const a = \"b\";\nconst c = \"d\";\nconst e = 'say \"hi\"';\n"
+        );
+        assert_eq!(
+            format(Path::new("a.js"), source, &single).unwrap(),
+            "const a = 'b';\nconst c = 'd';\nconst e = 'say \"hi\"';\n"
+        );
+    }
+
+    #[test]
+    fn test_format_semicolons() {
+        let source = "let a = 1\nlet b = 2;\n[a, b].forEach(log)\n";
+        let with = FmtConfig::default();
+        let without = FmtConfig {
+            semicolons: false,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            format(Path::new("a.js"), source, &with).unwrap(),
+            "let a = 1;\nlet b = 2;\n[a, b].forEach(log);\n"
+        );
+        assert_eq!(
+            format(Path::new("a.js"), source, &without).unwrap(),
+            "let a = 1\nlet b = 2;\n[a, b].forEach(log)\n"
+        );
+    }
+}
@@ -23,6 +23,34 @@ where
     }
 }
 
+/// Directories that never contain the project's own sources.
+pub const IGNORED_DIRS: [&str; 4] = [".git", "node_modules", "ike_modules", "target"];
+
+/// Files under `dir` for which `filter` returns true, without descending into
+/// [`IGNORED_DIRS`].
+pub fn walk_files(dir: &Path, filter: &dyn Fn(&Path) -> bool) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        if IGNORED_DIRS.contains(&name) {
+            continue;
+        }
+
+        if path.is_dir() {
+            paths.extend(walk_files(&path, filter)?);
+        } else if filter(&path) {
+            paths.push(path);
+        }
+    }
+
+    Ok(paths)
+}
+
 // Determine if a path is a file using regex
 pub fn is_file(path: &str) -> bool {
     let re = Regex::new(r"\.[a-zA-Z0-9]+$").unwrap();
@@ -0,0 +1,323 @@
+use ike_toml::IkeToml;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+    sync::{LazyLock, Mutex},
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ImportMapError {
+    #[error("Failed to read import map <cyan>{0}<r>: {1}")]
+    FailedToRead(PathBuf, String),
+    #[error("Invalid import map <cyan>{0}<r>: {1}")]
+    Invalid(PathBuf, String),
+    #[error("Import map entry <cyan>{0}<r> ends with a slash, so its target <cyan>{1}<r> must end with one too")]
+    InvalidPrefixTarget(String, String),
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct ImportMapJson {
+    #[serde(default)]
+    imports: HashMap<String, String>,
+    #[serde(default)]
+    scopes: HashMap<String, HashMap<String, String>>,
+}
+
+/// Entries sorted by key length, longest first, so the most specific prefix wins.
+type SpecifierMap = Vec<(String, String)>;
+
+/// Import map as described by https://github.com/WICG/import-maps.
+///
+/// Keys and targets starting with `./`, `../` or `/` are resolved against the directory
+/// of the map (the import map file or ike.toml), other targets are resolved again as a
+/// bare specifier. Relative specifiers match path keys by the file they point to. Keys
+/// ending with `/` map every specifier that starts with them.
+#[derive(Debug, Clone, Default)]
+pub struct ImportMap {
+    imports: SpecifierMap,
+    /// Scopes are keyed by the absolute path prefix of the importing module.
+    scopes: Vec<(String, SpecifierMap)>,
+}
+
+impl ImportMap {
+    pub fn from_file(path: &Path) -> Result<Self, ImportMapError> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| ImportMapError::FailedToRead(path.to_path_buf(), e.to_string()))?;
+        let json: ImportMapJson = serde_json::from_str(&source)
+            .map_err(|e| ImportMapError::Invalid(path.to_path_buf(), e.to_string()))?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+
+        Self::new(&json.imports, &json.scopes, base_dir)
+    }
+
+    /// Import map from the `[imports]` and `[scopes]` tables of ike.toml.
+    pub fn from_manifest(manifest: &IkeToml) -> Result<Self, ImportMapError> {
+        let base_dir = manifest.dir().unwrap_or(Path::new(""));
+
+        Self::new(&manifest.toml.imports, &manifest.toml.scopes, base_dir)
+    }
+
+    fn new(
+        imports: &HashMap<String, String>,
+        scopes: &HashMap<String, HashMap<String, String>>,
+        base_dir: &Path,
+    ) -> Result<Self, ImportMapError> {
+        let mut scopes = scopes
+            .iter()
+            .map(|(scope, map)| {
+                Ok((
+                    resolve_target(scope, base_dir),
+                    specifier_map(map, base_dir)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, ImportMapError>>()?;
+        scopes.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then(a.0.cmp(&b.0)));
+
+        Ok(Self {
+            imports: specifier_map(imports, base_dir)?,
+            scopes,
+        })
+    }
+
+    /// Adds the entries of `other`, replacing the ones that are already mapped.
+    pub fn extend(&mut self, other: ImportMap) {
+        merge(&mut self.imports, other.imports);
+
+        for (scope, map) in other.scopes {
+            match self
+                .scopes
+                .iter_mut()
+                .find(|(existing, _)| *existing == scope)
+            {
+                Some((_, existing)) => merge(existing, map),
+                None => self.scopes.push((scope, map)),
+            }
+        }
+        self.scopes
+            .sort_by(|a, b| b.0.len().cmp(&a.0.len()).then(a.0.cmp(&b.0)));
+    }
+
+    /// Maps a specifier imported from `referrer`. Scopes that contain the referrer are
+    /// tried from the most to the least specific before the top level imports.
+    pub fn resolve(&self, specifier: &str, referrer: &Path) -> Option<String> {
+        // Like the spec does with URLs, `./a.ts` is looked up as the path it points to
+        let resolved;
+        let specifier = if is_path(specifier) && referrer.is_absolute() {
+            let dir = if referrer.is_dir() {
+                referrer
+            } else {
+                referrer.parent().unwrap_or(referrer)
+            };
+            resolved = resolve_target(specifier, dir);
+            resolved.as_str()
+        } else {
+            specifier
+        };
+        let referrer = referrer.to_string_lossy();
+
+        self.scopes
+            .iter()
+            .filter(|(scope, _)| {
+                if scope.ends_with('/') {
+                    referrer.starts_with(scope.as_str())
+                } else {
+                    referrer == scope.as_str()
+                }
+            })
+            .find_map(|(_, map)| resolve_in(map, specifier))
+            .or_else(|| resolve_in(&self.imports, specifier))
+    }
+}
+
+fn specifier_map(
+    map: &HashMap<String, String>,
+    base_dir: &Path,
+) -> Result<SpecifierMap, ImportMapError> {
+    let mut entries = map
+        .iter()
+        .map(|(key, target)| {
+            if key.ends_with('/') && !target.ends_with('/') {
+                return Err(ImportMapError::InvalidPrefixTarget(
+                    key.clone(),
+                    target.clone(),
+                ));
+            }
+
+            Ok((
+                resolve_target(key, base_dir),
+                resolve_target(target, base_dir),
+            ))
+        })
+        .collect::<Result<SpecifierMap, ImportMapError>>()?;
+    entries.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then(a.0.cmp(&b.0)));
+
+    Ok(entries)
+}
+
+fn merge(map: &mut SpecifierMap, other: SpecifierMap) {
+    for (key, target) in other {
+        match map.iter_mut().find(|(existing, _)| *existing == key) {
+            Some(entry) => entry.1 = target,
+            None => map.push((key, target)),
+        }
+    }
+    map.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then(a.0.cmp(&b.0)));
+}
+
+fn resolve_in(map: &SpecifierMap, specifier: &str) -> Option<String> {
+    if let Some((_, target)) = map.iter().find(|(key, _)| key == specifier) {
+        return Some(target.clone());
+    }
+
+    map.iter()
+        .filter(|(key, _)| key.ends_with('/'))
+        .find_map(|(key, target)| {
+            let rest = specifier.strip_prefix(key.as_str())?;
+            Some(format!("{target}{rest}"))
+        })
+}
+
+fn is_path(specifier: &str) -> bool {
+    specifier.starts_with("./")
+        || specifier.starts_with("../")
+        || specifier == "."
+        || specifier == ".."
+        || Path::new(specifier).is_absolute()
+}
+
+/// Turns path-like keys and targets into absolute paths, keeping a trailing slash.
+fn resolve_target(target: &str, base_dir: &Path) -> String {
+    if !is_path(target) {
+        return target.to_string();
+    }
+
+    let mut normalized = PathBuf::new();
+    for component in base_dir.join(target).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component.as_os_str()),
+        }
+    }
+
+    let mut resolved = normalized.to_string_lossy().to_string();
+    if target.ends_with('/') && !resolved.ends_with('/') {
+        resolved.push('/');
+    }
+
+    resolved
+}
+
+static IMPORT_MAP: LazyLock<Mutex<ImportMap>> = LazyLock::new(Default::default);
+
+pub fn set_import_map(import_map: ImportMap) {
+    *IMPORT_MAP.lock().unwrap() = import_map;
+}
+
+pub fn import_map() -> ImportMap {
+    IMPORT_MAP.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import_map(imports: &[(&str, &str)], scopes: &[(&str, &[(&str, &str)])]) -> ImportMap {
+        let to_map = |entries: &[(&str, &str)]| {
+            entries
+                .iter()
+                .map(|(key, target)| (key.to_string(), target.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+        let scopes = scopes
+            .iter()
+            .map(|(scope, entries)| (scope.to_string(), to_map(entries)))
+            .collect();
+
+        ImportMap::new(&to_map(imports), &scopes, Path::new("/app")).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_imports() {
+        let map = import_map(
+            &[
+                ("@app/", "./src/"),
+                ("@app/config", "./config.ts"),
+                ("lodash", "lodash-es"),
+            ],
+            &[],
+        );
+        let referrer = Path::new("/app/main.ts");
+
+        assert_eq!(
+            map.resolve("@app/utils/strings.ts", referrer).as_deref(),
+            Some("/app/src/utils/strings.ts")
+        );
+        assert_eq!(
+            map.resolve("@app/config", referrer).as_deref(),
+            Some("/app/config.ts")
+        );
+        assert_eq!(
+            map.resolve("lodash", referrer).as_deref(),
+            Some("lodash-es")
+        );
+        assert_eq!(map.resolve("yaml", referrer), None);
+    }
+
+    #[test]
+    fn test_resolve_scopes() {
+        let map = import_map(
+            &[("log", "./log.ts")],
+            &[("./legacy/", &[("log", "./legacy/log.ts")])],
+        );
+
+        assert_eq!(
+            map.resolve("log", Path::new("/app/legacy/index.ts"))
+                .as_deref(),
+            Some("/app/legacy/log.ts")
+        );
+        assert_eq!(
+            map.resolve("log", Path::new("/app/index.ts")).as_deref(),
+            Some("/app/log.ts")
+        );
+    }
+
+    #[test]
+    fn test_resolve_relative_keys() {
+        let map = import_map(&[("./shim.ts", "./real.ts"), ("./vendor/", "./lib/")], &[]);
+
+        assert_eq!(
+            map.resolve("./shim.ts", Path::new("/app/main.ts"))
+                .as_deref(),
+            Some("/app/real.ts")
+        );
+        assert_eq!(
+            map.resolve("../shim.ts", Path::new("/app/src/main.ts"))
+                .as_deref(),
+            Some("/app/real.ts")
+        );
+        assert_eq!(
+            map.resolve("./vendor/a.ts", Path::new("/app/main.ts"))
+                .as_deref(),
+            Some("/app/lib/a.ts")
+        );
+        assert_eq!(
+            map.resolve("./shim.ts", Path::new("/app/src/main.ts")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_invalid_prefix_target() {
+        let imports = HashMap::from([("@app/".to_string(), "./src".to_string())]);
+
+        assert!(matches!(
+            ImportMap::new(&imports, &HashMap::new(), Path::new("/app")),
+            Err(ImportMapError::InvalidPrefixTarget(_, _))
+        ));
+    }
+}
@@ -0,0 +1,385 @@
+use super::InstallError;
+use crate::cache::cache_dir;
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use tar::Archive;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GitReference {
+    Branch(String),
+    Rev(String),
+    DefaultBranch,
+}
+
+/// Cache of git dependencies.
+///
+/// Every remote is mirrored once into `<root>/db/<url key>` and each commit that is
+/// depended on is exported to `<root>/checkouts/<url key>/<sha>`, so checkouts are
+/// keyed by URL and commit and never change once written.
+#[derive(Debug, Clone)]
+pub struct GitCache {
+    root: PathBuf,
+}
+
+impl Default for GitCache {
+    fn default() -> Self {
+        Self::new(cache_dir().join("git"))
+    }
+}
+
+impl GitCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Returns the checkout of `url` at `reference` along with its commit SHA.
+    ///
+    /// When `locked` is a commit that is already known, the remote isn't contacted at all.
+    /// Dependencies declared by registry packages (`from_registry`) can only be fetched
+    /// over https, ssh and git.
+    pub fn checkout(
+        &self,
+        url: &str,
+        reference: &GitReference,
+        locked: Option<&str>,
+        from_registry: bool,
+    ) -> Result<(PathBuf, String), InstallError> {
+        // Anything starting with a dash would be parsed as an option by git
+        if url.starts_with('-') {
+            return Err(InstallError::InvalidGitUrl(url.to_string()));
+        }
+        let revision = revision(reference);
+        if let Some(revision) = locked
+            .into_iter()
+            .chain([revision.as_str()])
+            .find(|revision| revision.starts_with('-'))
+        {
+            return Err(InstallError::GitRevisionNotFound(
+                url.to_string(),
+                revision.to_string(),
+            ));
+        }
+
+        let key = url_key(url);
+        let db = self.root.join("db").join(&key);
+
+        let cached_sha = locked.filter(|sha| db.is_dir() && has_commit(&db, url, sha));
+        let sha = match cached_sha {
+            Some(sha) => sha.to_string(),
+            None => {
+                self.fetch(url, &db, from_registry)?;
+
+                rev_parse(&db, url, locked.unwrap_or(revision.as_str()))?
+            }
+        };
+
+        let checkout = self.root.join("checkouts").join(&key).join(&sha);
+        if !checkout.is_dir() {
+            export(&db, url, &sha, &checkout)?;
+        }
+
+        Ok((checkout, sha))
+    }
+
+    /// Clones the remote as a bare mirror, or updates the existing one.
+    fn fetch(&self, url: &str, db: &Path, from_registry: bool) -> Result<(), InstallError> {
+        if !db.is_dir() {
+            fs::create_dir_all(db)?;
+            git(db, url, &["init", "--bare", "--quiet"])?;
+        }
+
+        // Settings on the command line take precedence over the user's git config, so
+        // transports like `ext::` and `file://` stay disabled whatever it allows
+        let protocols: &[&str] = if from_registry {
+            &[
+                "-c",
+                "protocol.allow=never",
+                "-c",
+                "protocol.https.allow=always",
+                "-c",
+                "protocol.ssh.allow=always",
+                "-c",
+                "protocol.git.allow=always",
+                "-c",
+                "protocol.file.allow=never",
+                "-c",
+                "protocol.ext.allow=never",
+            ]
+        } else {
+            &[]
+        };
+
+        let mut args = protocols.to_vec();
+        args.extend([
+            "fetch",
+            "--quiet",
+            "--force",
+            "--tags",
+            "--",
+            url,
+            "+refs/heads/*:refs/heads/*",
+            "+HEAD:refs/remotes/origin/HEAD",
+        ]);
+        git(db, url, &args)?;
+
+        Ok(())
+    }
+}
+
+fn revision(reference: &GitReference) -> String {
+    match reference {
+        GitReference::Branch(branch) => format!("refs/heads/{branch}"),
+        GitReference::Rev(rev) => rev.clone(),
+        GitReference::DefaultBranch => "refs/remotes/origin/HEAD".to_string(),
+    }
+}
+
+fn has_commit(db: &Path, url: &str, sha: &str) -> bool {
+    git(db, url, &["cat-file", "-e", &format!("{sha}^{{commit}}")]).is_ok()
+}
+
+fn rev_parse(db: &Path, url: &str, revision: &str) -> Result<String, InstallError> {
+    let output = git(
+        db,
+        url,
+        &["rev-parse", "--verify", &format!("{revision}^{{commit}}")],
+    )
+    .map_err(|_| InstallError::GitRevisionNotFound(url.to_string(), revision.to_string()))?;
+
+    Ok(String::from_utf8_lossy(&output).trim().to_string())
+}
+
+/// Writes the tree of a commit to `dest` using `git archive`, which leaves the mirror
+/// untouched.
+fn export(db: &Path, url: &str, sha: &str, dest: &Path) -> Result<(), InstallError> {
+    let archive = git(db, url, &["archive", "--format=tar", sha])?;
+    let staging = dest.with_extension("tmp");
+
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+
+    if let Err(e) = Archive::new(archive.as_slice()).unpack(&staging) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(InstallError::GitFailed(url.to_string(), e.to_string()));
+    }
+
+    if let Err(e) = fs::rename(&staging, dest) {
+        let _ = fs::remove_dir_all(&staging);
+        if !dest.is_dir() {
+            return Err(e.into());
+        }
+    }
+
+    Ok(())
+}
+
+fn git(dir: &Path, url: &str, args: &[&str]) -> Result<Vec<u8>, InstallError> {
+    let output = Command::new("git")
+        .arg("--git-dir")
+        .arg(dir)
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => InstallError::GitNotFound,
+            _ => InstallError::GitFailed(url.to_string(), e.to_string()),
+        })?;
+
+    if !output.status.success() {
+        return Err(InstallError::GitFailed(
+            url.to_string(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(output.stdout)
+}
+
+/// Readable, collision free directory name for a remote: `<repo name>-<hash of url>`.
+fn url_key(url: &str) -> String {
+    let name = url
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .rsplit(['/', '\\', ':'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect::<String>();
+    let hash: String = Sha256::digest(url.as_bytes())
+        .iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    if name.is_empty() {
+        hash
+    } else {
+        format!("{name}-{hash}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs git in a work tree, with an identity so commits work anywhere.
+    fn run(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=ike", "-c", "user.email=ike@example.com"])
+            .args(["-c", "commit.gpgsign=false", "-c", "tag.gpgsign=false"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn commit(dir: &Path, version: &str) -> String {
+        fs::write(
+            dir.join("index.js"),
+            format!("export const version = {version};"),
+        )
+        .unwrap();
+        run(dir, &["add", "index.js"]);
+        run(dir, &["commit", "--quiet", "-m", version]);
+
+        run(dir, &["rev-parse", "HEAD"])
+    }
+
+    #[test]
+    fn test_checkout() {
+        if Command::new("git").arg("--version").output().is_err() {
+            return;
+        }
+
+        let dir = std::env::temp_dir().join(format!("ike-git-test-{}", std::process::id()));
+        let repo = dir.join("utils");
+        fs::create_dir_all(&repo).unwrap();
+        run(&repo, &["init", "--quiet", "-b", "main"]);
+
+        let first = commit(&repo, "1");
+        run(&repo, &["tag", "v1"]);
+        let second = commit(&repo, "2");
+        run(&repo, &["checkout", "--quiet", "-b", "next"]);
+        let third = commit(&repo, "3");
+        run(&repo, &["checkout", "--quiet", "main"]);
+
+        let cache = GitCache::new(dir.join("cache"));
+        let url = format!("file://{}", repo.display());
+        let checkout = |reference: GitReference| {
+            let (path, sha) = cache.checkout(&url, &reference, None, false).unwrap();
+            (fs::read_to_string(path.join("index.js")).unwrap(), sha)
+        };
+        let version = |version: &str| format!("export const version = {version};");
+
+        assert_eq!(
+            checkout(GitReference::Branch("main".to_string())),
+            (version("2"), second.clone())
+        );
+        assert_eq!(
+            checkout(GitReference::Branch("next".to_string())),
+            (version("3"), third)
+        );
+        assert_eq!(
+            checkout(GitReference::Rev("v1".to_string())),
+            (version("1"), first.clone())
+        );
+        assert_eq!(
+            checkout(GitReference::Rev(first[..7].to_string())),
+            (version("1"), first)
+        );
+        assert_eq!(
+            checkout(GitReference::DefaultBranch),
+            (version("2"), second.clone())
+        );
+
+        // Locked commits are checked out without contacting the remote
+        fs::remove_dir_all(&repo).unwrap();
+        let (path, sha) = cache
+            .checkout(
+                &url,
+                &GitReference::Branch("main".to_string()),
+                Some(&second),
+                false,
+            )
+            .unwrap();
+        assert_eq!(sha, second);
+        assert_eq!(
+            fs::read_to_string(path.join("index.js")).unwrap(),
+            version("2")
+        );
+        assert!(matches!(
+            cache.checkout(&url, &GitReference::Rev("v2".to_string()), None, false),
+            Err(InstallError::GitFailed(..))
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_checkout_untrusted() {
+        if Command::new("git").arg("--version").output().is_err() {
+            return;
+        }
+
+        let dir =
+            std::env::temp_dir().join(format!("ike-git-untrusted-test-{}", std::process::id()));
+        let repo = dir.join("utils");
+        fs::create_dir_all(&repo).unwrap();
+        run(&repo, &["init", "--quiet", "-b", "main"]);
+        commit(&repo, "1");
+
+        let cache = GitCache::new(dir.join("cache"));
+        let url = format!("file://{}", repo.display());
+        let main = GitReference::Branch("main".to_string());
+
+        // Registry packages can't make git read local repositories or run commands
+        assert!(matches!(
+            cache.checkout(&url, &main, None, true),
+            Err(InstallError::GitFailed(..))
+        ));
+        assert!(matches!(
+            cache.checkout("ext::sh -c touch% /tmp/pwned", &main, None, true),
+            Err(InstallError::GitFailed(..))
+        ));
+        assert!(matches!(
+            cache.checkout("--upload-pack=touch /tmp/pwned", &main, None, false),
+            Err(InstallError::InvalidGitUrl(..))
+        ));
+        assert!(matches!(
+            cache.checkout(
+                &url,
+                &GitReference::Rev("--output=x".to_string()),
+                None,
+                false
+            ),
+            Err(InstallError::GitRevisionNotFound(..))
+        ));
+        assert!(cache.checkout(&url, &main, None, false).is_ok());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_url_key() {
+        let key = url_key("https://github.com/ike-js/utils.git");
+
+        assert!(key.starts_with("utils-"));
+        assert_eq!(key.len(), "utils-".len() + 16);
+        assert_ne!(key, url_key("https://gitlab.com/ike-js/utils.git"));
+    }
+}
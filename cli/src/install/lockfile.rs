@@ -0,0 +1,311 @@
+use super::{InstallError, InstalledPackage, PackageSource};
+use ike_toml::{Dependency, IkeToml};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Component, Path, PathBuf},
+};
+
+pub const LOCKFILE_NAME: &str = "ike.lock";
+pub const LOCKFILE_VERSION: u32 = 1;
+
+const HEADER: &str = "# This file is generated by `ike install`, do not edit it by hand.\n\n";
+
+/// Contents of `ike.lock`.
+///
+/// Besides the resolved packages it records the requirements of ike.toml that produced
+/// it, so `ike install --frozen` can tell when the two disagree. In a workspace the
+/// requirements of each member are recorded under `[members.<name>]`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Lockfile {
+    pub version: u32,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, String>,
+    #[serde(default, rename = "dev-dependencies")]
+    pub dev_dependencies: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub members: BTreeMap<String, MemberRequirements>,
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct MemberRequirements {
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, String>,
+    #[serde(default, rename = "dev-dependencies")]
+    pub dev_dependencies: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    /// `registry+<url>`, `path+<dir relative to the project>` or `git+<url>`.
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tarball: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Commit SHA of git dependencies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// Edges of the dependency graph as `name@range`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+}
+
+impl Lockfile {
+    pub fn read(path: &Path) -> Result<Option<Self>, InstallError> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let source = std::fs::read_to_string(path)?;
+        let lockfile: Lockfile = toml::from_str(&source)
+            .map_err(|e| InstallError::InvalidLockfile(path.to_path_buf(), e.to_string()))?;
+
+        if lockfile.version != LOCKFILE_VERSION {
+            return Err(InstallError::InvalidLockfile(
+                path.to_path_buf(),
+                format!("unsupported version {}", lockfile.version),
+            ));
+        }
+
+        Ok(Some(lockfile))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), InstallError> {
+        let contents = toml::to_string(self)
+            .map_err(|e| InstallError::InvalidLockfile(path.to_path_buf(), e.to_string()))?;
+
+        std::fs::write(path, format!("{HEADER}{contents}"))?;
+
+        Ok(())
+    }
+
+    /// Builds a lockfile from the result of an install. Packages are sorted by name so the
+    /// output doesn't depend on the order they were resolved in.
+    ///
+    /// The manifest in `project_dir` is recorded at the top level, any other manifest is
+    /// a workspace member.
+    pub fn new(manifests: &[&IkeToml], packages: &[InstalledPackage], project_dir: &Path) -> Self {
+        let mut packages: Vec<LockedPackage> = packages
+            .iter()
+            .map(|package| LockedPackage::new(package, project_dir))
+            .collect();
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut lockfile = Self {
+            version: LOCKFILE_VERSION,
+            packages,
+            ..Default::default()
+        };
+
+        for manifest in manifests {
+            let requirements = MemberRequirements::new(manifest);
+
+            if manifest.dir() == Some(project_dir) {
+                lockfile.dependencies = requirements.dependencies;
+                lockfile.dev_dependencies = requirements.dev_dependencies;
+            } else {
+                lockfile
+                    .members
+                    .insert(manifest.toml.package.name.clone(), requirements);
+            }
+        }
+
+        lockfile
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LockedPackage> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+
+    /// Whether the requirements recorded in the lockfile are the ones of the manifests.
+    pub fn matches(&self, manifests: &[&IkeToml], project_dir: &Path) -> bool {
+        manifests.iter().all(|manifest| {
+            let requirements = MemberRequirements::new(manifest);
+
+            if manifest.dir() == Some(project_dir) {
+                self.dependencies == requirements.dependencies
+                    && self.dev_dependencies == requirements.dev_dependencies
+            } else {
+                self.members.get(&manifest.toml.package.name) == Some(&requirements)
+            }
+        })
+    }
+
+    /// Keeps the packages of a previous lockfile that weren't part of this install, used
+    /// when only some members of a workspace were installed.
+    pub fn retain_from(&mut self, previous: &Lockfile) {
+        for package in &previous.packages {
+            if self.get(&package.name).is_none() {
+                self.packages.push(package.clone());
+            }
+        }
+
+        self.packages.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+}
+
+impl MemberRequirements {
+    fn new(manifest: &IkeToml) -> Self {
+        Self {
+            dependencies: requirements(&manifest.toml.dependencies),
+            dev_dependencies: requirements(&manifest.toml.dev_dependencies),
+        }
+    }
+}
+
+impl LockedPackage {
+    fn new(package: &InstalledPackage, project_dir: &Path) -> Self {
+        let mut locked = Self {
+            name: package.name.clone(),
+            version: package.version.clone(),
+            source: String::new(),
+            tarball: None,
+            integrity: None,
+            branch: None,
+            rev: None,
+            dependencies: package
+                .dependencies
+                .iter()
+                .map(|(name, range)| format!("{name}@{range}"))
+                .collect(),
+        };
+
+        match &package.source {
+            PackageSource::Registry {
+                registry,
+                tarball,
+                integrity,
+            } => {
+                locked.source = format!("registry+{registry}");
+                locked.tarball = Some(tarball.clone());
+                locked.integrity = Some(integrity.clone());
+            }
+            PackageSource::Path(path) => {
+                let relative = relative_to(path, project_dir);
+                locked.source = format!("path+{}", relative.to_string_lossy().replace('\\', "/"));
+            }
+            PackageSource::Git { url, branch, rev } => {
+                locked.source = format!("git+{url}");
+                locked.branch = branch.clone();
+                locked.rev = Some(rev.clone());
+            }
+        }
+
+        locked
+    }
+}
+
+/// Serializes a dependency of ike.toml the way it's recorded in the lockfile.
+pub fn requirement(dependency: &Dependency) -> String {
+    if dependency.workspace {
+        return "workspace".to_string();
+    }
+
+    if let Some(path) = &dependency.path {
+        return format!("path:{path}");
+    }
+
+    if let Some(git) = &dependency.git {
+        return match (&dependency.branch, &dependency.rev) {
+            (Some(branch), _) => format!("git:{git}#branch={branch}"),
+            (None, Some(rev)) => format!("git:{git}#rev={rev}"),
+            (None, None) => format!("git:{git}"),
+        };
+    }
+
+    dependency
+        .version
+        .clone()
+        .unwrap_or_else(|| "latest".to_string())
+}
+
+fn requirements(dependencies: &HashMap<String, Dependency>) -> BTreeMap<String, String> {
+    dependencies
+        .iter()
+        .map(|(name, dependency)| (name.clone(), requirement(dependency)))
+        .collect()
+}
+
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let path_components: Vec<Component> = path.components().collect();
+    let base_components: Vec<Component> = base.components().collect();
+
+    let common = path_components
+        .iter()
+        .zip(base_components.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    // Different roots (e.g. another drive on Windows), keep the absolute path
+    if common == 0 {
+        return path.to_path_buf();
+    }
+
+    let mut relative = PathBuf::new();
+    for _ in common..base_components.len() {
+        relative.push("..");
+    }
+    for component in &path_components[common..] {
+        relative.push(component.as_os_str());
+    }
+
+    if relative.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        relative
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_to() {
+        assert_eq!(
+            relative_to(Path::new("/app/packages/a"), Path::new("/app")),
+            PathBuf::from("packages/a")
+        );
+        assert_eq!(
+            relative_to(Path::new("/shared/a"), Path::new("/app/web")),
+            PathBuf::from("../../shared/a")
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let lockfile = Lockfile {
+            version: LOCKFILE_VERSION,
+            dependencies: BTreeMap::from([("yaml".to_string(), "^2.0.0".to_string())]),
+            dev_dependencies: BTreeMap::new(),
+            members: BTreeMap::from([(
+                "web".to_string(),
+                MemberRequirements {
+                    dependencies: BTreeMap::from([("utils".to_string(), "workspace".to_string())]),
+                    dev_dependencies: BTreeMap::new(),
+                },
+            )]),
+            packages: vec![LockedPackage {
+                name: "yaml".to_string(),
+                version: "2.4.1".to_string(),
+                source: "registry+https://registry.npmjs.org".to_string(),
+                tarball: Some("https://registry.npmjs.org/yaml/-/yaml-2.4.1.tgz".to_string()),
+                integrity: Some("sha512-abc".to_string()),
+                branch: None,
+                rev: None,
+                dependencies: vec![],
+            }],
+        };
+
+        let serialized = toml::to_string(&lockfile).unwrap();
+
+        assert_eq!(toml::from_str::<Lockfile>(&serialized).unwrap(), lockfile);
+    }
+}
@@ -0,0 +1,90 @@
+use super::InstallError;
+use std::path::Path;
+use toml_edit::{value, DocumentMut, InlineTable, Item, Table};
+
+pub const DEPENDENCIES: &str = "dependencies";
+pub const DEV_DEPENDENCIES: &str = "dev-dependencies";
+
+/// Value written for a dependency by `ike add`.
+#[derive(Debug, Clone)]
+pub enum DependencyEntry {
+    Version(String),
+    Path(String),
+}
+
+fn read(manifest_path: &Path) -> Result<DocumentMut, InstallError> {
+    let source = std::fs::read_to_string(manifest_path)?;
+
+    source
+        .parse::<DocumentMut>()
+        .map_err(|e| InstallError::InvalidManifest(manifest_path.to_path_buf(), e.to_string()))
+}
+
+/// Adds or replaces a dependency while keeping the formatting and comments of ike.toml.
+pub fn add_dependency(
+    manifest_path: &Path,
+    name: &str,
+    entry: DependencyEntry,
+    dev: bool,
+) -> Result<(), InstallError> {
+    let mut document = read(manifest_path)?;
+    let (table_name, other_table) = if dev {
+        (DEV_DEPENDENCIES, DEPENDENCIES)
+    } else {
+        (DEPENDENCIES, DEV_DEPENDENCIES)
+    };
+
+    // A package is either a dependency or a dev-dependency, never both
+    if let Some(other) = document
+        .get_mut(other_table)
+        .and_then(|table| table.as_table_like_mut())
+    {
+        other.remove(name);
+    }
+
+    let table = document
+        .entry(table_name)
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_like_mut()
+        .ok_or_else(|| {
+            InstallError::InvalidManifest(
+                manifest_path.to_path_buf(),
+                format!("'{table_name}' must be a table"),
+            )
+        })?;
+
+    let item = match entry {
+        DependencyEntry::Version(version) => value(version),
+        DependencyEntry::Path(path) => {
+            let mut inline = InlineTable::new();
+            inline.insert("path", path.into());
+            value(inline)
+        }
+    };
+    table.insert(name, item);
+
+    std::fs::write(manifest_path, document.to_string())?;
+
+    Ok(())
+}
+
+/// Removes a dependency from both dependency tables, returns false if it wasn't listed.
+pub fn remove_dependency(manifest_path: &Path, name: &str) -> Result<bool, InstallError> {
+    let mut document = read(manifest_path)?;
+    let mut removed = false;
+
+    for table_name in [DEPENDENCIES, DEV_DEPENDENCIES] {
+        if let Some(table) = document
+            .get_mut(table_name)
+            .and_then(|table| table.as_table_like_mut())
+        {
+            removed |= table.remove(name).is_some();
+        }
+    }
+
+    if removed {
+        std::fs::write(manifest_path, document.to_string())?;
+    }
+
+    Ok(removed)
+}
@@ -0,0 +1,957 @@
+pub mod git;
+pub mod lockfile;
+pub mod manifest;
+pub mod registry;
+pub mod store;
+
+use crate::{fs::read_json, resolver::MODULES_DIR};
+use git::{GitCache, GitReference};
+use ike_logger::{log, Logger};
+use ike_toml::{Dependency, IkeToml};
+use indexmap::IndexMap;
+use lockfile::Lockfile;
+use node_semver::{Range, Version};
+use registry::Registry;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+};
+use store::PackageStore;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum InstallError {
+    #[error("Could not find an <cyan>ike.toml<r> in <cyan>{0}<r> or any parent directory")]
+    ManifestNotFound(PathBuf),
+    #[error("Package <cyan>{0}<r> was not found in the registry")]
+    PackageNotFound(String),
+    #[error("No version of <cyan>{0}<r> matches <cyan>{1}<r>")]
+    NoMatchingVersion(String, String),
+    #[error("Invalid version range <cyan>{1}<r> for <cyan>{0}<r>")]
+    InvalidRange(String, String),
+    #[error("Invalid registry response for <cyan>{0}<r>: {1}")]
+    InvalidPackument(String, String),
+    #[error("Request to <cyan>{0}<r> failed with status {1}")]
+    RequestFailed(String, u16),
+    #[error("Failed to fetch <cyan>{0}<r>: {1}")]
+    FetchFailed(String, String),
+    #[error("Integrity check failed for <cyan>{0}<r>: expected {1}, got {2}")]
+    IntegrityMismatch(String, String, String),
+    #[error("Cannot verify <cyan>{0}<r>: unsupported integrity <cyan>{1}<r>")]
+    UnsupportedIntegrity(String, String),
+    #[error("Failed to extract <cyan>{0}<r>: {1}")]
+    ExtractFailed(String, String),
+    #[error("Path dependency <cyan>{0}<r> points to <cyan>{1}<r>, which does not exist")]
+    PathDependencyNotFound(String, PathBuf),
+    #[error("Failed to read manifest <cyan>{0}<r>: {1}")]
+    InvalidManifest(PathBuf, String),
+    #[error("Failed to read lockfile <cyan>{0}<r>: {1}")]
+    InvalidLockfile(PathBuf, String),
+    #[error("<cyan>ike.lock<r> is missing, run <cyan>ike install<r> without <cyan>--frozen<r> to create it")]
+    LockfileMissing,
+    #[error("<cyan>ike.toml<r> and <cyan>ike.lock<r> are out of sync, run <cyan>ike install<r> without <cyan>--frozen<r> to update the lockfile")]
+    LockfileOutOfDate,
+    #[error("<cyan>{0}<r> is not locked in <cyan>ike.lock<r>, run <cyan>ike install<r> without <cyan>--frozen<r>")]
+    NotLocked(String),
+    #[error(
+        "<cyan>{0}<r> was modified after it was installed, run <cyan>ike install<r> to restore it"
+    )]
+    ModifiedPackage(PathBuf),
+    #[error("<cyan>git<r> is required for git dependencies but it wasn't found in PATH")]
+    GitNotFound,
+    #[error("git failed for <cyan>{0}<r>: {1}")]
+    GitFailed(String, String),
+    #[error("Could not find <cyan>{1}<r> in <cyan>{0}<r>")]
+    GitRevisionNotFound(String, String),
+    #[error("<cyan>{0}<r> is not a valid git URL")]
+    InvalidGitUrl(String),
+    #[error("Package <cyan>{0}<r> is not a dependency")]
+    DependencyNotFound(String),
+    #[error("<cyan>{0}<r> is declared with <cyan>workspace = true<r> but no workspace member is named <cyan>{0}<r>")]
+    WorkspaceMemberNotFound(String),
+    #[error("<cyan>{0}<r> requires <cyan>{1}@{2}<r> but <cyan>{1}@{3}<r> is already installed, only one version of a package can be installed")]
+    VersionConflict(String, String, String, String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PackageSource {
+    Registry {
+        registry: String,
+        tarball: String,
+        integrity: String,
+    },
+    Path(PathBuf),
+    Git {
+        url: String,
+        branch: Option<String>,
+        /// Commit SHA the dependency resolved to.
+        rev: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct InstalledPackage {
+    pub name: String,
+    pub version: String,
+    pub source: PackageSource,
+    /// Where the package contents live (the store for registry packages).
+    pub path: PathBuf,
+    /// Requested dependencies of the package, name to range.
+    pub dependencies: IndexMap<String, String>,
+}
+
+/// A dependency waiting to be installed.
+struct QueuedDependency {
+    name: String,
+    dependency: Dependency,
+    /// Name of the package (or project) that declares the dependency.
+    dependent: String,
+    /// Directory of the declaring package, `file:` dependencies are relative to it.
+    base_dir: PathBuf,
+    /// Whether the declaring package, or one of the packages that led to it, came from
+    /// the registry.
+    from_registry: bool,
+}
+
+/// Installs the dependencies of a project into its `ike_modules` directory.
+///
+/// Dependencies are resolved breadth first and hoisted, so the ranges of the project
+/// itself decide which version is installed. Only one version of every package can be
+/// installed, a range the hoisted version doesn't satisfy fails the install. In a
+/// workspace the dependencies of every member share the `ike_modules` of the workspace
+/// root, and members are linked from their own directory instead of being fetched.
+#[derive(Debug)]
+pub struct Installer {
+    registry: Registry,
+    store: PackageStore,
+    git: GitCache,
+    project_dir: PathBuf,
+    installed: IndexMap<String, InstalledPackage>,
+    lockfile: Option<Lockfile>,
+    frozen: bool,
+    members: HashMap<String, PathBuf>,
+    partial: bool,
+}
+
+impl Installer {
+    pub fn new(registry: Registry, store: PackageStore, project_dir: PathBuf) -> Self {
+        Self {
+            registry,
+            store,
+            git: GitCache::default(),
+            project_dir,
+            installed: IndexMap::new(),
+            lockfile: None,
+            frozen: false,
+            members: HashMap::new(),
+            partial: false,
+        }
+    }
+
+    /// Workspace members by name, dependencies on them resolve to their directory.
+    pub fn with_members(mut self, members: HashMap<String, PathBuf>) -> Self {
+        self.members = members;
+        self
+    }
+
+    /// Only some members of the workspace are installed, so packages that aren't part
+    /// of this install are kept in `ike_modules`.
+    pub fn partial(mut self, partial: bool) -> Self {
+        self.partial = partial;
+        self
+    }
+
+    /// Prefers the versions of a previous install. With `frozen`, the install fails
+    /// instead of resolving anything that isn't in the lockfile.
+    pub fn with_lockfile(mut self, lockfile: Option<Lockfile>, frozen: bool) -> Self {
+        self.lockfile = lockfile;
+        self.frozen = frozen;
+        self
+    }
+
+    pub fn registry(&mut self) -> &mut Registry {
+        &mut self.registry
+    }
+
+    pub fn install(
+        &mut self,
+        manifests: &[&IkeToml],
+    ) -> Result<Vec<InstalledPackage>, InstallError> {
+        if self.frozen {
+            match &self.lockfile {
+                None => return Err(InstallError::LockfileMissing),
+                Some(lockfile) if !lockfile.matches(manifests, &self.project_dir) => {
+                    return Err(InstallError::LockfileOutOfDate)
+                }
+                _ => {}
+            }
+        }
+
+        let mut queue: VecDeque<QueuedDependency> = VecDeque::new();
+
+        for manifest in manifests {
+            let manifest_dir = manifest.dir().unwrap_or(&self.project_dir).to_path_buf();
+
+            for (name, dependency) in sorted(&manifest.toml.dependencies)
+                .into_iter()
+                .chain(sorted(&manifest.toml.dev_dependencies))
+            {
+                queue.push_back(QueuedDependency {
+                    name: name.clone(),
+                    dependency: dependency.clone(),
+                    dependent: manifest.toml.package.name.clone(),
+                    base_dir: manifest_dir.clone(),
+                    from_registry: false,
+                });
+            }
+        }
+
+        while let Some(queued) = queue.pop_front() {
+            let QueuedDependency {
+                name,
+                dependency,
+                dependent,
+                base_dir,
+                from_registry,
+            } = queued;
+
+            if let Some(existing) = self.installed.get(&name) {
+                let range = dependency.version.as_deref().unwrap_or("latest");
+                if !satisfies(range, &existing.version) {
+                    return Err(InstallError::VersionConflict(
+                        dependent,
+                        name,
+                        range.to_string(),
+                        existing.version.clone(),
+                    ));
+                }
+                continue;
+            }
+
+            let member = self
+                .members
+                .get(&name)
+                .filter(|_| dependency.path.is_none() && dependency.git.is_none())
+                .cloned();
+
+            let package = if let Some(member_dir) = member {
+                let package = self.install_path(&name, &member_dir)?;
+                if let Some(range) = &dependency.version {
+                    if !satisfies(range, &package.version) {
+                        return Err(InstallError::VersionConflict(
+                            dependent,
+                            name,
+                            range.clone(),
+                            package.version,
+                        ));
+                    }
+                }
+                package
+            } else if dependency.workspace {
+                return Err(InstallError::WorkspaceMemberNotFound(name));
+            } else if let Some(path) = &dependency.path {
+                self.install_path(&name, &base_dir.join(path))?
+            } else if dependency.git.is_some() {
+                self.install_git(&name, &dependency, from_registry)?
+            } else {
+                let range = dependency.version.as_deref().unwrap_or("latest");
+                self.install_registry(&name, range)?
+            };
+
+            let from_registry =
+                from_registry || matches!(package.source, PackageSource::Registry { .. });
+            for (dep_name, dep_range) in package.dependencies.iter() {
+                queue.push_back(QueuedDependency {
+                    name: dep_name.clone(),
+                    dependency: parse_spec(dep_range),
+                    dependent: package.name.clone(),
+                    base_dir: package.path.clone(),
+                    from_registry,
+                });
+            }
+
+            self.installed.insert(name, package);
+        }
+
+        for package in self.installed.values() {
+            link(&self.link_path(&package.name), &package.path)?;
+        }
+        if !self.partial {
+            self.prune()?;
+        }
+
+        Ok(self.installed.values().cloned().collect())
+    }
+
+    fn install_registry(
+        &mut self,
+        name: &str,
+        range: &str,
+    ) -> Result<InstalledPackage, InstallError> {
+        let locked = self
+            .lockfile
+            .as_ref()
+            .and_then(|lockfile| lockfile.get(name))
+            .filter(|locked| locked.source.starts_with("registry+"))
+            .filter(|locked| satisfies(range, &locked.version))
+            .filter(|locked| locked.tarball.is_some() && locked.integrity.is_some());
+
+        let (registry, version, tarball, integrity, dependencies) = match locked {
+            Some(locked) => (
+                locked.source["registry+".len()..].to_string(),
+                locked.version.clone(),
+                locked.tarball.clone().unwrap_or_default(),
+                locked.integrity.clone(),
+                locked
+                    .dependencies
+                    .iter()
+                    .map(|spec| {
+                        let (name, range) = parse_package_spec(spec);
+                        (name.to_string(), range.unwrap_or("latest").to_string())
+                    })
+                    .collect(),
+            ),
+            None if self.frozen => return Err(InstallError::NotLocked(name.to_string())),
+            None => {
+                let version = self.registry.resolve(name, range)?;
+
+                (
+                    self.registry.url().to_string(),
+                    version.version,
+                    version.dist.tarball,
+                    version.dist.integrity,
+                    sorted(&version.dependencies)
+                        .into_iter()
+                        .map(|(name, range)| (name.clone(), range.clone()))
+                        .collect(),
+                )
+            }
+        };
+
+        let cached = integrity
+            .as_deref()
+            .and_then(|integrity| Some((self.store.get(integrity)?, integrity.to_string())));
+
+        // A frozen install reproduces the lockfile exactly, other installs restore
+        // packages that were modified in the store
+        let cached = match cached {
+            Some((path, integrity)) => match self.store.verify_package(&integrity) {
+                Ok(()) => Some((path, integrity)),
+                Err(e) if self.frozen => return Err(e),
+                Err(_) => None,
+            },
+            None => None,
+        };
+
+        let (path, integrity) = match cached {
+            Some(cached) => cached,
+            None => {
+                let bytes = registry::fetch(&tarball)?;
+                self.store.insert(name, &bytes, integrity.as_deref())?
+            }
+        };
+
+        Ok(InstalledPackage {
+            name: name.to_string(),
+            version,
+            source: PackageSource::Registry {
+                registry,
+                tarball,
+                integrity,
+            },
+            path,
+            dependencies,
+        })
+    }
+
+    fn install_path(&mut self, name: &str, dir: &Path) -> Result<InstalledPackage, InstallError> {
+        let dir = dir.canonicalize().map_err(|_| {
+            InstallError::PathDependencyNotFound(name.to_string(), dir.to_path_buf())
+        })?;
+        let (version, dependencies) = read_package_info(&dir)?;
+
+        Ok(InstalledPackage {
+            name: name.to_string(),
+            version,
+            source: PackageSource::Path(dir.clone()),
+            path: dir,
+            dependencies,
+        })
+    }
+
+    fn install_git(
+        &mut self,
+        name: &str,
+        dependency: &Dependency,
+        from_registry: bool,
+    ) -> Result<InstalledPackage, InstallError> {
+        let url = dependency.git.clone().unwrap_or_default();
+        let reference = match (&dependency.branch, &dependency.rev) {
+            (Some(branch), _) => GitReference::Branch(branch.clone()),
+            (None, Some(rev)) => GitReference::Rev(rev.clone()),
+            (None, None) => GitReference::DefaultBranch,
+        };
+
+        // Branches only move when the lockfile doesn't pin them to a commit yet
+        let locked = self
+            .lockfile
+            .as_ref()
+            .and_then(|lockfile| lockfile.get(name))
+            .filter(|locked| locked.source == format!("git+{url}"))
+            .filter(|locked| locked.branch == dependency.branch)
+            .and_then(|locked| locked.rev.clone())
+            .filter(|locked| match &dependency.rev {
+                Some(rev) => locked.starts_with(rev.as_str()),
+                None => true,
+            });
+
+        if locked.is_none() && self.frozen {
+            return Err(InstallError::NotLocked(name.to_string()));
+        }
+
+        let (path, rev) = self
+            .git
+            .checkout(&url, &reference, locked.as_deref(), from_registry)?;
+        let (version, dependencies) = read_package_info(&path)?;
+
+        Ok(InstalledPackage {
+            name: name.to_string(),
+            version,
+            source: PackageSource::Git {
+                url,
+                branch: dependency.branch.clone(),
+                rev,
+            },
+            path,
+            dependencies,
+        })
+    }
+
+    fn modules_dir(&self) -> PathBuf {
+        self.project_dir.join(MODULES_DIR)
+    }
+
+    fn link_path(&self, name: &str) -> PathBuf {
+        self.modules_dir().join(name)
+    }
+
+    /// Removes links for packages that are no longer installed.
+    fn prune(&self) -> Result<(), InstallError> {
+        let modules_dir = self.modules_dir();
+        if !modules_dir.is_dir() {
+            return Ok(());
+        }
+
+        for entry in fs::read_dir(&modules_dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            if name.starts_with('@') && !is_link(&entry.path()) {
+                for scoped in fs::read_dir(entry.path())? {
+                    let scoped = scoped?;
+                    let scoped_name = format!("{}/{}", name, scoped.file_name().to_string_lossy());
+
+                    if !self.installed.contains_key(&scoped_name) {
+                        unlink(&scoped.path())?;
+                    }
+                }
+
+                if fs::read_dir(entry.path())?.next().is_none() {
+                    fs::remove_dir(entry.path())?;
+                }
+            } else if !self.installed.contains_key(&name) {
+                unlink(&entry.path())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the version and dependencies of a package from its ike.toml or package.json.
+fn read_package_info(dir: &Path) -> Result<(String, IndexMap<String, String>), InstallError> {
+    let manifest_path = dir.join("ike.toml");
+    let (version, dependencies) = if manifest_path.is_file() {
+        let manifest = IkeToml::from_file(manifest_path.clone())
+            .map_err(|e| InstallError::InvalidManifest(manifest_path, e.to_string()))?;
+        let dependencies = sorted(&manifest.toml.dependencies)
+            .into_iter()
+            .map(|(name, dependency)| (name.clone(), spec_to_string(dependency)))
+            .collect();
+
+        (manifest.toml.package.version, dependencies)
+    } else if dir.join("package.json").is_file() {
+        let package_json = dir.join("package.json");
+        let json: serde_json::Value = read_json(&package_json)
+            .map_err(|e| InstallError::InvalidManifest(package_json, e.to_string()))?;
+        let dependencies = json["dependencies"]
+            .as_object()
+            .map(|deps| {
+                deps.iter()
+                    .filter_map(|(name, range)| Some((name.clone(), range.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        (
+            json["version"].as_str().unwrap_or("0.0.0").to_string(),
+            dependencies,
+        )
+    } else {
+        ("0.0.0".to_string(), IndexMap::new())
+    };
+
+    Ok((version, dependencies))
+}
+
+/// Splits `name@range` (and `@scope/name@range`) into the name and the optional range.
+pub fn parse_package_spec(spec: &str) -> (&str, Option<&str>) {
+    match spec.get(1..).and_then(|rest| rest.find('@')) {
+        Some(index) => (&spec[..index + 1], Some(&spec[index + 2..])),
+        None => (spec, None),
+    }
+}
+
+/// npm style dependency strings are either a version range, `file:<path>`, a git URL
+/// (`git+<url>#<branch or commit>`) or `workspace:*` for workspace members.
+fn parse_spec(spec: &str) -> Dependency {
+    if spec.starts_with("workspace:") {
+        return Dependency {
+            workspace: true,
+            ..Default::default()
+        };
+    }
+
+    if let Some(path) = spec.strip_prefix("file:") {
+        return Dependency {
+            path: Some(path.to_string()),
+            ..Default::default()
+        };
+    }
+
+    let git = spec
+        .strip_prefix("git+")
+        .or_else(|| spec.starts_with("git://").then_some(spec));
+    if let Some(git) = git {
+        let (url, committish) = match git.split_once('#') {
+            Some((url, committish)) => (url, Some(committish.to_string())),
+            None => (git, None),
+        };
+        let is_sha = committish.as_ref().is_some_and(|committish| {
+            (7..=40).contains(&committish.len())
+                && committish.chars().all(|c| c.is_ascii_hexdigit())
+        });
+
+        return Dependency {
+            git: Some(url.to_string()),
+            branch: committish.clone().filter(|_| !is_sha),
+            rev: committish.filter(|_| is_sha),
+            ..Default::default()
+        };
+    }
+
+    Dependency {
+        version: Some(spec.to_string()),
+        ..Default::default()
+    }
+}
+
+fn spec_to_string(dependency: &Dependency) -> String {
+    if dependency.workspace {
+        return "workspace:*".to_string();
+    }
+
+    if let Some(git) = &dependency.git {
+        let url = if git.starts_with("git://") {
+            git.clone()
+        } else {
+            format!("git+{git}")
+        };
+
+        return match dependency.branch.as_ref().or(dependency.rev.as_ref()) {
+            Some(committish) => format!("{url}#{committish}"),
+            None => url,
+        };
+    }
+
+    match (&dependency.version, &dependency.path) {
+        (Some(version), _) => version.clone(),
+        (None, Some(path)) => format!("file:{path}"),
+        (None, None) => "latest".to_string(),
+    }
+}
+
+fn satisfies(range: &str, version: &str) -> bool {
+    match (Range::parse(range), Version::parse(version)) {
+        (Ok(range), Ok(version)) => range.satisfies(&version),
+        // Dist-tags and other non-range specs can't be checked
+        _ => true,
+    }
+}
+
+fn sorted<V>(map: &HashMap<String, V>) -> Vec<(&String, &V)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+fn is_link(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_symlink())
+}
+
+fn unlink(path: &Path) -> std::io::Result<()> {
+    if is_link(path) || path.is_file() {
+        fs::remove_file(path).or_else(|_| fs::remove_dir(path))
+    } else {
+        fs::remove_dir_all(path)
+    }
+}
+
+/// Links `path` to `target`, replacing whatever was there before. Falls back to copying
+/// when symlinks aren't available (e.g. Windows without developer mode).
+fn link(path: &Path, target: &Path) -> std::io::Result<()> {
+    if fs::symlink_metadata(path).is_ok() {
+        if fs::read_link(path).is_ok_and(|current| current == target) {
+            return Ok(());
+        }
+        unlink(path)?;
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    #[cfg(unix)]
+    let linked = std::os::unix::fs::symlink(target, path);
+    #[cfg(windows)]
+    let linked = std::os::windows::fs::symlink_dir(target, path);
+
+    match linked {
+        Ok(_) => Ok(()),
+        Err(_) => copy_dir(target, path),
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints one line per installed package.
+pub fn print_summary(packages: &[InstalledPackage]) {
+    for package in packages {
+        let source = match &package.source {
+            PackageSource::Path(path) => format!(" <d>({})<r>", path.display()),
+            PackageSource::Git { url, rev, .. } => {
+                format!(" <d>({}#{})<r>", url, rev.get(..7).unwrap_or(rev))
+            }
+            PackageSource::Registry { .. } => String::new(),
+        };
+
+        log!(
+            "<green>+<r> {}<d>@{}<r>{}",
+            package.name,
+            package.version,
+            source
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolver::IkeResolver;
+    use flate2::{write::GzEncoder, Compression};
+    use store::integrity_of;
+
+    /// Gzipped npm style tarball with the files under `package/`.
+    fn tarball(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, format!("package/{path}"), contents.as_bytes())
+                .unwrap();
+        }
+
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    /// Adds a package to a `file://` registry at `root`.
+    fn publish(root: &Path, name: &str, dependencies: &[(&str, &str)], files: &[(&str, &str)]) {
+        let dependencies: serde_json::Map<String, serde_json::Value> = dependencies
+            .iter()
+            .map(|(name, range)| (name.to_string(), serde_json::json!(range)))
+            .collect();
+        let package_json = serde_json::json!({
+            "name": name,
+            "version": "1.0.0",
+            "main": "index.js",
+            "dependencies": dependencies,
+        })
+        .to_string();
+
+        let mut files = files.to_vec();
+        files.push(("package.json", &package_json));
+        let tarball = tarball(&files);
+        let tarball_path = root.join(format!("{name}-1.0.0.tgz"));
+        fs::write(&tarball_path, &tarball).unwrap();
+
+        let packument = serde_json::json!({
+            "name": name,
+            "dist-tags": { "latest": "1.0.0" },
+            "versions": {
+                "1.0.0": {
+                    "name": name,
+                    "version": "1.0.0",
+                    "dependencies": dependencies,
+                    "dist": {
+                        "tarball": format!("file://{}", tarball_path.display()),
+                        "integrity": integrity_of(&tarball),
+                    },
+                },
+            },
+        });
+        fs::create_dir_all(root.join(name)).unwrap();
+        fs::write(root.join(name).join("index.json"), packument.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_install_transitive() {
+        let dir = std::env::temp_dir().join(format!("ike-install-test-{}", std::process::id()));
+        let (registry_dir, project_dir) = (dir.join("registry"), dir.join("project"));
+        fs::create_dir_all(&registry_dir).unwrap();
+        fs::create_dir_all(&project_dir).unwrap();
+
+        publish(
+            &registry_dir,
+            "greet",
+            &[("shout", "^1.0.0")],
+            &[(
+                "index.js",
+                "import { shout } from 'shout';\nexport const greet = (name) => shout(name);",
+            )],
+        );
+        publish(
+            &registry_dir,
+            "shout",
+            &[],
+            &[(
+                "index.js",
+                "export const shout = (text) => text.toUpperCase();",
+            )],
+        );
+
+        let manifest_path = project_dir.join("ike.toml");
+        fs::write(
+            &manifest_path,
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\ngreet = \"^1.0.0\"\n",
+        )
+        .unwrap();
+        let manifest = IkeToml::from_file(manifest_path).unwrap();
+
+        let registry = Registry::new(&format!("file://{}", registry_dir.display()));
+        let store = PackageStore::new(dir.join("store"));
+        let packages = Installer::new(registry, store, project_dir.clone())
+            .install(&[&manifest])
+            .unwrap();
+        assert_eq!(
+            packages
+                .iter()
+                .map(|package| package.name.as_str())
+                .collect::<Vec<_>>(),
+            ["greet", "shout"]
+        );
+        Lockfile::new(&[&manifest], &packages, &project_dir)
+            .write(&project_dir.join(lockfile::LOCKFILE_NAME))
+            .unwrap();
+
+        // The way the module loader does, from the directory of each importing file
+        let resolver = IkeResolver::default();
+        let greet = resolver.resolve(&project_dir, "greet").unwrap();
+        let shout = resolver.resolve(greet.parent().unwrap(), "shout").unwrap();
+        assert!(greet.starts_with(project_dir.join(MODULES_DIR)));
+        assert_eq!(shout, project_dir.join(MODULES_DIR).join("shout/index.js"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_install_conflict() {
+        let dir = std::env::temp_dir().join(format!("ike-conflict-test-{}", std::process::id()));
+        let (registry_dir, project_dir) = (dir.join("registry"), dir.join("project"));
+        fs::create_dir_all(&registry_dir).unwrap();
+        fs::create_dir_all(&project_dir).unwrap();
+
+        publish(&registry_dir, "greet", &[("shout", "^2.0.0")], &[]);
+        publish(&registry_dir, "shout", &[], &[]);
+
+        let manifest_path = project_dir.join("ike.toml");
+        fs::write(
+            &manifest_path,
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\ngreet = \"^1.0.0\"\nshout = \"^1.0.0\"\n",
+        )
+        .unwrap();
+        let manifest = IkeToml::from_file(manifest_path).unwrap();
+
+        let registry = Registry::new(&format!("file://{}", registry_dir.display()));
+        let store = PackageStore::new(dir.join("store"));
+        let result = Installer::new(registry, store, project_dir.clone()).install(&[&manifest]);
+        assert!(matches!(
+            result,
+            Err(InstallError::VersionConflict(dependent, name, range, version))
+                if dependent == "greet" && name == "shout" && range == "^2.0.0" && version == "1.0.0"
+        ));
+        assert!(!project_dir.join(MODULES_DIR).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_install_integrity_mismatch() {
+        let dir = std::env::temp_dir().join(format!("ike-integrity-test-{}", std::process::id()));
+        let (registry_dir, project_dir) = (dir.join("registry"), dir.join("project"));
+        fs::create_dir_all(&registry_dir).unwrap();
+        fs::create_dir_all(&project_dir).unwrap();
+
+        publish(&registry_dir, "shout", &[], &[("index.js", "export {};")]);
+        // Replaced after it was published
+        fs::write(
+            registry_dir.join("shout-1.0.0.tgz"),
+            tarball(&[("index.js", "throw new Error();")]),
+        )
+        .unwrap();
+
+        let manifest_path = project_dir.join("ike.toml");
+        fs::write(
+            &manifest_path,
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nshout = \"1.0.0\"\n",
+        )
+        .unwrap();
+        let manifest = IkeToml::from_file(manifest_path).unwrap();
+
+        let registry = Registry::new(&format!("file://{}", registry_dir.display()));
+        let store = PackageStore::new(dir.join("store"));
+        let result = Installer::new(registry, store, project_dir.clone()).install(&[&manifest]);
+        assert!(matches!(result, Err(InstallError::IntegrityMismatch(..))));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_install_frozen() {
+        let dir = std::env::temp_dir().join(format!("ike-frozen-test-{}", std::process::id()));
+        let (registry_dir, project_dir) = (dir.join("registry"), dir.join("project"));
+        fs::create_dir_all(&registry_dir).unwrap();
+        fs::create_dir_all(&project_dir).unwrap();
+
+        publish(&registry_dir, "shout", &[], &[("index.js", "export {};")]);
+
+        let manifest_path = project_dir.join("ike.toml");
+        fs::write(
+            &manifest_path,
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nshout = \"1.0.0\"\n",
+        )
+        .unwrap();
+        let manifest = IkeToml::from_file(manifest_path).unwrap();
+        let registry = || Registry::new(&format!("file://{}", registry_dir.display()));
+        let store = || PackageStore::new(dir.join("store"));
+        let lockfile_path = project_dir.join(lockfile::LOCKFILE_NAME);
+
+        let packages = Installer::new(registry(), store(), project_dir.clone())
+            .install(&[&manifest])
+            .unwrap();
+        Lockfile::new(&[&manifest], &packages, &project_dir)
+            .write(&lockfile_path)
+            .unwrap();
+
+        // Modified in place after it was installed
+        let stored = packages[0].path.join("index.js");
+        fs::write(&stored, "throw new Error();").unwrap();
+
+        let frozen = || {
+            Installer::new(registry(), store(), project_dir.clone())
+                .with_lockfile(Lockfile::read(&lockfile_path).unwrap(), true)
+                .install(&[&manifest])
+        };
+        assert!(matches!(
+            frozen(),
+            Err(InstallError::ModifiedPackage(path)) if path.ends_with("index.js")
+        ));
+
+        // Installs that may update the lockfile restore it instead
+        Installer::new(registry(), store(), project_dir.clone())
+            .install(&[&manifest])
+            .unwrap();
+        assert_eq!(fs::read_to_string(&stored).unwrap(), "export {};");
+        assert!(frozen().is_ok());
+
+        // A different package than the locked one is linked
+        let (other, _) = store()
+            .insert(
+                "shout",
+                &tarball(&[("index.js", "export const shout = 1;")]),
+                None,
+            )
+            .unwrap();
+        link(&project_dir.join(MODULES_DIR).join("shout"), &other).unwrap();
+        assert!(matches!(
+            IkeResolver::default().resolve(&project_dir, "shout"),
+            Err(crate::resolver::ResolveError::LockMismatch(name)) if name == "shout"
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        let git = parse_spec("git+file:///tmp/utils#main");
+        assert_eq!(git.git.as_deref(), Some("file:///tmp/utils"));
+        assert_eq!(git.branch.as_deref(), Some("main"));
+        assert_eq!(git.rev, None);
+
+        let pinned = parse_spec("git+https://example.com/utils.git#59700d2");
+        assert_eq!(pinned.branch, None);
+        assert_eq!(pinned.rev.as_deref(), Some("59700d2"));
+        assert_eq!(
+            spec_to_string(&pinned),
+            "git+https://example.com/utils.git#59700d2"
+        );
+
+        assert_eq!(
+            parse_spec("file:../utils").path.as_deref(),
+            Some("../utils")
+        );
+        assert_eq!(parse_spec("^1.2.0").version.as_deref(), Some("^1.2.0"));
+        assert!(parse_spec("workspace:*").workspace);
+    }
+
+    #[test]
+    fn test_parse_package_spec() {
+        assert_eq!(parse_package_spec("yaml"), ("yaml", None));
+        assert_eq!(parse_package_spec("yaml@^2.0.0"), ("yaml", Some("^2.0.0")));
+        assert_eq!(parse_package_spec("@ike/utils"), ("@ike/utils", None));
+        assert_eq!(
+            parse_package_spec("@ike/utils@latest"),
+            ("@ike/utils", Some("latest"))
+        );
+    }
+}
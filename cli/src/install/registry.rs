@@ -0,0 +1,163 @@
+use super::InstallError;
+use isahc::{config::Configurable, prelude::*, Request};
+use node_semver::{Range, Version};
+use serde::Deserialize;
+use std::{collections::HashMap, path::PathBuf};
+
+pub const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org";
+
+/// Environment variable that overrides the registry URL.
+pub const REGISTRY_ENV: &str = "IKE_REGISTRY";
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Packument {
+    pub name: String,
+    #[serde(default, rename = "dist-tags")]
+    pub dist_tags: HashMap<String, String>,
+    #[serde(default)]
+    pub versions: HashMap<String, PackageVersion>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PackageVersion {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub dependencies: HashMap<String, String>,
+    pub dist: Dist,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Dist {
+    pub tarball: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<String>,
+}
+
+/// Client for an npm-compatible registry.
+///
+/// `file://` URLs are supported as well, in which case `<root>/<name>/index.json` holds
+/// the packument and tarball URLs may point anywhere on disk.
+#[derive(Debug)]
+pub struct Registry {
+    url: String,
+    packuments: HashMap<String, Packument>,
+}
+
+impl Registry {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            packuments: HashMap::new(),
+        }
+    }
+
+    /// Uses the `--registry` flag, falling back to `IKE_REGISTRY` and the npm registry.
+    pub fn from_flag(flag: Option<&String>) -> Self {
+        let url = flag
+            .cloned()
+            .or_else(|| std::env::var(REGISTRY_ENV).ok())
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| DEFAULT_REGISTRY.to_string());
+
+        Self::new(&url)
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn packument(&mut self, name: &str) -> Result<&Packument, InstallError> {
+        if !self.packuments.contains_key(name) {
+            let url = format!("{}/{}", self.url, name.replace('/', "%2f"));
+            let bytes = match fetch(&url) {
+                Ok(bytes) => bytes,
+                Err(InstallError::RequestFailed(_, 404)) => {
+                    return Err(InstallError::PackageNotFound(name.to_string()))
+                }
+                Err(e) => return Err(e),
+            };
+            let packument: Packument = serde_json::from_slice(&bytes)
+                .map_err(|e| InstallError::InvalidPackument(name.to_string(), e.to_string()))?;
+
+            self.packuments.insert(name.to_string(), packument);
+        }
+
+        Ok(&self.packuments[name])
+    }
+
+    /// Picks the version to install for a range or dist-tag.
+    ///
+    /// Like npm, the `latest` tag wins when it satisfies the range, otherwise the highest
+    /// matching version is used.
+    pub fn resolve(&mut self, name: &str, range: &str) -> Result<PackageVersion, InstallError> {
+        let packument = self.packument(name)?;
+
+        if let Some(version) = packument.dist_tags.get(range) {
+            return packument.versions.get(version).cloned().ok_or_else(|| {
+                InstallError::NoMatchingVersion(name.to_string(), range.to_string())
+            });
+        }
+
+        let parsed = Range::parse(if range.is_empty() { "*" } else { range })
+            .map_err(|_| InstallError::InvalidRange(name.to_string(), range.to_string()))?;
+
+        let latest = packument
+            .dist_tags
+            .get("latest")
+            .and_then(|latest| Version::parse(latest).ok())
+            .filter(|latest| parsed.satisfies(latest));
+
+        let version = match latest {
+            Some(latest) => Some(latest),
+            None => packument
+                .versions
+                .keys()
+                .filter_map(|version| Version::parse(version).ok())
+                .filter(|version| parsed.satisfies(version))
+                .max(),
+        };
+
+        version
+            .and_then(|version| packument.versions.get(&version.to_string()).cloned())
+            .ok_or_else(|| InstallError::NoMatchingVersion(name.to_string(), range.to_string()))
+    }
+}
+
+/// Downloads the contents of a `http(s)://` or `file://` URL.
+pub fn fetch(url: &str) -> Result<Vec<u8>, InstallError> {
+    if let Some(path) = url.strip_prefix("file://") {
+        let mut path = PathBuf::from(urlencoding::decode(path).unwrap_or_default().as_ref());
+        if path.is_dir() {
+            path = path.join("index.json");
+        }
+
+        return std::fs::read(&path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => InstallError::RequestFailed(url.to_string(), 404),
+            _ => InstallError::FetchFailed(url.to_string(), e.to_string()),
+        });
+    }
+
+    let request = Request::get(url)
+        .header(
+            "Accept",
+            "application/vnd.npm.install-v1+json; q=1.0, application/json; q=0.8",
+        )
+        .redirect_policy(isahc::config::RedirectPolicy::Limit(10))
+        .body(())
+        .map_err(|e| InstallError::FetchFailed(url.to_string(), e.to_string()))?;
+
+    let mut response = isahc::send(request)
+        .map_err(|e| InstallError::FetchFailed(url.to_string(), e.to_string()))?;
+
+    if !response.status().is_success() {
+        return Err(InstallError::RequestFailed(
+            url.to_string(),
+            response.status().as_u16(),
+        ));
+    }
+
+    response
+        .bytes()
+        .map_err(|e| InstallError::FetchFailed(url.to_string(), e.to_string()))
+}
@@ -0,0 +1,380 @@
+use super::InstallError;
+use crate::cache::cache_dir;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+use tar::Archive;
+
+/// Content-addressed store for extracted package tarballs.
+///
+/// Packages live in `<root>/<sha512 of the tarball>` so the same tarball is only ever
+/// downloaded and extracted once, no matter how many projects depend on it. Next to
+/// each package, `<key>.files.json` records the hash of every extracted file so the
+/// module loader can detect packages that were modified after installation.
+#[derive(Debug, Clone)]
+pub struct PackageStore {
+    root: PathBuf,
+    indexes: RefCell<BTreeMap<String, BTreeMap<String, String>>>,
+}
+
+impl Default for PackageStore {
+    fn default() -> Self {
+        Self::new(cache_dir().join("packages"))
+    }
+}
+
+impl PackageStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            indexes: RefCell::default(),
+        }
+    }
+
+    /// Returns the extracted package for an integrity hash if it's already in the store.
+    pub fn get(&self, integrity: &str) -> Option<PathBuf> {
+        let key = key_from_integrity(integrity)?;
+        let path = self.root.join(&key);
+
+        (path.is_dir() && self.index_path(&key).is_file()).then_some(path)
+    }
+
+    /// Checks a file against the hashes recorded when its package was extracted. Files
+    /// outside of the store are always considered valid.
+    pub fn verify_file(&self, file: &Path) -> Result<(), InstallError> {
+        let (Ok(file), Ok(root)) = (file.canonicalize(), self.root.canonicalize()) else {
+            return Ok(());
+        };
+        let Ok(relative) = file.strip_prefix(&root) else {
+            return Ok(());
+        };
+
+        let mut components = relative.components();
+        let key = match components.next() {
+            Some(Component::Normal(key)) => key.to_string_lossy().to_string(),
+            _ => return Ok(()),
+        };
+        let relative = index_key(components.as_path());
+
+        if !self.indexes.borrow().contains_key(&key) {
+            let index = fs::read_to_string(self.index_path(&key))
+                .ok()
+                .and_then(|index| serde_json::from_str(&index).ok())
+                .ok_or_else(|| InstallError::ModifiedPackage(file.clone()))?;
+
+            self.indexes.borrow_mut().insert(key.clone(), index);
+        }
+
+        let expected = self.indexes.borrow()[&key].get(&relative).cloned();
+        let actual = integrity_of(&fs::read(&file)?);
+
+        match expected {
+            Some(expected) if expected == actual => Ok(()),
+            _ => Err(InstallError::ModifiedPackage(file)),
+        }
+    }
+
+    /// Checks every file of a stored package against its file index, including files
+    /// that were added or removed since it was extracted.
+    pub fn verify_package(&self, integrity: &str) -> Result<(), InstallError> {
+        let Some(key) = key_from_integrity(integrity) else {
+            return Ok(());
+        };
+        let dir = self.root.join(&key);
+        let index: BTreeMap<String, String> = fs::read_to_string(self.index_path(&key))
+            .ok()
+            .and_then(|index| serde_json::from_str(&index).ok())
+            .ok_or_else(|| InstallError::ModifiedPackage(dir.clone()))?;
+
+        let mut actual = BTreeMap::new();
+        hash_files(&dir, Path::new(""), &mut actual)?;
+
+        let modified = index
+            .iter()
+            .find(|(path, hash)| actual.get(*path) != Some(*hash))
+            .map(|(path, _)| path)
+            .or_else(|| actual.keys().find(|path| !index.contains_key(*path)));
+
+        match modified {
+            Some(path) => Err(InstallError::ModifiedPackage(dir.join(path))),
+            None => Ok(()),
+        }
+    }
+
+    fn index_path(&self, key: &str) -> PathBuf {
+        self.root.join(format!("{key}.files.json"))
+    }
+
+    /// Verifies the tarball against `expected` (if any), extracts it and returns the
+    /// package directory along with its integrity hash.
+    pub fn insert(
+        &self,
+        name: &str,
+        tarball: &[u8],
+        expected: Option<&str>,
+    ) -> Result<(PathBuf, String), InstallError> {
+        let integrity = integrity_of(tarball);
+
+        if let Some(expected) = expected {
+            if !matches_integrity(name, expected, tarball)? {
+                return Err(InstallError::IntegrityMismatch(
+                    name.to_string(),
+                    expected.to_string(),
+                    integrity,
+                ));
+            }
+        }
+
+        let key = key_from_integrity(&integrity).expect("integrity is always sha512");
+        let target = self.root.join(&key);
+        if target.is_dir()
+            && self.index_path(&key).is_file()
+            && self.verify_package(&integrity).is_ok()
+        {
+            return Ok((target, integrity));
+        }
+        if target.exists() {
+            fs::remove_dir_all(&target)?;
+        }
+
+        // Extract next to the target first so a failed extraction never leaves a
+        // half-written package behind
+        let staging = self.root.join(format!(".{key}.tmp"));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        fs::create_dir_all(&staging)?;
+
+        let index = match extract(tarball, &staging) {
+            Ok(index) => index,
+            Err(e) => {
+                let _ = fs::remove_dir_all(&staging);
+                return Err(InstallError::ExtractFailed(name.to_string(), e.to_string()));
+            }
+        };
+        let index = serde_json::to_string(&index)
+            .map_err(|e| InstallError::ExtractFailed(name.to_string(), e.to_string()))?;
+        fs::write(self.index_path(&key), index)?;
+
+        if let Err(e) = fs::rename(&staging, &target) {
+            let _ = fs::remove_dir_all(&staging);
+            // Another process may have extracted the same package in the meantime
+            if !target.is_dir() {
+                return Err(e.into());
+            }
+        }
+
+        Ok((target, integrity))
+    }
+}
+
+/// Computes an SRI hash (`sha512-<base64>`) like the ones npm stores in `dist.integrity`.
+pub fn integrity_of(bytes: &[u8]) -> String {
+    format!("sha512-{}", STANDARD.encode(Sha512::digest(bytes)))
+}
+
+/// Checks a tarball against an SRI string, which may list several hashes. A hash that
+/// can't be checked is an error rather than a pass, so a tarball is never trusted
+/// without being verified.
+fn matches_integrity(name: &str, expected: &str, bytes: &[u8]) -> Result<bool, InstallError> {
+    let mut checked = false;
+
+    for hash in expected.split_whitespace() {
+        let Some((algorithm, digest)) = hash.split_once('-') else {
+            continue;
+        };
+        let actual = match algorithm {
+            "sha256" => STANDARD.encode(Sha256::digest(bytes)),
+            "sha384" => STANDARD.encode(Sha384::digest(bytes)),
+            "sha512" => STANDARD.encode(Sha512::digest(bytes)),
+            _ => continue,
+        };
+
+        // Options like `?foo` may follow the digest
+        if digest.split('?').next() == Some(actual.as_str()) {
+            return Ok(true);
+        }
+        checked = true;
+    }
+
+    if checked {
+        Ok(false)
+    } else {
+        Err(InstallError::UnsupportedIntegrity(
+            name.to_string(),
+            expected.to_string(),
+        ))
+    }
+}
+
+/// Turns a `sha512-<base64>` integrity into the hex digest used as the store key.
+pub fn key_from_integrity(integrity: &str) -> Option<String> {
+    let digest = STANDARD.decode(integrity.strip_prefix("sha512-")?).ok()?;
+
+    Some(digest.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Relative paths in the file index always use forward slashes.
+fn index_key(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Adds the integrity of every file under `dir` to `index`, keyed by its path relative
+/// to the package.
+fn hash_files(
+    dir: &Path,
+    relative: &Path,
+    index: &mut BTreeMap<String, String>,
+) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let relative = relative.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            hash_files(&path, &relative, index)?;
+        } else if path.is_file() {
+            index.insert(index_key(&relative), integrity_of(&fs::read(&path)?));
+        }
+    }
+
+    Ok(())
+}
+
+/// Extracts a gzipped tarball, stripping the top level directory (`package/` for npm).
+/// Only regular files and directories are extracted, since links could point outside
+/// of the package. Returns the integrity of every extracted file.
+fn extract(tarball: &[u8], dest: &Path) -> std::io::Result<BTreeMap<String, String>> {
+    let mut archive = Archive::new(GzDecoder::new(tarball));
+    let mut index = BTreeMap::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_type = entry.header().entry_type();
+        if !entry_type.is_file() && !entry_type.is_dir() {
+            continue;
+        }
+
+        let path = entry.path()?.into_owned();
+
+        let relative: PathBuf = path.components().skip(1).collect();
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+
+        // Never write outside of the package directory
+        if relative
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_)))
+        {
+            continue;
+        }
+
+        let target = dest.join(&relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        entry.unpack(&target)?;
+
+        if target.is_file() {
+            index.insert(index_key(&relative), integrity_of(&fs::read(&target)?));
+        }
+    }
+
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integrity_key() {
+        let integrity = integrity_of(b"ike");
+
+        assert!(integrity.starts_with("sha512-"));
+        assert_eq!(key_from_integrity(&integrity).unwrap().len(), 128);
+        assert_eq!(key_from_integrity("sha1-abc"), None);
+    }
+
+    #[test]
+    fn test_matches_integrity() {
+        let sha256 = format!("sha256-{}", STANDARD.encode(Sha256::digest(b"ike")));
+
+        assert!(matches_integrity("ike", &integrity_of(b"ike"), b"ike").unwrap());
+        assert!(matches_integrity("ike", &sha256, b"ike").unwrap());
+        assert!(matches_integrity("ike", &format!("sha1-abc {sha256}"), b"ike").unwrap());
+        assert!(!matches_integrity("ike", &integrity_of(b"ike"), b"deno").unwrap());
+        assert!(matches_integrity("ike", "sha1-abc", b"ike").is_err());
+    }
+
+    #[test]
+    fn test_insert_skips_links() {
+        let dir = std::env::temp_dir().join(format!("ike-store-links-test-{}", std::process::id()));
+        let outside = dir.join("outside");
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("secret"), "secret").unwrap();
+
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut append =
+            |path: &str, entry_type: tar::EntryType, link: Option<&Path>, data: &[u8]| {
+                let mut header = tar::Header::new_gnu();
+                header.set_entry_type(entry_type);
+                header.set_size(data.len() as u64);
+                header.set_mode(0o644);
+                if let Some(link) = link {
+                    header.set_link_name(link).unwrap();
+                }
+                builder.append_data(&mut header, path, data).unwrap();
+            };
+        append(
+            "package/index.js",
+            tar::EntryType::Regular,
+            None,
+            b"export default 1;",
+        );
+        append("package/lib", tar::EntryType::Symlink, Some(&outside), b"");
+        append(
+            "package/lib/evil.js",
+            tar::EntryType::Regular,
+            None,
+            b"evil",
+        );
+        append(
+            "package/secret",
+            tar::EntryType::Link,
+            Some(&outside.join("secret")),
+            b"",
+        );
+        let tarball = builder.into_inner().unwrap();
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &tarball).unwrap();
+        let tarball = encoder.finish().unwrap();
+
+        let store = PackageStore::new(dir.join("store"));
+        let (package, integrity) = store.insert("hostile", &tarball, None).unwrap();
+
+        assert!(package.join("index.js").is_file());
+        assert!(package.join("lib").symlink_metadata().unwrap().is_dir());
+        assert!(!package.join("secret").exists());
+        assert!(!outside.join("evil.js").exists());
+        assert_eq!(
+            fs::read_to_string(outside.join("secret")).unwrap(),
+            "secret"
+        );
+        assert!(store.verify_package(&integrity).is_ok());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,287 @@
+mod rules;
+
+use crate::{
+    runtime::commonjs::is_commonjs,
+    transpiler::{code_frame, line_column, SyntaxError},
+};
+use ike_toml::{CompilerConfig, LintSeverity};
+use oxc_allocator::Allocator;
+use oxc_ast::{Trivias, Visit};
+use oxc_parser::Parser;
+use oxc_semantic::SemanticBuilder;
+use oxc_span::SourceType;
+use rules::{Report, RuleVisitor};
+use rustc_hash::FxHashMap;
+use serde::Serialize;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// Every rule with its severity when ike.toml doesn't set one.
+pub const RULES: [(&str, LintSeverity); 5] = [
+    ("no-unused-vars", LintSeverity::Warn),
+    ("no-undef", LintSeverity::Error),
+    ("no-floating-promises", LintSeverity::Warn),
+    ("no-debugger", LintSeverity::Error),
+    ("no-dupe-keys", LintSeverity::Error),
+];
+
+/// Comment that silences the rules it lists, or all of them, on its line or the next one.
+const IGNORE_COMMENT: &str = "ike-lint-ignore";
+
+#[derive(Debug, Error)]
+pub enum LintError {
+    #[error("Unknown lint rule <cyan>{0}<r> in ike.toml, expected one of {1}")]
+    UnknownRule(String, String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub file: PathBuf,
+    pub rule: &'static str,
+    pub severity: LintSeverity,
+    pub message: String,
+    /// One based line of the reported code.
+    pub line: usize,
+    /// One based column of the reported code, in characters.
+    pub column: usize,
+    #[serde(skip)]
+    pub code_frame: String,
+}
+
+impl Diagnostic {
+    /// A file that doesn't parse, reported like the rules so it shows up in JSON output.
+    pub fn syntax_error(error: SyntaxError) -> Self {
+        Self {
+            file: error.file,
+            rule: "syntax",
+            severity: LintSeverity::Error,
+            message: error.message,
+            line: error.line,
+            column: error.column,
+            code_frame: error.code_frame,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Linter {
+    severities: FxHashMap<&'static str, LintSeverity>,
+    /// Bindings the JSX transform references, used wherever JSX is.
+    jsx_factories: Vec<String>,
+}
+
+impl Linter {
+    pub fn new(
+        config: &HashMap<String, LintSeverity>,
+        compiler: &CompilerConfig,
+    ) -> Result<Self, LintError> {
+        let mut severities: FxHashMap<&'static str, LintSeverity> = RULES.into_iter().collect();
+        for (rule, severity) in config {
+            let Some((name, _)) = RULES.iter().find(|(name, _)| name == rule) else {
+                let names: Vec<&str> = RULES.iter().map(|(name, _)| *name).collect();
+                return Err(LintError::UnknownRule(rule.clone(), names.join(", ")));
+            };
+            severities.insert(name, *severity);
+        }
+
+        let jsx_factories = [&compiler.jsx_pragma, &compiler.jsx_pragma_frag]
+            .into_iter()
+            .map(|pragma| pragma.as_deref().unwrap_or("React"))
+            .map(|pragma| pragma.split('.').next().unwrap_or(pragma).to_string())
+            .collect();
+
+        Ok(Self {
+            severities,
+            jsx_factories,
+        })
+    }
+
+    /// Runs the enabled rules on `source`, the content of `path`.
+    pub fn lint(&self, path: &Path, source: &str) -> Result<Vec<Diagnostic>, SyntaxError> {
+        let source_type = SourceType::from_path(path).unwrap_or_default();
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, source_type).parse();
+
+        if let Some(error) = ret.errors.into_iter().next() {
+            return Err(SyntaxError::new(path, source, error));
+        }
+
+        let semantic = SemanticBuilder::new(source, source_type)
+            .build(&ret.program)
+            .semantic;
+        let mut visitor = RuleVisitor::new(semantic.symbols(), is_commonjs(path));
+        visitor.visit_program(&ret.program);
+        let reports = visitor.finish(&self.jsx_factories);
+
+        let ignores = ignore_comments(source, &ret.trivias);
+        let mut diagnostics: Vec<Diagnostic> = reports
+            .into_iter()
+            .filter_map(|report| self.diagnostic(path, source, report, &ignores))
+            .collect();
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+
+        Ok(diagnostics)
+    }
+
+    fn diagnostic(
+        &self,
+        path: &Path,
+        source: &str,
+        report: Report,
+        ignores: &FxHashMap<usize, Vec<String>>,
+    ) -> Option<Diagnostic> {
+        let severity = self.severities[report.rule];
+        if severity == LintSeverity::Off {
+            return None;
+        }
+
+        let (line, column) = line_column(source, report.span.start as usize);
+        let ignored = ignores
+            .get(&line)
+            .is_some_and(|rules| rules.is_empty() || rules.iter().any(|rule| rule == report.rule));
+        if ignored {
+            return None;
+        }
+
+        let reported = &source[report.span.start as usize..report.span.end as usize];
+        let length = reported.lines().next().unwrap_or_default().chars().count();
+
+        Some(Diagnostic {
+            file: path.to_path_buf(),
+            rule: report.rule,
+            severity,
+            message: report.message,
+            line,
+            column,
+            code_frame: code_frame(source, line, column, length),
+        })
+    }
+}
+
+/// Lines silenced by `// ike-lint-ignore` comments, to the rules they list. A comment
+/// after code applies to its own line, otherwise to the next one.
+fn ignore_comments(source: &str, trivias: &Trivias) -> FxHashMap<usize, Vec<String>> {
+    let mut ignores = FxHashMap::default();
+
+    for comment in trivias.comments() {
+        let (start, end) = (comment.span.start as usize, comment.span.end as usize);
+        let text = source[start..end]
+            .trim_start_matches(['/', '*'])
+            .trim_end_matches(['/', '*'])
+            .trim();
+        let Some(rules) = text.strip_prefix(IGNORE_COMMENT) else {
+            continue;
+        };
+        // `ike-lint-ignored` or other words starting the same
+        if rules.starts_with(|char: char| !char.is_whitespace() && char != ',') {
+            continue;
+        }
+
+        let (line, _) = line_column(source, start);
+        let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+        let after_code = !source[line_start..start]
+            .trim()
+            .trim_end_matches(['/', '*'])
+            .is_empty();
+        let target = if after_code { line } else { line + 1 };
+
+        let rules: Vec<String> = rules
+            .split(|char: char| char.is_whitespace() || char == ',')
+            .filter(|rule| !rule.is_empty())
+            // `-- reason` ends the list
+            .take_while(|rule| !rule.starts_with("--"))
+            .map(String::from)
+            .collect();
+
+        // Several comments can target one line, an empty list silences every rule
+        match ignores.entry(target) {
+            Entry::Vacant(entry) => {
+                entry.insert(rules);
+            }
+            Entry::Occupied(mut entry) => {
+                let existing = entry.get_mut();
+                if existing.is_empty() || rules.is_empty() {
+                    existing.clear();
+                } else {
+                    existing.extend(rules);
+                }
+            }
+        }
+    }
+
+    ignores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(name: &str, source: &str) -> Vec<(&'static str, usize)> {
+        let linter = Linter::new(&HashMap::new(), &CompilerConfig::default()).unwrap();
+        linter
+            .lint(Path::new(name), source)
+            .unwrap()
+            .into_iter()
+            .map(|diagnostic| (diagnostic.rule, diagnostic.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_lint() {
+        let source = r#"import { used, unused } from "./a.js";
+const _ignored = 1;
+async function load() {}
+load();
+load().catch(() => {});
+await load();
+console.log(used, missing, typeof maybe, Ike.version);
+// ike-lint-ignore no-undef
+other();
+const point = { x: 1, x: 2 };
+export { point };
+"#;
+
+        assert_eq!(
+            lint("a.js", source),
+            vec![
+                ("no-unused-vars", 1),
+                ("no-floating-promises", 4),
+                ("no-undef", 7),
+                ("no-dupe-keys", 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_typescript() {
+        let source = r#"import type { Options } from "./options.ts";
+interface Local { options: Options }
+export function run(local: Local): Record<string, number> {
+  debugger;
+  return {};
+}
+"#;
+
+        assert_eq!(lint("a.ts", source), vec![("no-debugger", 4)]);
+    }
+
+    #[test]
+    fn test_ignore_comments() {
+        let source = "// ike-lint-ignore no-undef\nfoo(); /* ike-lint-ignore no-unused-vars */\n// ike-lint-ignore\nbar(); // ike-lint-ignore no-undef\n";
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, SourceType::default()).parse();
+        let ignores = ignore_comments(source, &ret.trivias);
+
+        assert_eq!(ignores[&2], ["no-undef", "no-unused-vars"]);
+        assert!(ignores[&4].is_empty());
+    }
+
+    #[test]
+    fn test_unknown_rule() {
+        let config = HashMap::from([("no-such-rule".to_string(), LintSeverity::Warn)]);
+        assert!(Linter::new(&config, &CompilerConfig::default()).is_err());
+    }
+}
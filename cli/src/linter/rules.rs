@@ -0,0 +1,552 @@
+use oxc_ast::{
+    ast::{
+        BindingIdentifier, BindingPatternKind, Declaration, ExportDefaultDeclaration,
+        ExportDefaultDeclarationKind, ExportNamedDeclaration, Expression, ExpressionStatement,
+        IdentifierReference, ImportDeclaration, ImportDeclarationSpecifier, JSXElement,
+        JSXFragment, JSXIdentifier, ObjectExpression, ObjectPropertyKind, PropertyKey,
+        PropertyKind, Statement, TSEnumDeclaration, TSInterfaceDeclaration, TSModuleDeclaration,
+        TSType, TSTypeName, UnaryExpression, VariableDeclaration,
+    },
+    syntax_directed_operations::BoundNames,
+    visit::walk,
+    Visit,
+};
+use oxc_semantic::{SymbolId, SymbolTable};
+use oxc_span::{GetSpan, Span};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// Globals of the ECMAScript standard library.
+const BUILTIN_GLOBALS: [&str; 60] = [
+    "AggregateError",
+    "Array",
+    "ArrayBuffer",
+    "Atomics",
+    "BigInt",
+    "BigInt64Array",
+    "BigUint64Array",
+    "Boolean",
+    "DataView",
+    "Date",
+    "Error",
+    "EvalError",
+    "FinalizationRegistry",
+    "Float32Array",
+    "Float64Array",
+    "Function",
+    "Infinity",
+    "Int16Array",
+    "Int32Array",
+    "Int8Array",
+    "Intl",
+    "JSON",
+    "Map",
+    "Math",
+    "NaN",
+    "Number",
+    "Object",
+    "Promise",
+    "Proxy",
+    "RangeError",
+    "ReferenceError",
+    "Reflect",
+    "RegExp",
+    "Set",
+    "SharedArrayBuffer",
+    "String",
+    "Symbol",
+    "SyntaxError",
+    "TypeError",
+    "URIError",
+    "Uint16Array",
+    "Uint32Array",
+    "Uint8Array",
+    "Uint8ClampedArray",
+    "WeakMap",
+    "WeakRef",
+    "WeakSet",
+    "arguments",
+    "decodeURI",
+    "decodeURIComponent",
+    "encodeURI",
+    "encodeURIComponent",
+    "escape",
+    "eval",
+    "globalThis",
+    "isFinite",
+    "isNaN",
+    "parseFloat",
+    "parseInt",
+    "undefined",
+];
+
+/// Globals defined by the ike runtime, see `runtime.js` and `setup_context`.
+const IKE_GLOBALS: [&str; 25] = [
+    "$rustFunction",
+    "ByteLengthQueuingStrategy",
+    "CountQueuingStrategy",
+    "Headers",
+    "Ike",
+    "ReadableByteStreamController",
+    "ReadableStream",
+    "ReadableStreamBYOBReader",
+    "ReadableStreamBYOBRequest",
+    "ReadableStreamDefaultController",
+    "ReadableStreamDefaultReader",
+    "TextDecoder",
+    "TextEncoder",
+    "TransformStream",
+    "TransformStreamDefaultController",
+    "URL",
+    "URLSearchParams",
+    "WritableStream",
+    "WritableStreamDefaultController",
+    "WritableStreamDefaultWriter",
+    "atob",
+    "btoa",
+    "clearTimeout",
+    "console",
+    "setTimeout",
+];
+
+/// Parameters of the function CommonJS modules are wrapped in.
+const COMMONJS_GLOBALS: [&str; 5] = ["exports", "require", "module", "__filename", "__dirname"];
+
+/// Functions of `Ike` that return a promise.
+const IKE_ASYNC_FUNCTIONS: [&str; 5] = [
+    "createDir",
+    "createFile",
+    "readFile",
+    "readTextFile",
+    "remove",
+];
+
+/// Functions of `Promise` that return a promise.
+const PROMISE_FUNCTIONS: [&str; 6] = ["all", "allSettled", "any", "race", "reject", "resolve"];
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub rule: &'static str,
+    pub span: Span,
+    pub message: String,
+}
+
+/// Runs every rule in a single pass, [`RuleVisitor::finish`] reports what can only be
+/// known after the whole file was seen.
+pub struct RuleVisitor<'s> {
+    symbols: &'s SymbolTable,
+    commonjs: bool,
+    reports: Vec<Report>,
+    /// Bindings that may be unused, with the span of their name.
+    declarations: Vec<(SymbolId, String, Span)>,
+    used_symbols: FxHashSet<SymbolId>,
+    /// Names referenced without a resolved binding, they keep any binding of the same
+    /// name used.
+    used_names: FxHashSet<String>,
+    exported_names: FxHashSet<String>,
+    async_functions: FxHashSet<SymbolId>,
+    /// Calls of local functions in statement position, floating if the function is async.
+    local_calls: Vec<(SymbolId, Span)>,
+    /// Inside of a type, where references may be global types.
+    type_depth: usize,
+    /// Inside of `declare module` or `declare global`.
+    ambient_depth: usize,
+    /// The operand of the `typeof` being visited, it may be undefined.
+    typeof_operand: Option<Span>,
+    has_jsx: bool,
+}
+
+impl<'s> RuleVisitor<'s> {
+    pub fn new(symbols: &'s SymbolTable, commonjs: bool) -> Self {
+        Self {
+            symbols,
+            commonjs,
+            reports: Vec::new(),
+            declarations: Vec::new(),
+            used_symbols: FxHashSet::default(),
+            used_names: FxHashSet::default(),
+            exported_names: FxHashSet::default(),
+            async_functions: FxHashSet::default(),
+            local_calls: Vec::new(),
+            type_depth: 0,
+            ambient_depth: 0,
+            typeof_operand: None,
+            has_jsx: false,
+        }
+    }
+
+    pub fn finish(mut self, jsx_factories: &[String]) -> Vec<Report> {
+        // Functions are hoisted, so calls may come before the declaration
+        for (symbol_id, span) in std::mem::take(&mut self.local_calls) {
+            if self.async_functions.contains(&symbol_id) {
+                self.report_floating_promise(span);
+            }
+        }
+
+        if self.has_jsx {
+            self.used_names.extend(jsx_factories.iter().cloned());
+        }
+
+        let mut reported = FxHashSet::default();
+        for (symbol_id, name, span) in std::mem::take(&mut self.declarations) {
+            let used = self.used_symbols.contains(&symbol_id)
+                || self.used_names.contains(&name)
+                || self.exported_names.contains(&name)
+                || name.starts_with('_');
+            // Overloads and merged declarations share their symbol
+            if !used && reported.insert(symbol_id) {
+                self.report(
+                    "no-unused-vars",
+                    span,
+                    format!("`{name}` is declared but never used"),
+                );
+            }
+        }
+
+        self.reports
+    }
+
+    fn report(&mut self, rule: &'static str, span: Span, message: String) {
+        self.reports.push(Report {
+            rule,
+            span,
+            message,
+        });
+    }
+
+    fn report_floating_promise(&mut self, span: Span) {
+        self.report(
+            "no-floating-promises",
+            span,
+            "Promise is neither awaited nor handled, add `await` or `.catch()`".to_string(),
+        );
+    }
+
+    fn declare(&mut self, ident: &BindingIdentifier) {
+        if self.ambient_depth > 0 {
+            return;
+        }
+        if let Some(symbol_id) = ident.symbol_id.get() {
+            self.declarations
+                .push((symbol_id, ident.name.to_string(), ident.span));
+        }
+    }
+
+    fn is_global(&self, name: &str) -> bool {
+        BUILTIN_GLOBALS.contains(&name)
+            || IKE_GLOBALS.contains(&name)
+            || (self.commonjs && COMMONJS_GLOBALS.contains(&name))
+    }
+
+    fn resolve(&self, ident: &IdentifierReference) -> Option<SymbolId> {
+        ident
+            .reference_id
+            .get()
+            .and_then(|reference_id| self.symbols.get_reference(reference_id).symbol_id())
+    }
+
+    /// Whether the statement `expr` creates a promise nothing handles.
+    fn check_floating_promise(&mut self, expr: &Expression) {
+        let floating = match expr {
+            Expression::CallExpression(call) => match &call.callee {
+                Expression::Identifier(ident) => {
+                    if let Some(symbol_id) = self.resolve(ident) {
+                        self.local_calls.push((symbol_id, expr.span()));
+                    }
+                    false
+                }
+                Expression::StaticMemberExpression(member) => {
+                    let object = match &member.object {
+                        Expression::Identifier(ident) => Some(ident.name.as_str()),
+                        _ => None,
+                    };
+                    match (object, member.property.name.as_str()) {
+                        // `.then(onFulfilled, onRejected)` handles the rejection
+                        (_, "then") => call.arguments.len() < 2,
+                        (_, "finally") => true,
+                        (Some("Promise"), name) => PROMISE_FUNCTIONS.contains(&name),
+                        (Some("Ike"), name) => IKE_ASYNC_FUNCTIONS.contains(&name),
+                        _ => false,
+                    }
+                }
+                // Async IIFEs
+                Expression::ParenthesizedExpression(paren) => match &paren.expression {
+                    Expression::ArrowFunctionExpression(arrow) => arrow.r#async,
+                    Expression::FunctionExpression(function) => function.r#async,
+                    _ => false,
+                },
+                _ => false,
+            },
+            Expression::NewExpression(new) => {
+                matches!(&new.callee, Expression::Identifier(ident) if ident.name == "Promise")
+            }
+            Expression::ImportExpression(_) => true,
+            _ => false,
+        };
+
+        if floating {
+            self.report_floating_promise(expr.span());
+        }
+    }
+
+    /// Names bound by an exported declaration.
+    fn export_declaration(&mut self, declaration: &Declaration) {
+        match declaration {
+            Declaration::VariableDeclaration(decl) => {
+                for declarator in &decl.declarations {
+                    declarator.id.bound_names(&mut |ident| {
+                        self.exported_names.insert(ident.name.to_string());
+                    });
+                }
+            }
+            Declaration::FunctionDeclaration(function) => {
+                self.exported_names
+                    .extend(function.id.iter().map(|id| id.name.to_string()));
+            }
+            Declaration::ClassDeclaration(class) => {
+                self.exported_names
+                    .extend(class.id.iter().map(|id| id.name.to_string()));
+            }
+            Declaration::TSTypeAliasDeclaration(alias) => {
+                self.exported_names.insert(alias.id.name.to_string());
+            }
+            Declaration::TSInterfaceDeclaration(interface) => {
+                self.exported_names.insert(interface.id.name.to_string());
+            }
+            Declaration::TSEnumDeclaration(decl) => {
+                self.exported_names.insert(decl.id.name.to_string());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'a, 's> Visit<'a> for RuleVisitor<'s> {
+    fn visit_statement(&mut self, statement: &Statement<'a>) {
+        if let Statement::DebuggerStatement(stmt) = statement {
+            self.report(
+                "no-debugger",
+                stmt.span,
+                "Unexpected `debugger` statement".to_string(),
+            );
+        }
+
+        walk::walk_statement(self, statement);
+    }
+
+    fn visit_import_declaration(&mut self, decl: &ImportDeclaration<'a>) {
+        for specifier in decl.specifiers.iter().flatten() {
+            match specifier {
+                ImportDeclarationSpecifier::ImportSpecifier(specifier) => {
+                    self.declare(&specifier.local);
+                }
+                ImportDeclarationSpecifier::ImportDefaultSpecifier(specifier) => {
+                    self.declare(&specifier.local);
+                }
+                ImportDeclarationSpecifier::ImportNamespaceSpecifier(specifier) => {
+                    self.declare(&specifier.local);
+                }
+            }
+        }
+
+        walk::walk_import_declaration(self, decl);
+    }
+
+    fn visit_export_named_declaration(&mut self, decl: &ExportNamedDeclaration<'a>) {
+        if let Some(declaration) = &decl.declaration {
+            self.export_declaration(declaration);
+        }
+        // Re-exports don't use local bindings
+        if decl.source.is_none() {
+            for specifier in &decl.specifiers {
+                self.exported_names
+                    .insert(specifier.local.name().to_string());
+            }
+        }
+
+        walk::walk_export_named_declaration(self, decl);
+    }
+
+    fn visit_export_default_declaration(&mut self, decl: &ExportDefaultDeclaration<'a>) {
+        match &decl.declaration {
+            ExportDefaultDeclarationKind::FunctionDeclaration(function) => {
+                self.exported_names
+                    .extend(function.id.iter().map(|id| id.name.to_string()));
+            }
+            ExportDefaultDeclarationKind::ClassDeclaration(class) => {
+                self.exported_names
+                    .extend(class.id.iter().map(|id| id.name.to_string()));
+            }
+            _ => {}
+        }
+
+        walk::walk_export_default_declaration(self, decl);
+    }
+
+    fn visit_declaration(&mut self, declaration: &Declaration<'a>) {
+        match declaration {
+            Declaration::FunctionDeclaration(function) if !function.declare => {
+                if let Some(id) = &function.id {
+                    self.declare(id);
+                    if function.r#async {
+                        self.async_functions.extend(id.symbol_id.get());
+                    }
+                }
+            }
+            Declaration::ClassDeclaration(class) if !class.declare => {
+                if let Some(id) = &class.id {
+                    self.declare(id);
+                }
+            }
+            Declaration::TSTypeAliasDeclaration(alias) if !alias.declare => {
+                self.declare(&alias.id);
+            }
+            Declaration::TSInterfaceDeclaration(interface) if !interface.declare => {
+                self.declare(&interface.id);
+            }
+            Declaration::TSEnumDeclaration(decl) if !decl.declare => {
+                self.declare(&decl.id);
+            }
+            _ => {}
+        }
+
+        walk::walk_declaration(self, declaration);
+    }
+
+    fn visit_variable_declaration(&mut self, decl: &VariableDeclaration<'a>) {
+        if !decl.declare {
+            for declarator in &decl.declarations {
+                declarator.id.bound_names(&mut |ident| self.declare(ident));
+
+                let is_async = match &declarator.init {
+                    Some(Expression::ArrowFunctionExpression(arrow)) => arrow.r#async,
+                    Some(Expression::FunctionExpression(function)) => function.r#async,
+                    _ => false,
+                };
+                if let (true, BindingPatternKind::BindingIdentifier(id)) =
+                    (is_async, &declarator.id.kind)
+                {
+                    self.async_functions.extend(id.symbol_id.get());
+                }
+            }
+        }
+
+        walk::walk_variable_declaration(self, decl);
+    }
+
+    fn visit_identifier_reference(&mut self, ident: &IdentifierReference<'a>) {
+        let Some(reference_id) = ident.reference_id.get() else {
+            self.used_names.insert(ident.name.to_string());
+            return;
+        };
+
+        match self.symbols.get_reference(reference_id).symbol_id() {
+            Some(symbol_id) => {
+                self.used_symbols.insert(symbol_id);
+            }
+            None => {
+                self.used_names.insert(ident.name.to_string());
+
+                let checked = self.type_depth == 0
+                    && self.ambient_depth == 0
+                    && self.typeof_operand != Some(ident.span);
+                if checked && !self.is_global(&ident.name) {
+                    self.report(
+                        "no-undef",
+                        ident.span,
+                        format!("`{}` is not defined", ident.name),
+                    );
+                }
+            }
+        }
+    }
+
+    fn visit_expression_statement(&mut self, stmt: &ExpressionStatement<'a>) {
+        self.check_floating_promise(&stmt.expression);
+        walk::walk_expression_statement(self, stmt);
+    }
+
+    fn visit_unary_expression(&mut self, expr: &UnaryExpression<'a>) {
+        if let (true, Expression::Identifier(ident)) =
+            (expr.operator.as_str() == "typeof", &expr.argument)
+        {
+            self.typeof_operand = Some(ident.span);
+        }
+
+        walk::walk_unary_expression(self, expr);
+    }
+
+    fn visit_object_expression(&mut self, expr: &ObjectExpression<'a>) {
+        let mut keys = FxHashMap::default();
+
+        for property in &expr.properties {
+            let ObjectPropertyKind::ObjectProperty(property) = property else {
+                continue;
+            };
+            // Getters and setters come in pairs
+            if property.kind != PropertyKind::Init || property.computed {
+                continue;
+            }
+            let key = match &property.key {
+                PropertyKey::StaticIdentifier(ident) => ident.name.to_string(),
+                PropertyKey::StringLiteral(literal) => literal.value.to_string(),
+                _ => continue,
+            };
+
+            if keys.insert(key.clone(), property.key.span()).is_some() {
+                self.report(
+                    "no-dupe-keys",
+                    property.key.span(),
+                    format!("Duplicate key `{key}`, only the last one is used"),
+                );
+            }
+        }
+
+        walk::walk_object_expression(self, expr);
+    }
+
+    fn visit_ts_type(&mut self, ty: &TSType<'a>) {
+        self.type_depth += 1;
+        walk::walk_ts_type(self, ty);
+        self.type_depth -= 1;
+    }
+
+    fn visit_ts_type_name(&mut self, name: &TSTypeName<'a>) {
+        self.type_depth += 1;
+        walk::walk_ts_type_name(self, name);
+        self.type_depth -= 1;
+    }
+
+    fn visit_ts_interface_declaration(&mut self, decl: &TSInterfaceDeclaration<'a>) {
+        // `extends` clauses are expressions, but name types
+        self.type_depth += 1;
+        walk::walk_ts_interface_declaration(self, decl);
+        self.type_depth -= 1;
+    }
+
+    fn visit_ts_enum_declaration(&mut self, decl: &TSEnumDeclaration<'a>) {
+        // Members can reference each other by their name
+        self.type_depth += 1;
+        walk::walk_ts_enum_declaration(self, decl);
+        self.type_depth -= 1;
+    }
+
+    fn visit_ts_module_declaration(&mut self, decl: &TSModuleDeclaration<'a>) {
+        self.ambient_depth += 1;
+        walk::walk_ts_module_declaration(self, decl);
+        self.ambient_depth -= 1;
+    }
+
+    fn visit_jsx_element(&mut self, element: &JSXElement<'a>) {
+        self.has_jsx = true;
+        walk::walk_jsx_element(self, element);
+    }
+
+    fn visit_jsx_fragment(&mut self, fragment: &JSXFragment<'a>) {
+        self.has_jsx = true;
+        walk::walk_jsx_fragment(self, fragment);
+    }
+
+    fn visit_jsx_identifier(&mut self, ident: &JSXIdentifier<'a>) {
+        self.used_names.insert(ident.name.to_string());
+    }
+}
@@ -1,15 +1,28 @@
 #![feature(let_chains)]
 #![feature(async_closure)]
 
+pub mod bundler;
+pub mod cache;
+pub mod check;
 pub mod cli;
+pub mod dotenv;
 mod error;
 pub mod format;
+pub mod formatter;
 pub mod fs;
 pub mod globals;
+pub mod import_map;
+pub mod install;
+pub mod linter;
 pub mod macros;
 pub mod panic_handler;
+pub mod remote;
+pub mod repl;
+pub mod resolver;
 pub mod runtime;
+pub mod standalone;
 pub mod testing;
+pub mod transpile_cache;
 pub mod transpiler;
 mod utils;
 mod which;
@@ -18,13 +31,28 @@ use anyhow::Result;
 use cli::cli::Cli;
 use ike_logger::{elog, Logger};
 use panic_handler::setup_panic_handler;
+use standalone::Standalone;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     setup_panic_handler();
 
-    // TODO: fix this
-    // dotenvy::dotenv()?;
+    // Executables made by `ike compile` run their embedded program instead of the CLI
+    match Standalone::find() {
+        Ok(Some(standalone)) => {
+            if let Err(e) = standalone.run() {
+                elog!(error, "{}", e);
+                std::process::exit(1);
+            }
+
+            return Ok(());
+        }
+        Ok(None) => {}
+        Err(e) => {
+            elog!(error, "{}", e);
+            std::process::exit(1);
+        }
+    }
 
     let cli = Cli::new();
     match cli.run().await {
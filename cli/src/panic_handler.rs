@@ -1,3 +1,4 @@
+use crate::standalone;
 use ike_logger::{elog, pretty_fmt, Logger};
 
 pub fn setup_panic_handler() {
@@ -13,6 +14,12 @@ pub fn setup_panic_handler() {
         elog!("<d>==============================================================<r>");
         eprintln!();
         eprintln!("Ike {}", env!("CARGO_PKG_VERSION"),);
+        if let Some(metadata) = standalone::metadata() {
+            eprintln!(
+                "Program: {} {} (compiled with Ike {})",
+                metadata.name, metadata.version, metadata.ike_version
+            );
+        }
         print_sys_info();
         eprint!("Args: ");
         let args = std::env::args();
@@ -0,0 +1,500 @@
+use crate::cache::cache_dir;
+use isahc::{config::Configurable, prelude::*, Request};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
+};
+use thiserror::Error;
+use url::Url;
+
+// Surfaced to JS through the module loader, so the messages are plain text
+#[derive(Debug, Error)]
+pub enum RemoteError {
+    #[error("Invalid module URL \"{0}\": {1}")]
+    InvalidUrl(String, String),
+    #[error("Failed to fetch {0}: {1}")]
+    FetchFailed(String, String),
+    #[error("Failed to fetch {0}: server responded with {1}")]
+    RequestFailed(String, u16),
+    #[error("{0} is not cached, run without --cached-only to download it")]
+    NotCached(String),
+    #[error("Failed to cache {0}: {1}")]
+    FailedToCache(String, String),
+    #[error("Refusing to follow the redirect from {0} to {1}")]
+    InvalidRedirect(String, String),
+    /// Net access to the host (`net_host`) of a URL or one of its redirects was denied.
+    #[error("Requires network access to \"{0}\"")]
+    NetDenied(String),
+}
+
+/// How remote modules are looked up in the cache, set by `--reload` and `--cached-only`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheSetting {
+    /// Use the cached copy, download modules that aren't cached yet.
+    #[default]
+    Use,
+    /// Download every module again and refresh the cache.
+    Reload,
+    /// Never touch the network, fail if a module isn't cached.
+    Only,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Metadata {
+    url: String,
+    /// URL after following redirects, relative imports resolve against it.
+    final_url: String,
+    content_type: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RemoteModule {
+    pub url: Url,
+    pub source: String,
+    content_type: Option<String>,
+}
+
+impl RemoteModule {
+    /// File name with the extension the module should be transpiled as. The extension
+    /// of the URL wins, the `Content-Type` header is used for URLs without one.
+    pub fn virtual_path(&self) -> PathBuf {
+        let from_url = Path::new(self.url.path())
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .filter(|ext| {
+                matches!(
+                    *ext,
+                    "js" | "mjs" | "cjs" | "jsx" | "ts" | "mts" | "cts" | "tsx"
+                )
+            });
+        let extension = from_url.unwrap_or_else(|| {
+            let media_type = self
+                .content_type
+                .as_deref()
+                .and_then(|content_type| content_type.split(';').next())
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase();
+
+            match media_type.as_str() {
+                "application/typescript"
+                | "application/x-typescript"
+                | "text/typescript"
+                | "video/vnd.dlna.mpeg-tts"
+                | "video/mp2t" => "ts",
+                "text/tsx" => "tsx",
+                "text/jsx" => "jsx",
+                _ => "js",
+            }
+        });
+
+        PathBuf::from(format!("remote.{extension}"))
+    }
+}
+
+/// Returns true for `http://` and `https://` specifiers.
+pub fn is_remote(specifier: &str) -> bool {
+    specifier.starts_with("https://") || specifier.starts_with("http://")
+}
+
+/// Resolves `specifier` imported from a remote module. Absolute URLs are kept as they
+/// are, relative and root-relative paths are joined with the importing URL. Returns
+/// `None` for bare specifiers, they go through the usual resolution.
+pub fn resolve_url(specifier: &str, referrer: &str) -> Result<Option<Url>, RemoteError> {
+    let invalid =
+        |e: url::ParseError| RemoteError::InvalidUrl(specifier.to_string(), e.to_string());
+
+    if is_remote(specifier) {
+        return Url::parse(specifier).map(Some).map_err(invalid);
+    }
+
+    let is_relative =
+        specifier.starts_with("./") || specifier.starts_with("../") || specifier.starts_with('/');
+    if !is_relative || !is_remote(referrer) {
+        return Ok(None);
+    }
+
+    let base = Url::parse(referrer)
+        .map_err(|e| RemoteError::InvalidUrl(referrer.to_string(), e.to_string()))?;
+
+    base.join(specifier).map(Some).map_err(invalid)
+}
+
+/// Host of a URL the way `--allow-net` lists it, with the port when there is one.
+pub fn net_host(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+
+    match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    }
+}
+
+/// Remote modules stored on disk, keyed by the hash of their URL.
+#[derive(Debug, Clone)]
+pub struct RemoteCache {
+    dir: PathBuf,
+    setting: CacheSetting,
+}
+
+impl Default for RemoteCache {
+    fn default() -> Self {
+        Self::new(cache_dir().join("remote"), cache_setting())
+    }
+}
+
+impl RemoteCache {
+    pub fn new(dir: PathBuf, setting: CacheSetting) -> Self {
+        Self { dir, setting }
+    }
+
+    /// Whether loading `url` downloads it, which needs net access to its host.
+    pub fn requires_network(&self, url: &Url) -> bool {
+        match self.setting {
+            CacheSetting::Use => self.read(url).is_none(),
+            CacheSetting::Reload => true,
+            CacheSetting::Only => false,
+        }
+    }
+
+    /// Loads a module from the cache or downloads it. `allow_host` is asked before every
+    /// request, including the ones made to follow redirects.
+    pub fn load(
+        &self,
+        url: &Url,
+        allow_host: impl FnMut(&Url) -> bool,
+    ) -> Result<RemoteModule, RemoteError> {
+        if self.setting != CacheSetting::Reload {
+            if let Some(module) = self.read(url) {
+                return Ok(module);
+            }
+        }
+
+        if self.setting == CacheSetting::Only {
+            return Err(RemoteError::NotCached(url.to_string()));
+        }
+
+        let module = fetch(url, allow_host)?;
+        self.write(url, &module)
+            .map_err(|e| RemoteError::FailedToCache(url.to_string(), e.to_string()))?;
+
+        Ok(module)
+    }
+
+    /// Removes every cached module, returns how many there were.
+    pub fn clean(&self) -> std::io::Result<usize> {
+        if !self.dir.exists() {
+            return Ok(0);
+        }
+
+        let count = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .count();
+        std::fs::remove_dir_all(&self.dir)?;
+
+        Ok(count)
+    }
+
+    fn entry(&self, url: &Url) -> PathBuf {
+        let hash: String = Sha256::digest(url.as_str().as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        self.dir.join(hash)
+    }
+
+    fn read(&self, url: &Url) -> Option<RemoteModule> {
+        let entry = self.entry(url);
+        let metadata = std::fs::read_to_string(entry.with_extension("json")).ok()?;
+        let metadata: Metadata = serde_json::from_str(&metadata).ok()?;
+        let source = std::fs::read_to_string(&entry).ok()?;
+
+        Some(RemoteModule {
+            url: Url::parse(&metadata.final_url).ok()?,
+            source,
+            content_type: metadata.content_type,
+        })
+    }
+
+    fn write(&self, url: &Url, module: &RemoteModule) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let entry = self.entry(url);
+        let metadata = Metadata {
+            url: url.to_string(),
+            final_url: module.url.to_string(),
+            content_type: module.content_type.clone(),
+        };
+
+        std::fs::write(&entry, &module.source)?;
+        std::fs::write(
+            entry.with_extension("json"),
+            serde_json::to_string_pretty(&metadata)?,
+        )
+    }
+}
+
+const MAX_REDIRECTS: usize = 10;
+
+/// Downloads a module, following redirects by hand so every host it's fetched from goes
+/// through `allow_host`.
+fn fetch(url: &Url, mut allow_host: impl FnMut(&Url) -> bool) -> Result<RemoteModule, RemoteError> {
+    let failed =
+        |e: &dyn std::fmt::Display| RemoteError::FetchFailed(url.to_string(), e.to_string());
+    let mut current = url.clone();
+
+    for _ in 0..=MAX_REDIRECTS {
+        if !allow_host(&current) {
+            return Err(RemoteError::NetDenied(net_host(&current)));
+        }
+
+        let request = Request::get(current.as_str())
+            .header(
+                "Accept",
+                "application/typescript, application/javascript, */*",
+            )
+            .redirect_policy(isahc::config::RedirectPolicy::None)
+            .body(())
+            .map_err(|e| failed(&e))?;
+
+        let mut response = isahc::send(request).map_err(|e| failed(&e))?;
+
+        if response.status().is_redirection() {
+            let location = response
+                .headers()
+                .get("location")
+                .and_then(|value| value.to_str().ok());
+
+            if let Some(location) = location {
+                current = redirect_target(&current, location)?;
+                continue;
+            }
+        }
+
+        if !response.status().is_success() {
+            return Err(RemoteError::RequestFailed(
+                url.to_string(),
+                response.status().as_u16(),
+            ));
+        }
+
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let source = response.text().map_err(|e| failed(&e))?;
+
+        return Ok(RemoteModule {
+            url: current,
+            source,
+            content_type,
+        });
+    }
+
+    Err(failed(&"too many redirects"))
+}
+
+/// Resolves the `Location` of a redirect. Only http(s) URLs are followed and https
+/// modules are never downgraded to http.
+fn redirect_target(from: &Url, location: &str) -> Result<Url, RemoteError> {
+    let invalid = || RemoteError::InvalidRedirect(from.to_string(), location.to_string());
+    let target = from.join(location).map_err(|_| invalid())?;
+
+    if !is_remote(target.as_str()) || (from.scheme() == "https" && target.scheme() == "http") {
+        return Err(invalid());
+    }
+
+    Ok(target)
+}
+
+static CACHE_SETTING: LazyLock<Mutex<CacheSetting>> = LazyLock::new(Default::default);
+
+pub fn set_cache_setting(setting: CacheSetting) {
+    *CACHE_SETTING.lock().unwrap() = setting;
+}
+
+pub fn cache_setting() -> CacheSetting {
+    *CACHE_SETTING.lock().unwrap()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        sync::atomic::{AtomicUsize, Ordering},
+        sync::Arc,
+    };
+
+    /// Serves `body` as TypeScript for every request and counts how many were made.
+    pub(crate) fn serve(body: &'static str) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut buf = [0; 1024];
+                let _ = stream.read(&mut buf);
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/typescript\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+            }
+        });
+
+        (format!("http://{addr}"), requests)
+    }
+
+    /// Redirects every request to `location`.
+    fn redirect(location: String) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut buf = [0; 1024];
+                let _ = stream.read(&mut buf);
+
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 302 Found\r\nLocation: {location}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+            }
+        });
+
+        format!("http://{addr}")
+    }
+
+    #[test]
+    fn test_net_host() {
+        let host = |url: &str| net_host(&Url::parse(url).unwrap());
+
+        assert_eq!(host("https://example.com/mod.ts"), "example.com");
+        assert_eq!(host("http://localhost:8080/mod.ts"), "localhost:8080");
+        assert_eq!(host("https://example.com:443/mod.ts"), "example.com");
+    }
+
+    #[test]
+    fn test_resolve_url() {
+        let referrer = "https://example.com/lib/mod.ts";
+
+        assert_eq!(
+            resolve_url("./util.ts", referrer)
+                .unwrap()
+                .unwrap()
+                .as_str(),
+            "https://example.com/lib/util.ts"
+        );
+        assert_eq!(
+            resolve_url("../util.ts", referrer)
+                .unwrap()
+                .unwrap()
+                .as_str(),
+            "https://example.com/util.ts"
+        );
+        assert_eq!(
+            resolve_url("/std/mod.ts", referrer)
+                .unwrap()
+                .unwrap()
+                .as_str(),
+            "https://example.com/std/mod.ts"
+        );
+        assert!(resolve_url("lodash", referrer).unwrap().is_none());
+        assert!(resolve_url("./util.ts", "/app/main.ts").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_cache_settings() {
+        let (server, requests) = serve("export const answer: number = 42;");
+        let url = Url::parse(&format!("{server}/mod")).unwrap();
+        let dir = std::env::temp_dir().join(format!("ike-remote-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let only = RemoteCache::new(dir.clone(), CacheSetting::Only);
+        assert!(matches!(
+            only.load(&url, |_| true),
+            Err(RemoteError::NotCached(_))
+        ));
+
+        let module = RemoteCache::new(dir.clone(), CacheSetting::Use)
+            .load(&url, |_| true)
+            .unwrap();
+        assert_eq!(module.source, "export const answer: number = 42;");
+        assert_eq!(module.virtual_path(), PathBuf::from("remote.ts"));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        assert!(only.load(&url, |_| true).is_ok());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        RemoteCache::new(dir.clone(), CacheSetting::Reload)
+            .load(&url, |_| true)
+            .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        assert!(!RemoteCache::new(dir.clone(), CacheSetting::Use).requires_network(&url));
+        assert!(!only.requires_network(&url));
+        assert!(RemoteCache::new(dir.clone(), CacheSetting::Reload).requires_network(&url));
+        let other = Url::parse(&format!("{server}/other")).unwrap();
+        assert!(RemoteCache::new(dir.clone(), CacheSetting::Use).requires_network(&other));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_redirects() {
+        let (server, _) = serve("export default 'moved';");
+        let target = Url::parse(&format!("{server}/mod.ts")).unwrap();
+        let url = Url::parse(&format!("{}/mod.ts", redirect(target.to_string()))).unwrap();
+        let dir =
+            std::env::temp_dir().join(format!("ike-remote-redirect-test-{}", std::process::id()));
+        let cache = RemoteCache::new(dir.clone(), CacheSetting::Reload);
+
+        // Every host on the way needs net access, not only the first one
+        let mut hosts = vec![];
+        let module = cache
+            .load(&url, |url| {
+                hosts.push(net_host(url));
+                true
+            })
+            .unwrap();
+        assert_eq!(module.url, target);
+        assert_eq!(module.source, "export default 'moved';");
+        assert_eq!(hosts, [net_host(&url), net_host(&target)]);
+
+        let first = net_host(&url);
+        assert!(matches!(
+            cache.load(&url, |url| net_host(url) == first),
+            Err(RemoteError::NetDenied(host)) if host == net_host(&target)
+        ));
+
+        let secure = Url::parse("https://example.com/mod.ts").unwrap();
+        assert!(matches!(
+            redirect_target(&secure, "http://example.com/mod.ts"),
+            Err(RemoteError::InvalidRedirect(..))
+        ));
+        assert!(matches!(
+            redirect_target(&secure, "file:///etc/passwd"),
+            Err(RemoteError::InvalidRedirect(..))
+        ));
+        assert_eq!(
+            redirect_target(&secure, "/v2/mod.ts").unwrap().as_str(),
+            "https://example.com/v2/mod.ts"
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,205 @@
+use boa_engine::{property::PropertyKey, Context, JsValue, Source};
+use rustyline::{
+    completion::Completer,
+    validate::{ValidationContext, ValidationResult, Validator},
+    Helper, Highlighter, Hinter,
+};
+use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
+
+/// Line editor support of the REPL: completes globals and properties, and keeps
+/// reading lines while brackets, templates or comments are open.
+#[derive(Helper, Hinter, Highlighter)]
+pub struct ReplHelper {
+    ctx: Rc<RefCell<Context>>,
+    /// Top-level declarations of earlier inputs, `let` and `const` aren't properties of
+    /// the global object.
+    declared: Rc<RefCell<BTreeSet<String>>>,
+}
+
+impl ReplHelper {
+    pub fn new(ctx: Rc<RefCell<Context>>, declared: Rc<RefCell<BTreeSet<String>>>) -> Self {
+        Self { ctx, declared }
+    }
+
+    /// Property names of the value `path` evaluates to, or of the global object.
+    fn property_names(&self, path: Option<&str>) -> Vec<String> {
+        let Ok(mut ctx) = self.ctx.try_borrow_mut() else {
+            return Vec::new();
+        };
+        let ctx = &mut *ctx;
+
+        let value = match path {
+            // Only plain property accesses, completing must not call anything
+            Some(path) if path.split('.').all(is_identifier) => {
+                ctx.eval(Source::from_bytes(path.as_bytes()))
+            }
+            Some(_) => return Vec::new(),
+            None => Ok(JsValue::from(ctx.global_object())),
+        };
+        let Ok(mut object) = value.and_then(|value| value.to_object(ctx)) else {
+            return Vec::new();
+        };
+
+        let mut names = Vec::new();
+        loop {
+            if let Ok(keys) = object.own_property_keys(ctx) {
+                for key in keys {
+                    if let PropertyKey::String(_) = key {
+                        names.push(key.to_string());
+                    }
+                }
+            }
+            match object.prototype() {
+                Some(prototype) => object = prototype,
+                None => break,
+            }
+        }
+        if path.is_none() {
+            names.extend(self.declared.borrow().iter().cloned());
+        }
+
+        names
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let before = &line[..pos];
+        let start = before
+            .char_indices()
+            .rev()
+            .find(|(_, char)| !is_identifier_char(*char) && *char != '.')
+            .map_or(0, |(index, char)| index + char.len_utf8());
+        let word = &before[start..];
+
+        let (path, prefix, prefix_start) = match word.rfind('.') {
+            Some(dot) => (Some(&word[..dot]), &word[dot + 1..], start + dot + 1),
+            None => (None, word, start),
+        };
+
+        let mut candidates: Vec<String> = self
+            .property_names(path)
+            .into_iter()
+            .filter(|name| name.starts_with(prefix) && is_identifier(name))
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+
+        Ok((prefix_start, candidates))
+    }
+}
+
+impl Validator for ReplHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        if is_incomplete(ctx.input()) {
+            Ok(ValidationResult::Incomplete)
+        } else {
+            Ok(ValidationResult::Valid(None))
+        }
+    }
+}
+
+fn is_identifier_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_' || char == '$'
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|char: char| char.is_ascii_digit())
+        && name.chars().all(is_identifier_char)
+}
+
+/// Whether the input ends inside of brackets, a template literal or a block comment.
+/// Unbalanced closing brackets are left for the parser to report.
+pub fn is_incomplete(input: &str) -> bool {
+    // Open brackets, '`' for templates and '$' for their `${` substitutions
+    let mut stack = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        if stack.last() == Some(&'`') {
+            match char {
+                '\\' => {
+                    chars.next();
+                }
+                '`' => {
+                    stack.pop();
+                }
+                '$' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    stack.push('$');
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match char {
+            '"' | '\'' => {
+                // Strings can't span lines, an unterminated one is a syntax error
+                while let Some(next) = chars.next() {
+                    match next {
+                        '\\' => {
+                            chars.next();
+                        }
+                        next if next == char || next == '\n' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = None;
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == Some('*') => break,
+                        Some(next) => previous = Some(next),
+                        None => return true,
+                    }
+                }
+            }
+            '`' | '(' | '[' | '{' => stack.push(char),
+            ')' | ']' | '}' => {
+                if stack.pop().is_none() {
+                    return false;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    !stack.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_incomplete() {
+        assert!(is_incomplete("function f() {"));
+        assert!(is_incomplete("const a = [1,\n2,"));
+        assert!(is_incomplete("`${a}\n"));
+        assert!(is_incomplete("`${fn({"));
+        assert!(is_incomplete("/* comment"));
+
+        assert!(!is_incomplete("function f() {}"));
+        assert!(!is_incomplete("const a = \"{\" // ("));
+        assert!(!is_incomplete("`${a}` + '`'"));
+        assert!(!is_incomplete("})"));
+    }
+}
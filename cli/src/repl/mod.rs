@@ -0,0 +1,166 @@
+mod editor;
+mod rewrite;
+
+use crate::{
+    cache::cache_dir,
+    globals::VERSION,
+    runtime::{
+        console::Console,
+        errors::{print_error, transpile_error},
+        modules::IkeModuleLoader,
+        queue::Queue,
+        runtime::{evaulte_module, load_modules, setup_context},
+    },
+    transpiler::transpile_source,
+};
+use anyhow::Result;
+use boa_engine::{
+    builtins::promise::PromiseState, object::builtins::JsPromise, Context, JsError, JsResult,
+    JsValue, Module, Source,
+};
+use editor::ReplHelper;
+use ike_logger::{log, Logger};
+use rewrite::{rewrite_modules, to_script};
+use rustyline::{error::ReadlineError, history::DefaultHistory, Config, Editor};
+use smol::LocalExecutor;
+use std::{
+    cell::RefCell,
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// Name of the file REPL input is transpiled as, relative to the project root.
+const REPL_FILE: &str = "$repl.ts";
+
+pub struct Repl {
+    ctx: Rc<RefCell<Context>>,
+    /// Path inputs are transpiled and evaluated as.
+    path: PathBuf,
+    declared: Rc<RefCell<BTreeSet<String>>>,
+}
+
+impl Repl {
+    pub fn new(root: &Path) -> JsResult<Self> {
+        let path = root.join(REPL_FILE);
+        let queue = Rc::new(Queue::new(LocalExecutor::new()));
+        let module_loader = Rc::new(IkeModuleLoader::new(root)?);
+        let mut ctx = Context::builder()
+            .job_queue(queue)
+            .module_loader(module_loader.clone())
+            .build()
+            .unwrap();
+
+        load_modules(&mut ctx, module_loader)?;
+        setup_context(&mut ctx, Some(&path));
+
+        let script_source = Source::from_bytes(include_bytes!("../runtime/runtime.js"));
+        let script_module = Module::parse(script_source, None, &mut ctx)?;
+        evaulte_module(&mut ctx, script_module)?;
+
+        Ok(Self {
+            ctx: Rc::new(RefCell::new(ctx)),
+            path,
+            declared: Rc::default(),
+        })
+    }
+
+    /// Reads and evaluates input until `.exit` or Ctrl+D.
+    pub fn run(&self) -> Result<()> {
+        let config = Config::builder().auto_add_history(true).build();
+        let mut editor: Editor<ReplHelper, DefaultHistory> = Editor::with_config(config)?;
+        editor.set_helper(Some(ReplHelper::new(
+            self.ctx.clone(),
+            self.declared.clone(),
+        )));
+
+        let history = cache_dir().join("repl_history");
+        // There is no history before the first session
+        let _ = editor.load_history(&history);
+
+        log!(
+            info,
+            "Welcome to ike {}, type <cyan>.exit<r> or press <cyan>Ctrl+D<r> to leave",
+            VERSION
+        );
+
+        loop {
+            let input = match editor.readline("> ") {
+                Ok(input) => input,
+                // Ctrl+C only discards the current input
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            match input.trim() {
+                "" => {}
+                ".exit" => break,
+                ".help" => {
+                    log!("  <cyan>.exit<r>  Leave the REPL");
+                    log!("  <cyan>.help<r>  Show this help");
+                }
+                _ => self.eval(&input),
+            }
+        }
+
+        std::fs::create_dir_all(cache_dir())?;
+        editor.save_history(&history)?;
+
+        Ok(())
+    }
+
+    /// Evaluates one input and prints its value or the error it threw.
+    fn eval(&self, input: &str) {
+        let ctx = &mut *self.ctx.borrow_mut();
+
+        let source = rewrite_modules(&self.path, input);
+        let transpiled = match transpile_source(&self.path, &source) {
+            Ok(transpiled) => transpiled,
+            Err(err) => {
+                let err = transpile_error(err, ctx).to_opaque(ctx);
+                print_error(&err, "error", 0, ctx);
+                return;
+            }
+        };
+
+        let script = to_script(&transpiled.code);
+        self.declared.borrow_mut().extend(script.declared);
+
+        let source = Source::from_bytes(script.code.as_bytes()).with_path(&self.path);
+        let result = ctx.eval(source).and_then(|value| {
+            ctx.run_jobs();
+            if script.is_async {
+                settle(value)
+            } else {
+                Ok(value)
+            }
+        });
+
+        match result {
+            Ok(value) => {
+                let _ = Console::print_value(&value, ctx);
+            }
+            Err(err) => {
+                let err = err.to_opaque(ctx);
+                print_error(&err, "error", 0, ctx);
+            }
+        }
+    }
+}
+
+/// Value of the promise async input evaluates to, the promise itself if it's still pending.
+fn settle(value: JsValue) -> JsResult<JsValue> {
+    let Some(promise) = value
+        .as_object()
+        .and_then(|object| JsPromise::from_object(object.clone()).ok())
+    else {
+        return Ok(value);
+    };
+
+    match promise.state() {
+        PromiseState::Fulfilled(value) => Ok(value),
+        PromiseState::Rejected(err) => Err(JsError::from_opaque(err)),
+        PromiseState::Pending => Ok(value),
+    }
+}
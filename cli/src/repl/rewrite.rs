@@ -0,0 +1,309 @@
+use crate::bundler::emit::apply_edits;
+use oxc_allocator::Allocator;
+use oxc_ast::{
+    ast::{
+        AwaitExpression, ExportDefaultDeclarationKind, ForOfStatement, FunctionBody,
+        ImportDeclaration, ImportDeclarationSpecifier, Statement, VariableDeclarationKind,
+    },
+    syntax_directed_operations::BoundNames,
+    visit::walk,
+    Visit,
+};
+use oxc_parser::Parser;
+use oxc_span::{GetSpan, SourceType, Span};
+use std::path::Path;
+
+/// Input of the REPL ready to be evaluated as a script.
+#[derive(Debug)]
+pub struct Script {
+    pub code: String,
+    /// The code evaluates to a promise of the result, because it awaits at the top level.
+    pub is_async: bool,
+    /// Names declared at the top level, for completions.
+    pub declared: Vec<String>,
+}
+
+/// Replaces what only modules can do: imports become dynamic imports and `export`
+/// is dropped from declarations. Runs before transpiling, so the typescript transform
+/// doesn't remove imports that aren't used on the same line.
+pub fn rewrite_modules(path: &Path, source: &str) -> String {
+    let allocator = Allocator::default();
+    let source_type = SourceType::from_path(path).unwrap_or_default();
+    let ret = Parser::new(&allocator, source, source_type).parse();
+
+    // The transpiler reports the errors
+    if !ret.errors.is_empty() {
+        return source.to_string();
+    }
+
+    let mut edits = Vec::new();
+    for statement in &ret.program.body {
+        match statement {
+            Statement::ImportDeclaration(decl) if !decl.import_kind.is_type() => {
+                edits.push((decl.span, dynamic_import(decl)));
+            }
+            Statement::ExportNamedDeclaration(decl) => match &decl.declaration {
+                Some(declaration) => {
+                    edits.push((
+                        Span::new(decl.span.start, declaration.span().start),
+                        String::new(),
+                    ));
+                }
+                None => edits.push((decl.span, String::new())),
+            },
+            Statement::ExportDefaultDeclaration(decl) => {
+                let declaration = decl.declaration.span();
+                let anonymous = match &decl.declaration {
+                    ExportDefaultDeclarationKind::FunctionDeclaration(function) => {
+                        function.id.is_none()
+                    }
+                    ExportDefaultDeclarationKind::ClassDeclaration(class) => class.id.is_none(),
+                    _ => false,
+                };
+
+                // Statements can't start with an anonymous function or class
+                if anonymous {
+                    edits.push((decl.span, format!("({})", text(source, declaration))));
+                } else {
+                    edits.push((Span::new(decl.span.start, declaration.start), String::new()));
+                }
+            }
+            Statement::ExportAllDeclaration(decl) => edits.push((decl.span, String::new())),
+            _ => {}
+        }
+    }
+
+    apply_edits(source, edits)
+}
+
+/// Turns transpiled input into a script. Input with top-level await runs in an async
+/// function, its declarations are hoisted out of it so later inputs still see them.
+pub fn to_script(code: &str) -> Script {
+    let allocator = Allocator::default();
+    let source_type = SourceType::from_path(Path::new("repl.mjs")).unwrap();
+    let ret = Parser::new(&allocator, code, source_type).parse();
+    let program = ret.program;
+
+    let mut finder = AwaitFinder::default();
+    finder.visit_program(&program);
+    let is_async = finder.found;
+
+    let is_module_syntax = |statement: &Statement| {
+        matches!(
+            statement,
+            Statement::ImportDeclaration(_)
+                | Statement::ExportAllDeclaration(_)
+                | Statement::ExportDefaultDeclaration(_)
+                | Statement::ExportNamedDeclaration(_)
+        )
+    };
+    let last = program
+        .body
+        .iter()
+        .rposition(|statement| !is_module_syntax(statement));
+
+    let mut declared = Vec::new();
+    let mut lexical = Vec::new();
+    let mut vars = Vec::new();
+    let mut edits = Vec::new();
+
+    for (index, statement) in program.body.iter().enumerate() {
+        match statement {
+            // Like the `export {}` the typescript transform leaves behind
+            statement if is_module_syntax(statement) => {
+                edits.push((statement.span(), String::new()));
+            }
+            Statement::VariableDeclaration(decl) => {
+                let mut names = Vec::new();
+                for declarator in &decl.declarations {
+                    declarator
+                        .id
+                        .bound_names(&mut |ident| names.push(ident.name.to_string()));
+                }
+                declared.extend(names.iter().cloned());
+                if !is_async {
+                    continue;
+                }
+
+                if decl.kind == VariableDeclarationKind::Var {
+                    vars.extend(names);
+                } else {
+                    lexical.extend(names);
+                }
+                let assignments: Vec<String> = decl
+                    .declarations
+                    .iter()
+                    .filter_map(|declarator| {
+                        let init = declarator.init.as_ref()?;
+                        Some(format!(
+                            "({} = {})",
+                            text(code, declarator.id.span()),
+                            text(code, init.span())
+                        ))
+                    })
+                    .collect();
+                let replacement = if assignments.is_empty() {
+                    String::new()
+                } else {
+                    format!("{};", assignments.join(", "))
+                };
+                edits.push((decl.span, replacement));
+            }
+            Statement::FunctionDeclaration(function) => {
+                let Some(id) = &function.id else {
+                    continue;
+                };
+                declared.push(id.name.to_string());
+                if is_async {
+                    vars.push(id.name.to_string());
+                    let replacement = format!("{} = {};", id.name, text(code, function.span));
+                    edits.push((function.span, replacement));
+                }
+            }
+            Statement::ClassDeclaration(class) => {
+                let Some(id) = &class.id else {
+                    continue;
+                };
+                declared.push(id.name.to_string());
+                if is_async {
+                    lexical.push(id.name.to_string());
+                    let replacement = format!("{} = {};", id.name, text(code, class.span));
+                    edits.push((class.span, replacement));
+                }
+            }
+            // The value of the input is the one of its last expression
+            Statement::ExpressionStatement(stmt) if is_async && Some(index) == last => {
+                let replacement = format!("return ({});", text(code, stmt.expression.span()));
+                edits.push((stmt.span, replacement));
+            }
+            _ => {}
+        }
+    }
+
+    let body = apply_edits(code, edits);
+    if !is_async {
+        return Script {
+            code: body,
+            is_async,
+            declared,
+        };
+    }
+
+    let mut script = String::new();
+    if !lexical.is_empty() {
+        script.push_str(&format!("let {};\n", lexical.join(", ")));
+    }
+    if !vars.is_empty() {
+        script.push_str(&format!("var {};\n", vars.join(", ")));
+    }
+    script.push_str(&format!("(async () => {{\n{}\n}})()", body.trim_end()));
+
+    Script {
+        code: script,
+        is_async,
+        declared,
+    }
+}
+
+fn text(code: &str, span: Span) -> &str {
+    &code[span.start as usize..span.end as usize]
+}
+
+/// `import { a as b } from "m"` to `const { "a": b } = await import("m")`.
+fn dynamic_import(decl: &ImportDeclaration) -> String {
+    let specifier = serde_json::to_string(decl.source.value.as_str()).unwrap();
+    let import = format!("await import({specifier})");
+    let Some(specifiers) = &decl.specifiers else {
+        return format!("{import};");
+    };
+
+    let mut namespace = None;
+    let mut properties = Vec::new();
+    for specifier in specifiers {
+        match specifier {
+            ImportDeclarationSpecifier::ImportSpecifier(specifier)
+                if !specifier.import_kind.is_type() =>
+            {
+                let imported = serde_json::to_string(specifier.imported.name().as_str()).unwrap();
+                properties.push(format!("{imported}: {}", specifier.local.name));
+            }
+            ImportDeclarationSpecifier::ImportDefaultSpecifier(specifier) => {
+                properties.push(format!("default: {}", specifier.local.name));
+            }
+            ImportDeclarationSpecifier::ImportNamespaceSpecifier(specifier) => {
+                namespace = Some(specifier.local.name.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    match namespace {
+        Some(namespace) if properties.is_empty() => format!("const {namespace} = {import};"),
+        Some(namespace) => format!(
+            "const {namespace} = {import};\nconst {{ {} }} = {namespace};",
+            properties.join(", ")
+        ),
+        None => format!("const {{ {} }} = {import};", properties.join(", ")),
+    }
+}
+
+/// Finds awaits outside of functions.
+#[derive(Default)]
+struct AwaitFinder {
+    found: bool,
+}
+
+impl<'a> Visit<'a> for AwaitFinder {
+    fn visit_await_expression(&mut self, _: &AwaitExpression<'a>) {
+        self.found = true;
+    }
+
+    fn visit_for_of_statement(&mut self, stmt: &ForOfStatement<'a>) {
+        self.found |= stmt.r#await;
+        walk::walk_for_of_statement(self, stmt);
+    }
+
+    // Awaits in functions belong to them
+    fn visit_function_body(&mut self, _: &FunctionBody<'a>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_modules() {
+        let path = Path::new("repl.ts");
+
+        assert_eq!(
+            rewrite_modules(path, "import a, { b as c, type D } from \"./m.ts\";"),
+            "const { default: a, \"b\": c } = await import(\"./m.ts\");"
+        );
+        assert_eq!(
+            rewrite_modules(path, "import * as fs from \"fs\";"),
+            "const fs = await import(\"fs\");"
+        );
+        assert_eq!(
+            rewrite_modules(path, "export const a = 1;\nexport default function () {}"),
+            "const a = 1;\n(function () {})"
+        );
+    }
+
+    #[test]
+    fn test_to_script() {
+        let script = to_script("const a = 1;\na + 1;\n");
+        assert!(!script.is_async);
+        assert_eq!(script.code, "const a = 1;\na + 1;\n");
+        assert_eq!(script.declared, vec!["a"]);
+
+        let script =
+            to_script("const { a, b: c } = await load();\nfunction f() {}\nlet d;\nf(await a);\n");
+        assert!(script.is_async);
+        assert_eq!(
+            script.code,
+            "let a, c, d;\nvar f;\n(async () => {\n({ a, b: c } = await load());\nf = function f() {};\n\nreturn (f(await a));\n})()"
+        );
+
+        assert!(!to_script("async function f() { await g(); }").is_async);
+    }
+}
@@ -0,0 +1,449 @@
+use crate::install::{
+    lockfile::{Lockfile, LOCKFILE_NAME},
+    store::key_from_integrity,
+};
+use ike_fs::find_nearest_file;
+use ike_toml::{Dependency, IkeToml, Workspace};
+use oxc_resolver::{EnforceExtension, ResolveOptions, Resolver};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+};
+use thiserror::Error;
+
+/// Directory installed dependencies are linked into, next to the project's ike.toml.
+pub const MODULES_DIR: &str = "ike_modules";
+
+const EXTENSIONS: [&str; 6] = ["ts", "mts", "js", "mjs", "cts", "cjs"];
+
+#[derive(Debug, Error)]
+pub enum ResolveError {
+    #[error("Cannot find module \"{0}\"")]
+    ModuleNotFound(String),
+    #[error("Package \"{0}\" is not listed in the dependencies of {1}")]
+    DependencyNotFound(String, PathBuf),
+    #[error("Dependency \"{0}\" is not installed, run ike install")]
+    DependencyNotInstalled(String),
+    #[error("Package \"{0}\" does not export \"{1}\"")]
+    SubpathNotExported(String, String),
+    #[error("No workspace member is named \"{0}\"")]
+    WorkspaceMemberNotFound(String),
+    #[error("Dependency \"{0}\" does not match ike.lock, run ike install")]
+    LockMismatch(String),
+    #[error("Failed to read manifest {0}: {1}")]
+    InvalidManifest(PathBuf, String),
+    #[error("{0}")]
+    Node(String),
+}
+
+/// Resolves import specifiers the way ike.toml describes packages.
+///
+/// Relative and absolute specifiers are resolved against the importing file, bare
+/// specifiers are looked up in the `dependencies`/`dev-dependencies` of the nearest
+/// ike.toml and mapped through the package `exports`. Packages that only ship a
+/// package.json are resolved node-style. Workspace members resolve each other by name
+/// straight from their directories.
+#[derive(Debug, Default)]
+pub struct IkeResolver {
+    manifests: RefCell<HashMap<PathBuf, Option<IkeToml>>>,
+    lockfiles: RefCell<HashMap<PathBuf, Option<Lockfile>>>,
+    workspaces: RefCell<HashMap<PathBuf, Workspace>>,
+}
+
+impl IkeResolver {
+    pub fn resolve(&self, referrer_dir: &Path, specifier: &str) -> Result<PathBuf, ResolveError> {
+        if is_relative_specifier(specifier) {
+            let path = normalize(&referrer_dir.join(specifier));
+
+            return resolve_file(&path)
+                .ok_or_else(|| ResolveError::ModuleNotFound(specifier.to_string()));
+        }
+
+        let (name, subpath) = split_bare_specifier(specifier);
+        let Some(manifest) = self.nearest_manifest(referrer_dir)? else {
+            return resolve_node(referrer_dir, specifier);
+        };
+        let manifest_path = manifest.file_path.clone().unwrap_or_default();
+        let manifest_dir = manifest_path
+            .parent()
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        // Members of a workspace share the ike_modules and ike.lock of the root, and
+        // installed packages the ones of the project they are installed in
+        let install_dir = match installing_project(&manifest_dir) {
+            Some(project_dir) => project_dir,
+            None => manifest
+                .workspace_root
+                .as_deref()
+                .and_then(Path::parent)
+                .unwrap_or(&manifest_dir)
+                .to_path_buf(),
+        };
+
+        // Packages can import their own exports by name
+        if manifest.toml.package.name == name {
+            return self.resolve_package(&manifest_dir, name, subpath);
+        }
+
+        let dependency = manifest
+            .toml
+            .dependencies
+            .get(name)
+            .or_else(|| manifest.toml.dev_dependencies.get(name));
+
+        match dependency {
+            Some(dependency) => {
+                if dependency.path.is_none() && dependency.git.is_none() {
+                    if let Some(member_dir) = self.workspace_member(&manifest, name)? {
+                        return self.resolve_package(&member_dir, name, subpath);
+                    }
+                    if dependency.workspace {
+                        return Err(ResolveError::WorkspaceMemberNotFound(name.to_string()));
+                    }
+                }
+
+                let package_dir = package_dir(&manifest_dir, &install_dir, name, dependency);
+                if !package_dir.exists() {
+                    return Err(ResolveError::DependencyNotInstalled(name.to_string()));
+                }
+                if dependency.path.is_none() {
+                    self.verify_locked(&install_dir, name, &package_dir)?;
+                }
+
+                self.resolve_package(&package_dir, name, subpath)
+            }
+            None => {
+                let installed = install_dir.join(MODULES_DIR).join(name);
+                if installed.exists() {
+                    self.verify_locked(&install_dir, name, &installed)?;
+                    return self.resolve_package(&installed, name, subpath);
+                }
+
+                resolve_node(referrer_dir, specifier)
+                    .map_err(|_| ResolveError::DependencyNotFound(name.to_string(), manifest_path))
+            }
+        }
+    }
+
+    fn resolve_package(
+        &self,
+        package_dir: &Path,
+        name: &str,
+        subpath: Option<&str>,
+    ) -> Result<PathBuf, ResolveError> {
+        let manifest = match self.manifest(&package_dir.join("ike.toml"))? {
+            Some(manifest) => manifest,
+            None if package_dir.join("package.json").exists() => {
+                let specifier = match subpath {
+                    Some(subpath) => format!("./{subpath}"),
+                    None => ".".to_string(),
+                };
+
+                return resolve_node(package_dir, &specifier);
+            }
+            None => {
+                let path = match subpath {
+                    Some(subpath) => package_dir.join(subpath),
+                    None => package_dir.to_path_buf(),
+                };
+
+                return resolve_file(&path)
+                    .ok_or_else(|| ResolveError::ModuleNotFound(join_specifier(name, subpath)));
+            }
+        };
+
+        let package = &manifest.toml.package;
+        let target = match export_targets(&manifest) {
+            Some(exports) => match (exports.get(subpath.unwrap_or(".")), subpath) {
+                (Some(import), _) => Some(import.clone()),
+                (None, None) => package.main.clone(),
+                (None, Some(subpath)) => {
+                    return Err(ResolveError::SubpathNotExported(
+                        name.to_string(),
+                        subpath.to_string(),
+                    ))
+                }
+            },
+            None => match subpath {
+                Some(subpath) => Some(subpath.to_string()),
+                None => package.main.clone(),
+            },
+        };
+
+        let path = match target {
+            Some(target) => normalize(&package_dir.join(target)),
+            None => package_dir.to_path_buf(),
+        };
+
+        resolve_file(&path)
+            .ok_or_else(|| ResolveError::ModuleNotFound(join_specifier(name, subpath)))
+    }
+
+    /// Makes sure an installed package is the one recorded in ike.lock.
+    fn verify_locked(
+        &self,
+        install_dir: &Path,
+        name: &str,
+        package_dir: &Path,
+    ) -> Result<(), ResolveError> {
+        let lockfile_path = install_dir.join(LOCKFILE_NAME);
+        if !self.lockfiles.borrow().contains_key(&lockfile_path) {
+            let lockfile = Lockfile::read(&lockfile_path)
+                .map_err(|e| ResolveError::InvalidManifest(lockfile_path.clone(), e.to_string()))?;
+            self.lockfiles
+                .borrow_mut()
+                .insert(lockfile_path.clone(), lockfile);
+        }
+
+        let lockfiles = self.lockfiles.borrow();
+        let Some(lockfile) = lockfiles[&lockfile_path].as_ref() else {
+            return Ok(());
+        };
+        let Some(locked) = lockfile.get(name) else {
+            return Err(ResolveError::LockMismatch(name.to_string()));
+        };
+
+        // Only symlinked packages point into the cache, copies can't be checked this way.
+        // Registry packages are stored by tarball hash and git checkouts by commit.
+        let is_link = std::fs::symlink_metadata(package_dir)
+            .is_ok_and(|metadata| metadata.file_type().is_symlink());
+        let expected = match (&locked.integrity, &locked.rev) {
+            (Some(integrity), _) => key_from_integrity(integrity),
+            (None, Some(rev)) => Some(rev.clone()),
+            (None, None) => return Ok(()),
+        };
+
+        if is_link {
+            let actual = package_dir
+                .canonicalize()
+                .ok()
+                .and_then(|dir| Some(dir.file_name()?.to_string_lossy().to_string()));
+
+            if expected.is_none() || expected != actual {
+                return Err(ResolveError::LockMismatch(name.to_string()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Directory of the workspace member called `name`, if the manifest is in a workspace.
+    fn workspace_member(
+        &self,
+        manifest: &IkeToml,
+        name: &str,
+    ) -> Result<Option<PathBuf>, ResolveError> {
+        let Some(root_path) = manifest.workspace_root.clone() else {
+            return Ok(None);
+        };
+
+        if !self.workspaces.borrow().contains_key(&root_path) {
+            let workspace = Workspace::load(root_path.clone())
+                .map_err(|e| ResolveError::InvalidManifest(root_path.clone(), e.to_string()))?;
+            self.workspaces
+                .borrow_mut()
+                .insert(root_path.clone(), workspace);
+        }
+
+        Ok(self.workspaces.borrow()[&root_path]
+            .member(name)
+            .and_then(|member| member.dir())
+            .map(PathBuf::from))
+    }
+
+    fn nearest_manifest(&self, dir: &Path) -> Result<Option<IkeToml>, ResolveError> {
+        match find_nearest_file(dir.to_path_buf(), "ike.toml") {
+            Some(path) => self.manifest(&path),
+            None => Ok(None),
+        }
+    }
+
+    fn manifest(&self, path: &Path) -> Result<Option<IkeToml>, ResolveError> {
+        if let Some(manifest) = self.manifests.borrow().get(path) {
+            return Ok(manifest.clone());
+        }
+
+        let manifest = if path.is_file() {
+            let mut manifest = IkeToml::from_file(path.to_path_buf())
+                .map_err(|e| ResolveError::InvalidManifest(path.to_path_buf(), e.to_string()))?;
+            manifest.workspace_root = manifest.find_workspace_root();
+            Some(manifest)
+        } else {
+            None
+        };
+
+        self.manifests
+            .borrow_mut()
+            .insert(path.to_path_buf(), manifest.clone());
+
+        Ok(manifest)
+    }
+}
+
+/// Location of a dependency on disk. Path dependencies are relative to the manifest,
+/// everything else is expected to be installed into the `ike_modules` of `install_dir`.
+pub fn package_dir(
+    manifest_dir: &Path,
+    install_dir: &Path,
+    name: &str,
+    dependency: &Dependency,
+) -> PathBuf {
+    match &dependency.path {
+        Some(path) => normalize(&manifest_dir.join(path)),
+        None => install_dir.join(MODULES_DIR).join(name),
+    }
+}
+
+/// Directory of the project whose `ike_modules` a path is in. Transitive dependencies
+/// are hoisted, so packages import them from there.
+fn installing_project(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.file_name().is_some_and(|name| name == MODULES_DIR))
+        .and_then(Path::parent)
+        .map(Path::to_path_buf)
+}
+
+/// Maps export keys (without a leading `./`) to the file they import. The top level
+/// `[exports]` table takes precedence over `package.exports`.
+fn export_targets(manifest: &IkeToml) -> Option<HashMap<String, String>> {
+    let key = |key: &str| match key.strip_prefix("./") {
+        Some(stripped) if !stripped.is_empty() => stripped.to_string(),
+        _ => key.to_string(),
+    };
+
+    match (&manifest.toml.exports, &manifest.toml.package.exports) {
+        (Some(exports), _) => Some(
+            exports
+                .iter()
+                .map(|(name, export)| (key(name), export.import.clone()))
+                .collect(),
+        ),
+        (None, Some(exports)) => Some(
+            exports
+                .iter()
+                .filter_map(|(name, export)| Some((key(name), export.get("import")?.clone())))
+                .collect(),
+        ),
+        (None, None) => None,
+    }
+}
+
+fn is_relative_specifier(specifier: &str) -> bool {
+    specifier.starts_with("./")
+        || specifier.starts_with("../")
+        || specifier == "."
+        || specifier == ".."
+        || Path::new(specifier).is_absolute()
+}
+
+/// Splits `@scope/name/sub/path` into `@scope/name` and `sub/path`.
+fn split_bare_specifier(specifier: &str) -> (&str, Option<&str>) {
+    let separator = if specifier.starts_with('@') {
+        specifier.match_indices('/').nth(1).map(|(index, _)| index)
+    } else {
+        specifier.find('/')
+    };
+
+    match separator {
+        Some(index) => (&specifier[..index], Some(&specifier[index + 1..])),
+        None => (specifier, None),
+    }
+}
+
+fn join_specifier(name: &str, subpath: Option<&str>) -> String {
+    match subpath {
+        Some(subpath) => format!("{name}/{subpath}"),
+        None => name.to_string(),
+    }
+}
+
+/// Tries the path as is, with each known extension and as a directory with an index file.
+fn resolve_file(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+
+    for extension in EXTENSIONS {
+        let mut file = path.as_os_str().to_owned();
+        file.push(".");
+        file.push(extension);
+
+        let file = PathBuf::from(file);
+        if file.is_file() {
+            return Some(file);
+        }
+    }
+
+    if path.is_dir() {
+        return EXTENSIONS
+            .iter()
+            .map(|extension| path.join(format!("index.{extension}")))
+            .find(|file| file.is_file());
+    }
+
+    None
+}
+
+fn resolve_node(dir: &Path, specifier: &str) -> Result<PathBuf, ResolveError> {
+    let options = ResolveOptions {
+        enforce_extension: EnforceExtension::Disabled,
+        condition_names: vec!["node".into(), "import".into()],
+        extensions: EXTENSIONS
+            .iter()
+            .map(|extension| format!(".{extension}"))
+            .collect(),
+        // Keep paths of installed packages inside ike_modules rather than the store, so
+        // their own imports are resolved against the project
+        symlinks: false,
+        ..ResolveOptions::default()
+    };
+
+    Resolver::new(options)
+        .resolve(dir, specifier)
+        .map(|resolution| resolution.full_path())
+        .map_err(|e| ResolveError::Node(e.to_string()))
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component.as_os_str()),
+        }
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_bare_specifier() {
+        assert_eq!(split_bare_specifier("yaml"), ("yaml", None));
+        assert_eq!(
+            split_bare_specifier("yaml/parse/doc"),
+            ("yaml", Some("parse/doc"))
+        );
+        assert_eq!(split_bare_specifier("@ike/utils"), ("@ike/utils", None));
+        assert_eq!(
+            split_bare_specifier("@ike/utils/strings"),
+            ("@ike/utils", Some("strings"))
+        );
+    }
+
+    #[test]
+    fn test_is_relative_specifier() {
+        assert!(is_relative_specifier("./mod.ts"));
+        assert!(is_relative_specifier("../mod.ts"));
+        assert!(!is_relative_specifier("yaml"));
+        assert!(!is_relative_specifier("@ike/utils"));
+    }
+}
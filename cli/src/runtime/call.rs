@@ -4,6 +4,8 @@ use crate::{
     assert_arg_type,
     runtime::{
         buffer::is_utf8,
+        commonjs::require_file_fn,
+        toml::parse_toml,
         uuid::{uuid_parse, uuid_stringify, uuidv4, uuidv5},
     },
 };
@@ -45,6 +47,12 @@ pub fn rust_function(
         });
         m.insert("uuidv4", unsafe { NativeFunction::from_closure(uuidv4) });
         m.insert("uuidv5", unsafe { NativeFunction::from_closure(uuidv5) });
+        m.insert("requireFile", unsafe {
+            NativeFunction::from_closure(require_file_fn)
+        });
+        m.insert("parseToml", unsafe {
+            NativeFunction::from_closure(parse_toml)
+        });
         m
     };
     if args.is_empty() {
@@ -0,0 +1,699 @@
+use crate::{
+    fs::read_json,
+    import_map::{import_map, ImportMap},
+    install::store::PackageStore,
+    resolver::IkeResolver,
+    runtime::{
+        errors::transpile_error,
+        modules::{builtin_module_source, is_builtin_module},
+        source_map::SourceMaps,
+    },
+    transpiler::transpile_file,
+};
+use boa_engine::{
+    builtins::promise::PromiseState, js_string, object::FunctionObjectBuilder,
+    object::ObjectInitializer, property::Attribute, Context, JsData, JsError, JsNativeError,
+    JsObject, JsResult, JsString, JsValue, Module, NativeFunction, Source,
+};
+use boa_gc::{Finalize, Trace};
+use ike_core::{str_from_jsvalue, throw};
+use ike_fs::{find_nearest_file, read_to_string};
+use oxc_allocator::Allocator;
+use oxc_ast::{
+    ast::{
+        Argument, AssignmentTarget, AwaitExpression, Expression, ForOfStatement, FunctionBody,
+        IdentifierReference, MetaProperty, ObjectPropertyKind, Statement,
+    },
+    visit::walk,
+    Visit,
+};
+use oxc_parser::Parser;
+use oxc_span::SourceType;
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+/// Words that can't be used as the name of an `export const`.
+const RESERVED_WORDS: [&str; 46] = [
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// State shared by every `require` of a context.
+#[derive(Trace, Finalize, JsData)]
+pub struct CommonJs {
+    /// Module objects by filename, exposed as `require.cache`.
+    cache: JsObject,
+    #[unsafe_ignore_trace]
+    resolver: IkeResolver,
+    #[unsafe_ignore_trace]
+    import_map: ImportMap,
+    #[unsafe_ignore_trace]
+    store: PackageStore,
+}
+
+enum Resolved {
+    File(PathBuf),
+    Builtin(String),
+}
+
+impl CommonJs {
+    pub fn init(ctx: &mut Context) {
+        let state = Self {
+            cache: JsObject::with_null_proto(),
+            resolver: IkeResolver::default(),
+            import_map: import_map(),
+            store: PackageStore::default(),
+        };
+
+        ctx.realm().host_defined_mut().insert(state);
+    }
+
+    fn cache(ctx: &Context) -> JsResult<JsObject> {
+        match ctx.realm().host_defined().get::<Self>() {
+            Some(state) => Ok(state.cache.clone()),
+            None => throw!(err, "CommonJS support is not initialized"),
+        }
+    }
+
+    /// Resolves a specifier the way `require` does from the module at `filename`.
+    fn resolve(specifier: &str, filename: &Path, ctx: &Context) -> JsResult<Resolved> {
+        let realm = ctx.realm().clone();
+        let host_defined = realm.host_defined();
+        let Some(state) = host_defined.get::<Self>() else {
+            throw!(err, "CommonJS support is not initialized");
+        };
+
+        let specifier = state
+            .import_map
+            .resolve(specifier, filename)
+            .unwrap_or_else(|| specifier.to_string());
+        if is_builtin_module(&specifier) || specifier.starts_with("module:") {
+            return Ok(Resolved::Builtin(specifier));
+        }
+
+        let dir = filename.parent().unwrap_or(filename);
+        let file = match state.resolver.resolve(dir, &specifier) {
+            Ok(file) => file,
+            Err(err) => throw!(
+                typ,
+                "Cannot find module \"{}\" from {}: {}",
+                specifier,
+                filename.display(),
+                err
+            ),
+        };
+
+        if state.store.verify_file(&file).is_err() {
+            throw!(
+                err,
+                "Integrity check failed: {} was modified after it was installed, run `ike install` to restore it",
+                file.display()
+            );
+        }
+
+        Ok(Resolved::File(file))
+    }
+}
+
+/// Whether a file is CommonJS. `.cjs`/`.cts` always are and other TypeScript files never
+/// are. For `.js`/`.jsx` the `type` of the nearest package.json decides, without one the
+/// syntax of the file does.
+pub fn is_commonjs(path: &Path) -> bool {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("cjs" | "cts") => return true,
+        Some("js" | "jsx") => {}
+        _ => return false,
+    }
+
+    match package_type(path).as_deref() {
+        Some("commonjs") => true,
+        Some("module") => false,
+        _ => read_to_string(path).is_ok_and(|source| looks_like_commonjs(path, &source)),
+    }
+}
+
+fn package_type(path: &Path) -> Option<String> {
+    let package_json = find_nearest_file(path.parent()?.to_path_buf(), "package.json")?;
+    let json: serde_json::Value = read_json(package_json).ok()?;
+
+    json["type"].as_str().map(String::from)
+}
+
+/// A file is CommonJS when it references `require`, `module` or `exports` and uses
+/// nothing only modules can: import or export declarations, top-level await or
+/// `import.meta`.
+fn looks_like_commonjs(path: &Path, source: &str) -> bool {
+    if !source.contains("require") && !source.contains("exports") {
+        return false;
+    }
+
+    let allocator = Allocator::default();
+    let source_type = SourceType::from_path(path).unwrap_or_default();
+    let ret = Parser::new(&allocator, source, source_type).parse();
+
+    let has_module_declarations = ret.program.body.iter().any(|statement| {
+        matches!(
+            statement,
+            Statement::ImportDeclaration(_)
+                | Statement::ExportAllDeclaration(_)
+                | Statement::ExportDefaultDeclaration(_)
+                | Statement::ExportNamedDeclaration(_)
+        )
+    });
+    if has_module_declarations {
+        return false;
+    }
+
+    let mut finder = ModuleSyntaxFinder::default();
+    finder.visit_program(&ret.program);
+
+    finder.uses_commonjs && !finder.uses_esm
+}
+
+/// Looks for the CommonJS globals and the syntax only modules have.
+#[derive(Default)]
+struct ModuleSyntaxFinder {
+    uses_commonjs: bool,
+    uses_esm: bool,
+    /// Number of functions the visitor is in, awaits in them aren't top-level.
+    function_depth: usize,
+}
+
+impl<'a> Visit<'a> for ModuleSyntaxFinder {
+    fn visit_identifier_reference(&mut self, ident: &IdentifierReference<'a>) {
+        if matches!(ident.name.as_str(), "require" | "module" | "exports") {
+            self.uses_commonjs = true;
+        }
+    }
+
+    fn visit_meta_property(&mut self, meta: &MetaProperty<'a>) {
+        if meta.meta.name == "import" {
+            self.uses_esm = true;
+        }
+    }
+
+    fn visit_await_expression(&mut self, expr: &AwaitExpression<'a>) {
+        self.uses_esm |= self.function_depth == 0;
+        walk::walk_await_expression(self, expr);
+    }
+
+    fn visit_for_of_statement(&mut self, stmt: &ForOfStatement<'a>) {
+        self.uses_esm |= stmt.r#await && self.function_depth == 0;
+        walk::walk_for_of_statement(self, stmt);
+    }
+
+    fn visit_function_body(&mut self, body: &FunctionBody<'a>) {
+        self.function_depth += 1;
+        walk::walk_function_body(self, body);
+        self.function_depth -= 1;
+    }
+}
+
+/// Finds the names a CommonJS module exports without running it, by looking at the
+/// top level assignments to `exports`/`module.exports`.
+pub fn export_names(path: &Path, source: &str) -> BTreeSet<String> {
+    let allocator = Allocator::default();
+    let source_type = SourceType::from_path(path).unwrap_or_default();
+    let ret = Parser::new(&allocator, source, source_type).parse();
+    let mut names = BTreeSet::new();
+
+    for statement in &ret.program.body {
+        let Statement::ExpressionStatement(statement) = statement else {
+            continue;
+        };
+
+        match &statement.expression {
+            // exports.name = ..., module.exports.name = ..., module.exports = { name }
+            Expression::AssignmentExpression(assignment) => match &assignment.left {
+                AssignmentTarget::StaticMemberExpression(member) => {
+                    if is_exports(&member.object) {
+                        names.insert(member.property.name.to_string());
+                    } else if is_module_exports(&member.object, &member.property.name) {
+                        if let Expression::ObjectExpression(object) = &assignment.right {
+                            for property in &object.properties {
+                                if let ObjectPropertyKind::ObjectProperty(property) = property {
+                                    if let Some(name) = property.key.static_name() {
+                                        names.insert(name.to_string());
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                AssignmentTarget::ComputedMemberExpression(member) => {
+                    if let Expression::StringLiteral(name) = &member.expression {
+                        if is_exports(&member.object) {
+                            names.insert(name.value.to_string());
+                        }
+                    }
+                }
+                _ => {}
+            },
+            // Object.defineProperty(exports, "name", ...)
+            Expression::CallExpression(call) => {
+                let Expression::StaticMemberExpression(callee) = &call.callee else {
+                    continue;
+                };
+                let is_define_property = matches!(&callee.object, Expression::Identifier(object) if object.name == "Object")
+                    && callee.property.name == "defineProperty";
+
+                if let (true, Some(Argument::StringLiteral(name))) =
+                    (is_define_property, call.arguments.get(1))
+                {
+                    let target = call.arguments.first().and_then(Argument::as_expression);
+                    if target.is_some_and(is_exports) {
+                        names.insert(name.value.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    names.retain(|name| is_exportable(name));
+    names
+}
+
+fn is_exports(expression: &Expression) -> bool {
+    match expression {
+        Expression::Identifier(identifier) => identifier.name == "exports",
+        Expression::StaticMemberExpression(member) => {
+            is_module_exports(&member.object, &member.property.name)
+        }
+        _ => false,
+    }
+}
+
+fn is_module_exports(object: &Expression, property: &str) -> bool {
+    matches!(object, Expression::Identifier(identifier) if identifier.name == "module")
+        && property == "exports"
+}
+
+fn is_exportable(name: &str) -> bool {
+    let mut chars = name.chars();
+    let valid_start = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$');
+
+    valid_start
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && !RESERVED_WORDS.contains(&name)
+        && name != "__esModule"
+}
+
+/// ES module that exposes a CommonJS module to `import`. `module.exports` is the default
+/// export and every detected name is also a named export.
+pub fn esm_wrapper(path: &Path) -> String {
+    let names = read_to_string(path)
+        .map(|source| export_names(path, &source))
+        .unwrap_or_default();
+    let filename = serde_json::to_string(&path.to_string_lossy()).unwrap_or_default();
+
+    let mut wrapper = format!(
+        "const __cjs_exports = $rustFunction(\"requireFile\")({filename});\nexport default __cjs_exports;\n"
+    );
+    for name in names {
+        wrapper.push_str(&format!(
+            "export const {name} = __cjs_exports[\"{name}\"];\n"
+        ));
+    }
+
+    wrapper
+}
+
+/// `$rustFunction("requireFile")(path)`, used by the ESM wrapper of CommonJS modules.
+pub fn require_file_fn(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let Some(path) = args.first() else {
+        throw!(typ, "Expected a path in requireFile");
+    };
+    let path = str_from_jsvalue!(path, ctx);
+
+    require_file(Path::new(&path), ctx)
+}
+
+/// Loads a module with CommonJS semantics and returns its `module.exports`.
+///
+/// Modules are cached by filename before they run, so a module that's required again
+/// while it's still loading (a cycle) gets its exports as they are at that point.
+pub fn require_file(path: &Path, ctx: &mut Context) -> JsResult<JsValue> {
+    let cache = CommonJs::cache(ctx)?;
+    let key = js_string!(path.to_string_lossy().to_string());
+
+    if cache.has_own_property(key.clone(), ctx)? {
+        let module = cache.get(key, ctx)?;
+        return module
+            .as_object()
+            .map(|module| module.get(js_string!("exports"), ctx))
+            .unwrap_or(Ok(JsValue::undefined()));
+    }
+
+    let module = new_module(&path.to_string_lossy(), ctx);
+    cache.set(key.clone(), module.clone(), false, ctx)?;
+
+    let result = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => load_json(path, &module, ctx),
+        _ if is_commonjs(path) => load_commonjs(path, &module, ctx),
+        _ => load_esm(path, &module, ctx),
+    };
+
+    // Failed modules are not cached, requiring them again runs them again
+    if let Err(err) = result {
+        cache.delete_property_or_throw(key, ctx)?;
+        return Err(err);
+    }
+
+    module.set(js_string!("loaded"), true, false, ctx)?;
+    module.get(js_string!("exports"), ctx)
+}
+
+fn require_builtin(specifier: &str, ctx: &mut Context) -> JsResult<JsValue> {
+    let cache = CommonJs::cache(ctx)?;
+    let key = js_string!(specifier);
+
+    if let Some(module) = cache.get(key.clone(), ctx)?.as_object() {
+        return module.get(js_string!("exports"), ctx);
+    }
+
+    let loader = ctx.module_loader();
+    let esm = match builtin_module_source(specifier) {
+        Some(source) => Module::parse(Source::from_bytes(source.as_bytes()), None, ctx)?,
+        None => match loader.get_module(key.clone()) {
+            Some(module) => module,
+            None => throw!(typ, "Cannot find module \"{}\"", specifier),
+        },
+    };
+
+    let module = new_module(specifier, ctx);
+    let namespace = evaluate_sync(&esm, specifier, ctx)?;
+    module.set(js_string!("exports"), namespace, false, ctx)?;
+    module.set(js_string!("loaded"), true, false, ctx)?;
+    cache.set(key, module.clone(), false, ctx)?;
+
+    module.get(js_string!("exports"), ctx)
+}
+
+fn new_module(filename: &str, ctx: &mut Context) -> JsObject {
+    let exports = JsObject::with_object_proto(ctx.intrinsics());
+
+    ObjectInitializer::new(ctx)
+        .property(js_string!("id"), js_string!(filename), Attribute::all())
+        .property(
+            js_string!("filename"),
+            js_string!(filename),
+            Attribute::all(),
+        )
+        .property(js_string!("loaded"), false, Attribute::all())
+        .property(js_string!("exports"), exports, Attribute::all())
+        .build()
+}
+
+/// Creates the `require` function of the module at `filename`.
+fn create_require(filename: &Path, ctx: &mut Context) -> JsResult<JsObject> {
+    let filename = js_string!(filename.to_string_lossy().to_string());
+
+    let require = NativeFunction::from_copy_closure_with_captures(
+        |_: &JsValue, args: &[JsValue], filename: &JsString, ctx: &mut Context| {
+            let specifier = match args.first() {
+                Some(specifier) if specifier.is_string() => str_from_jsvalue!(specifier, ctx),
+                _ => throw!(typ, "The \"id\" argument of require must be a string"),
+            };
+            let filename = PathBuf::from(filename.to_std_string_escaped());
+
+            match CommonJs::resolve(&specifier, &filename, ctx)? {
+                Resolved::File(file) => require_file(&file, ctx),
+                Resolved::Builtin(name) => require_builtin(&name, ctx),
+            }
+        },
+        filename.clone(),
+    );
+    let resolve = NativeFunction::from_copy_closure_with_captures(
+        |_: &JsValue, args: &[JsValue], filename: &JsString, ctx: &mut Context| {
+            let specifier = match args.first() {
+                Some(specifier) if specifier.is_string() => str_from_jsvalue!(specifier, ctx),
+                _ => throw!(
+                    typ,
+                    "The \"request\" argument of require.resolve must be a string"
+                ),
+            };
+            let filename = PathBuf::from(filename.to_std_string_escaped());
+
+            match CommonJs::resolve(&specifier, &filename, ctx)? {
+                Resolved::File(file) => Ok(js_string!(file.to_string_lossy().to_string()).into()),
+                Resolved::Builtin(name) => Ok(js_string!(name).into()),
+            }
+        },
+        filename,
+    );
+
+    let resolve = FunctionObjectBuilder::new(ctx.realm(), resolve)
+        .name(js_string!("resolve"))
+        .length(1)
+        .build();
+    let require: JsObject = FunctionObjectBuilder::new(ctx.realm(), require)
+        .name(js_string!("require"))
+        .length(1)
+        .build()
+        .into();
+
+    require.set(js_string!("resolve"), resolve, false, ctx)?;
+    require.set(js_string!("cache"), CommonJs::cache(ctx)?, false, ctx)?;
+
+    Ok(require)
+}
+
+fn load_commonjs(path: &Path, module: &JsObject, ctx: &mut Context) -> JsResult<()> {
+    let is_typescript = matches!(
+        path.extension().and_then(|extension| extension.to_str()),
+        Some("ts" | "cts" | "tsx")
+    );
+    let source = if is_typescript {
+        transpile_with_source_map(path, ctx)?
+    } else {
+        match read_to_string(path) {
+            Ok(source) => source,
+            Err(err) => throw!(err, "Failed to load {}: {}", path.display(), err),
+        }
+    };
+
+    // Same wrapper as node, so `this` is `exports` and the module scope stays private
+    let wrapped =
+        format!("(function (exports, require, module, __filename, __dirname) {{{source}\n}})");
+    let function = ctx.eval(Source::from_bytes(wrapped.as_bytes()).with_path(path))?;
+    let Some(function) = function.as_callable() else {
+        throw!(err, "Failed to load {}", path.display());
+    };
+
+    let exports = module.get(js_string!("exports"), ctx)?;
+    let require = create_require(path, ctx)?;
+    let filename = js_string!(path.to_string_lossy().to_string());
+    let dirname = js_string!(path.parent().unwrap_or(path).to_string_lossy().to_string());
+
+    function.call(
+        &exports,
+        &[
+            exports.clone(),
+            require.into(),
+            module.clone().into(),
+            filename.into(),
+            dirname.into(),
+        ],
+        ctx,
+    )?;
+
+    Ok(())
+}
+
+/// Transpiles a file, keeping its source map for error reporting.
+fn transpile_with_source_map(path: &Path, ctx: &mut Context) -> JsResult<String> {
+    let transpiled = transpile_file(path).map_err(|err| transpile_error(err, ctx))?;
+    let code = match SourceMaps::from_context(ctx) {
+        Some(source_maps) => source_maps.prepare(path, &transpiled),
+        None => transpiled.code,
+    };
+
+    Ok(code)
+}
+
+fn load_json(path: &Path, module: &JsObject, ctx: &mut Context) -> JsResult<()> {
+    let json: serde_json::Value = match read_json(path) {
+        Ok(json) => json,
+        Err(err) => throw!(err, "Failed to parse {}: {}", path.display(), err),
+    };
+    let value = JsValue::from_json(&json, ctx)?;
+
+    module.set(js_string!("exports"), value, false, ctx)?;
+
+    Ok(())
+}
+
+/// `require` of an ES module evaluates it right away and returns its namespace.
+fn load_esm(path: &Path, module: &JsObject, ctx: &mut Context) -> JsResult<()> {
+    let loader = ctx.module_loader();
+    let key = js_string!(path.to_string_lossy().to_string());
+
+    let esm = match loader.get_module(key.clone()) {
+        Some(esm) => esm,
+        None => {
+            let source = transpile_with_source_map(path, ctx)?;
+            let esm = Module::parse(
+                Source::from_bytes(source.as_bytes()).with_path(path),
+                None,
+                ctx,
+            )?;
+            loader.register_module(key, esm.clone());
+            esm
+        }
+    };
+
+    let namespace = evaluate_sync(&esm, &path.to_string_lossy(), ctx)?;
+    module.set(js_string!("exports"), namespace, false, ctx)?;
+
+    Ok(())
+}
+
+fn evaluate_sync(module: &Module, name: &str, ctx: &mut Context) -> JsResult<JsValue> {
+    let promise = module.load_link_evaluate(ctx);
+    ctx.run_jobs();
+
+    match promise.state() {
+        PromiseState::Fulfilled(_) => Ok(module.namespace(ctx).into()),
+        PromiseState::Rejected(err) => Err(JsError::from_opaque(err)),
+        PromiseState::Pending => throw!(
+            err,
+            "require() of {} is not supported because it uses top-level await",
+            name
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_names() {
+        let source = r#"
+            Object.defineProperty(exports, "__esModule", { value: true });
+            exports.add = (a, b) => a + b;
+            module.exports.sub = (a, b) => a - b;
+            exports["mul"] = (a, b) => a * b;
+            exports.default = 1;
+            Object.defineProperty(exports, "version", { value: "1.0.0" });
+        "#;
+
+        assert_eq!(
+            export_names(Path::new("math.cjs"), source),
+            BTreeSet::from(["add", "mul", "sub", "version"].map(String::from))
+        );
+        assert_eq!(
+            export_names(
+                Path::new("math.cjs"),
+                "module.exports = { add, sub: 1, 'my-name': 2 };"
+            ),
+            BTreeSet::from(["add", "sub"].map(String::from))
+        );
+    }
+
+    #[test]
+    fn test_looks_like_commonjs() {
+        let path = Path::new("index.js");
+
+        assert!(looks_like_commonjs(
+            path,
+            "const fs = require('fs');\nmodule.exports = fs;"
+        ));
+        assert!(!looks_like_commonjs(
+            path,
+            "import fs from 'fs';\nexport default require;"
+        ));
+        assert!(!looks_like_commonjs(path, "export const value = 1;"));
+        assert!(!looks_like_commonjs(
+            path,
+            "const message = 'required';\nawait Promise.resolve(exports);"
+        ));
+        assert!(!looks_like_commonjs(
+            path,
+            "module.exports = import.meta.url;"
+        ));
+        assert!(!looks_like_commonjs(path, "console.log('module.exports');"));
+        assert!(looks_like_commonjs(
+            path,
+            "module.exports = async () => await require('fs');"
+        ));
+    }
+
+    #[test]
+    fn test_is_commonjs() {
+        let dir = std::env::temp_dir().join(format!("ike-commonjs-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let entry = dir.join("main.ts");
+        std::fs::write(
+            &entry,
+            "const message: string = \"require exports\";\nawait Promise.resolve(message);\n",
+        )
+        .unwrap();
+        assert!(!is_commonjs(&entry));
+
+        // Never sniffed, even when they look like CommonJS
+        let script = dir.join("script.ts");
+        std::fs::write(&script, "module.exports = require('fs');").unwrap();
+        assert!(!is_commonjs(&script));
+        assert!(is_commonjs(&dir.join("script.cts")));
+
+        let script = dir.join("script.js");
+        std::fs::write(&script, "module.exports = require('fs');").unwrap();
+        assert!(is_commonjs(&script));
+
+        std::fs::write(dir.join("package.json"), r#"{ "type": "module" }"#).unwrap();
+        assert!(!is_commonjs(&script));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -57,6 +57,16 @@ impl Console {
         }
     }
 
+    /// Prints `value` the same way `console.log` does, e.g. for results of the REPL.
+    pub fn print_value(value: &JsValue, ctx: &mut Context) -> JsResult<JsValue> {
+        Self::print(
+            std::slice::from_ref(value),
+            ctx,
+            LogLevel::Normal,
+            &mut Self::default(),
+        )
+    }
+
     fn log(
         _: &JsValue,
         args: &[JsValue],
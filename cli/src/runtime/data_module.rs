@@ -0,0 +1,157 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use boa_engine::{JsNativeError, JsResult};
+use std::{fmt, path::Path};
+
+/// Prefix the transpiler puts in front of specifiers imported with a `type` attribute,
+/// e.g. `ike-import-type:json:./config.json`. Boa's module loader only gets the
+/// specifier, so the attribute has to travel inside of it.
+pub const IMPORT_TYPE_PREFIX: &str = "ike-import-type:";
+
+/// Modules that export the content of a file instead of running it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportType {
+    /// `with { type: "json" }`, the parsed JSON value.
+    Json,
+    /// `with { type: "text" }`, the content as a string.
+    Text,
+    /// `with { type: "bytes" }`, the content as a Uint8Array.
+    Bytes,
+    /// `with { type: "toml" }`, the parsed TOML table.
+    Toml,
+}
+
+impl ImportType {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "json" => Some(Self::Json),
+            "text" => Some(Self::Text),
+            "bytes" => Some(Self::Bytes),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    /// `.json` and `.toml` files are data modules even without an import attribute.
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    /// Key of a data module in the module map, so a file imported as text and as JSON
+    /// are two different modules.
+    pub fn module_key(&self, path: &Path) -> String {
+        format!("{IMPORT_TYPE_PREFIX}{self}:{}", path.display())
+    }
+
+    /// Source of a module whose default export is `content` as this type.
+    pub fn module_source(&self, content: &[u8], path: &Path) -> JsResult<String> {
+        let text = || {
+            std::str::from_utf8(content).map_err(|_| {
+                JsNativeError::typ().with_message(format!("{} is not valid UTF-8", path.display()))
+            })
+        };
+
+        let source = match self {
+            Self::Json => {
+                let text = text()?;
+                serde_json::from_str::<serde_json::Value>(text).map_err(|err| {
+                    JsNativeError::syntax()
+                        .with_message(format!("Invalid JSON in {}: {err}", path.display()))
+                })?;
+
+                format!("export default JSON.parse({});", string_literal(text))
+            }
+            Self::Text => format!("export default {};", string_literal(text()?)),
+            Self::Bytes => format!(
+                "export default Uint8Array.from(atob(\"{}\"), (char) => char.charCodeAt(0));",
+                STANDARD.encode(content)
+            ),
+            Self::Toml => {
+                let text = text()?;
+                text.parse::<toml::Table>().map_err(|err| {
+                    JsNativeError::syntax()
+                        .with_message(format!("Invalid TOML in {}: {err}", path.display()))
+                })?;
+
+                format!(
+                    "export default $rustFunction(\"parseToml\")({});",
+                    string_literal(text)
+                )
+            }
+        };
+
+        Ok(source)
+    }
+}
+
+impl fmt::Display for ImportType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Json => "json",
+            Self::Text => "text",
+            Self::Bytes => "bytes",
+            Self::Toml => "toml",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// Splits a specifier rewritten by the transpiler into its import type and the original
+/// specifier.
+pub fn split_import_type(specifier: &str) -> JsResult<(Option<ImportType>, String)> {
+    let Some(rest) = specifier.strip_prefix(IMPORT_TYPE_PREFIX) else {
+        return Ok((None, specifier.to_string()));
+    };
+    let (kind, specifier) = rest.split_once(':').unwrap_or((rest, ""));
+
+    match ImportType::parse(kind) {
+        Some(import_type) => Ok((Some(import_type), specifier.to_string())),
+        None => Err(JsNativeError::typ()
+            .with_message(format!(
+                "Unsupported import type \"{kind}\" for \"{specifier}\", expected one of json, text, bytes or toml"
+            ))
+            .into()),
+    }
+}
+
+// JSON strings are valid JS string literals
+fn string_literal(text: &str) -> String {
+    serde_json::to_string(text).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_import_type() {
+        let (import_type, specifier) =
+            split_import_type("ike-import-type:json:./config.json").unwrap();
+        assert_eq!(import_type, Some(ImportType::Json));
+        assert_eq!(specifier, "./config.json");
+
+        let (import_type, specifier) = split_import_type("./main.ts").unwrap();
+        assert_eq!(import_type, None);
+        assert_eq!(specifier, "./main.ts");
+
+        assert!(split_import_type("ike-import-type:css:./style.css").is_err());
+    }
+
+    #[test]
+    fn test_module_source() {
+        let path = Path::new("data.json");
+
+        assert_eq!(
+            ImportType::Text
+                .module_source(b"a \"quote\"\n", path)
+                .unwrap(),
+            "export default \"a \\\"quote\\\"\\n\";"
+        );
+        assert!(ImportType::Json.module_source(b"{ invalid", path).is_err());
+        assert!(ImportType::Toml.module_source(b"key = ", path).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+use crate::create_method;
+use boa_engine::{
+    js_string,
+    object::{builtins::JsArray, builtins::JsProxy, ObjectInitializer},
+    property::Attribute,
+    Context, JsData, JsNativeError, JsObject, JsResult, JsValue, NativeFunction,
+};
+use boa_gc::{Finalize, Trace};
+use ike_core::permissions::{check_permission, PermissionKind};
+use ike_core::{str_from_jsvalue, throw};
+
+// Methods that are always resolved on the target instead of the environment
+const ENV_METHODS: [&str; 5] = ["get", "set", "delete", "has", "toObject"];
+
+#[derive(Debug, Default, Trace, Finalize, JsData)]
+pub struct IkeEnv {}
+
+impl IkeEnv {
+    pub fn init(ctx: &mut Context) -> JsObject {
+        let target = ObjectInitializer::with_native_data(Self::default(), ctx)
+            .function(create_method!(Self::get), js_string!("get"), 1)
+            .function(create_method!(Self::set), js_string!("set"), 2)
+            .function(create_method!(Self::delete), js_string!("delete"), 1)
+            .function(create_method!(Self::has), js_string!("has"), 1)
+            .function(create_method!(Self::to_object), js_string!("toObject"), 0)
+            .build();
+
+        // Property-style access (`Ike.env.HOME = "..."`) goes through the proxy traps,
+        // so reads and writes always hit the real process environment.
+        let proxy = JsProxy::builder(target)
+            .get(Self::proxy_get)
+            .set(Self::proxy_set)
+            .has(Self::proxy_has)
+            .delete_property(Self::proxy_delete)
+            .own_keys(Self::proxy_own_keys)
+            .get_own_property_descriptor(Self::proxy_get_own_property_descriptor)
+            .build(ctx);
+
+        JsObject::from(proxy)
+    }
+
+    pub fn get(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+        let key = Self::key_from_args(args, "get", ctx)?;
+        check_permission(PermissionKind::Env, Some(&key), ctx)?;
+
+        match std::env::var(&key) {
+            Ok(value) => Ok(JsValue::from(js_string!(value))),
+            Err(_) => Ok(JsValue::undefined()),
+        }
+    }
+
+    pub fn set(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+        let key = Self::key_from_args(args, "set", ctx)?;
+        let value = args.get(1);
+        if value.is_none() || value.unwrap().is_undefined() {
+            throw!(typ, "Ike.env.set: Expected a value for {}", key);
+        }
+        let value = str_from_jsvalue!(value.unwrap(), ctx);
+        check_permission(PermissionKind::Env, Some(&key), ctx)?;
+
+        Self::set_var(&key, &value)?;
+
+        Ok(JsValue::undefined())
+    }
+
+    pub fn delete(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+        let key = Self::key_from_args(args, "delete", ctx)?;
+        Self::validate_key(&key)?;
+        check_permission(PermissionKind::Env, Some(&key), ctx)?;
+
+        std::env::remove_var(&key);
+
+        Ok(JsValue::undefined())
+    }
+
+    pub fn has(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+        let key = Self::key_from_args(args, "has", ctx)?;
+        check_permission(PermissionKind::Env, Some(&key), ctx)?;
+
+        Ok(JsValue::from(std::env::var_os(&key).is_some()))
+    }
+
+    pub fn to_object(_: &JsValue, _: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+        check_permission(PermissionKind::Env, None, ctx)?;
+        let obj = JsObject::with_object_proto(ctx.intrinsics());
+
+        for (key, value) in std::env::vars() {
+            obj.set(
+                js_string!(key),
+                JsValue::from(js_string!(value)),
+                false,
+                ctx,
+            )?;
+        }
+
+        Ok(JsValue::from(obj))
+    }
+
+    fn proxy_get(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+        let target = Self::target_from_args(args)?;
+        let key = args.get(1).cloned().unwrap_or_default();
+        let property_key = key.to_property_key(ctx)?;
+        // The methods and what every object inherits, `String(Ike.env)` calls `toString`
+        let on_target = target.has_property(property_key.clone(), ctx)?;
+
+        match Self::env_key(&key) {
+            Some(name) if !on_target => {
+                check_permission(PermissionKind::Env, Some(&name), ctx)?;
+
+                match std::env::var(&name) {
+                    Ok(value) => Ok(JsValue::from(js_string!(value))),
+                    Err(_) => Ok(JsValue::undefined()),
+                }
+            }
+            _ => target.get(property_key, ctx),
+        }
+    }
+
+    fn proxy_set(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+        let key = args.get(1).cloned().unwrap_or_default();
+        let value = args.get(2).cloned().unwrap_or_default();
+
+        match Self::env_key(&key) {
+            Some(name) if !ENV_METHODS.contains(&name.as_str()) => {
+                let value = str_from_jsvalue!(value, ctx);
+                check_permission(PermissionKind::Env, Some(&name), ctx)?;
+                Self::set_var(&name, &value)?;
+
+                Ok(JsValue::from(true))
+            }
+            _ => throw!(typ, "Ike.env: Cannot assign to {}", key.display()),
+        }
+    }
+
+    fn proxy_has(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+        let target = Self::target_from_args(args)?;
+        let key = args.get(1).cloned().unwrap_or_default();
+
+        if let Some(name) = Self::env_key(&key) {
+            check_permission(PermissionKind::Env, Some(&name), ctx)?;
+
+            if std::env::var_os(&name).is_some() {
+                return Ok(JsValue::from(true));
+            }
+        }
+
+        Ok(JsValue::from(
+            target.has_property(key.to_property_key(ctx)?, ctx)?,
+        ))
+    }
+
+    fn proxy_delete(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+        let key = args.get(1).cloned().unwrap_or_default();
+
+        match Self::env_key(&key) {
+            Some(name) if !ENV_METHODS.contains(&name.as_str()) => {
+                Self::validate_key(&name)?;
+                check_permission(PermissionKind::Env, Some(&name), ctx)?;
+                std::env::remove_var(&name);
+
+                Ok(JsValue::from(true))
+            }
+            _ => Ok(JsValue::from(false)),
+        }
+    }
+
+    fn proxy_own_keys(_: &JsValue, _: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+        check_permission(PermissionKind::Env, None, ctx)?;
+
+        let keys = std::env::vars_os()
+            .filter_map(|(key, _)| key.into_string().ok())
+            .map(|key| JsValue::from(js_string!(key)));
+
+        Ok(JsArray::from_iter(keys, ctx).into())
+    }
+
+    fn proxy_get_own_property_descriptor(
+        _: &JsValue,
+        args: &[JsValue],
+        ctx: &mut Context,
+    ) -> JsResult<JsValue> {
+        let key = args.get(1).cloned().unwrap_or_default();
+        let Some(name) = Self::env_key(&key) else {
+            return Ok(JsValue::undefined());
+        };
+        check_permission(PermissionKind::Env, Some(&name), ctx)?;
+
+        let value = match std::env::var(&name) {
+            Ok(value) => value,
+            Err(_) => return Ok(JsValue::undefined()),
+        };
+
+        let descriptor = ObjectInitializer::new(ctx)
+            .property(
+                js_string!("value"),
+                JsValue::from(js_string!(value)),
+                Attribute::all(),
+            )
+            .property(js_string!("writable"), true, Attribute::all())
+            .property(js_string!("enumerable"), true, Attribute::all())
+            .property(js_string!("configurable"), true, Attribute::all())
+            .build();
+
+        Ok(JsValue::from(descriptor))
+    }
+
+    fn target_from_args(args: &[JsValue]) -> JsResult<JsObject> {
+        match args.first().and_then(|target| target.as_object()) {
+            Some(target) => Ok(target.clone()),
+            None => throw!(typ, "Ike.env: Invalid proxy target"),
+        }
+    }
+
+    fn env_key(key: &JsValue) -> Option<String> {
+        key.as_string().map(|key| key.to_std_string_escaped())
+    }
+
+    fn key_from_args(args: &[JsValue], method: &str, ctx: &mut Context) -> JsResult<String> {
+        let key = args.first();
+        if key.is_none() || key.unwrap().is_undefined() {
+            throw!(typ, "Ike.env.{}: Expected a variable name", method);
+        }
+
+        Ok(str_from_jsvalue!(key.unwrap(), ctx))
+    }
+
+    fn validate_key(key: &str) -> JsResult<()> {
+        if key.is_empty() || key.contains('=') || key.contains('\0') {
+            throw!(typ, "Ike.env: Invalid environment variable name {:?}", key);
+        }
+
+        Ok(())
+    }
+
+    fn set_var(key: &str, value: &str) -> JsResult<()> {
+        Self::validate_key(key)?;
+        if value.contains('\0') {
+            throw!(
+                typ,
+                "Ike.env: Value of {} cannot contain NUL characters",
+                key
+            );
+        }
+
+        std::env::set_var(key, value);
+
+        Ok(())
+    }
+}
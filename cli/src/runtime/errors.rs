@@ -0,0 +1,154 @@
+use super::source_map::error_stack;
+use crate::transpiler::{code_frame, SyntaxError};
+use boa_engine::{js_string, Context, JsError, JsNativeError, JsObject, JsValue};
+use ike_core::{get_prototype_name, js_str_to_string};
+use ike_logger::{elog, escape, Logger};
+
+/// Turns a transpiler error into a JS error. Syntax errors become a `SyntaxError` with
+/// `fileName`, `lineNumber`, `columnNumber` and `codeFrame` properties.
+pub fn transpile_error(err: anyhow::Error, ctx: &mut Context) -> JsError {
+    let Some(syntax_error) = err.downcast_ref::<SyntaxError>() else {
+        return JsNativeError::error().with_message(err.to_string()).into();
+    };
+
+    let error = JsNativeError::syntax()
+        .with_message(syntax_error.message.clone())
+        .to_opaque(ctx);
+    let properties = [
+        (
+            "fileName",
+            JsValue::from(js_string!(syntax_error.file.display().to_string())),
+        ),
+        ("lineNumber", JsValue::from(syntax_error.line as u32)),
+        ("columnNumber", JsValue::from(syntax_error.column as u32)),
+        (
+            "codeFrame",
+            JsValue::from(js_string!(syntax_error.code_frame.clone())),
+        ),
+    ];
+    for (key, value) in properties {
+        // a fresh error object can't refuse new properties
+        let _ = error.set(js_string!(key), value, false, ctx);
+    }
+
+    JsError::from_opaque(error.into())
+}
+
+/// Prints an error thrown by JS, followed by where it was thrown with a code frame and
+/// the remapped stack frames.
+pub fn print_error(error: &JsValue, label: &str, indent: usize, ctx: &mut Context) {
+    let indent = "  ".repeat(indent);
+    let Some(error) = error.as_object() else {
+        let message = error
+            .to_string(ctx)
+            .map(|message| message.to_std_string_escaped())
+            .unwrap_or_default();
+        elog!("{}<r><red>{}<r>: {}", indent, label, escape(&message));
+        return;
+    };
+
+    let name = match error.prototype() {
+        Some(proto) => get_prototype_name!(proto, ctx),
+        None => "Error".to_string(),
+    };
+    let message = error
+        .get(js_string!("message"), ctx)
+        .and_then(|message| message.to_string(ctx))
+        .map(|message| js_str_to_string!(message))
+        .unwrap_or_default();
+    elog!(
+        "{}<r><red>{}<r><d>({})<r>: {}",
+        indent,
+        label,
+        name,
+        escape(&message)
+    );
+
+    if let Some((location, code_frame)) = error_location(error, ctx) {
+        elog!("{}  <d>at {}<r>", indent, escape(&location));
+        if let Some(code_frame) = code_frame {
+            print_code_frame(&code_frame, &indent);
+        }
+    }
+
+    for frame in error_stack(error, ctx) {
+        elog!("{}  <d>{}<r>", indent, escape(&frame));
+    }
+}
+
+/// Prints a code frame made by the transpiler, highlighting the line of the error and
+/// the underline.
+pub fn print_code_frame(code_frame: &str, indent: &str) {
+    for line in code_frame.lines() {
+        if line.starts_with('>') {
+            elog!("{}  {}", indent, escape(line));
+        } else if line.ends_with('^') {
+            elog!("{}  <red>{}<r>", indent, escape(line));
+        } else {
+            elog!("{}  <d>{}<r>", indent, escape(line));
+        }
+    }
+}
+
+/// `file:line:column` of errors created by [`transpile_error`] or thrown by a transpiled
+/// module, see [`SourceMaps::prepare`](super::source_map::SourceMaps::prepare), along with
+/// their code frame.
+pub fn error_location(error: &JsObject, ctx: &mut Context) -> Option<(String, Option<String>)> {
+    let mut get = |key: &str| {
+        error
+            .get(js_string!(key), ctx)
+            .ok()
+            .filter(|value| !value.is_undefined())
+            .and_then(|value| value.to_string(ctx).ok())
+            .map(|value| value.to_std_string_escaped())
+    };
+
+    let file = get("fileName")?;
+    let line: usize = get("lineNumber")?.parse().ok()?;
+    let column: usize = get("columnNumber")?.parse().ok()?;
+
+    // Remote modules have no file to read the code from
+    let frame = get("codeFrame").or_else(|| {
+        let source_text = std::fs::read_to_string(&file).ok()?;
+        Some(code_frame(&source_text, line, column, 1))
+    });
+
+    Some((format!("{file}:{line}:{column}"), frame))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{runtime::source_map::SourceMaps, transpiler::transpile_file};
+    use boa_engine::{builtins::promise::PromiseState, Module, Source};
+
+    #[test]
+    fn test_error_location() {
+        let dir = std::env::temp_dir().join(format!("ike-errors-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("main.ts");
+        let source = "interface User {\n  name: string;\n}\n\nconst check = (user: User): void => {\n  if (!user.name) {\n    throw new TypeError(`Missing name`);\n  }\n};\n\ncheck({ name: '' } as User);\n";
+        std::fs::write(&path, source).unwrap();
+
+        let ctx = &mut Context::default();
+        let source_maps = SourceMaps::default();
+        source_maps.init(ctx);
+
+        let transpiled = transpile_file(&path).unwrap();
+        let code = source_maps.prepare(&path, &transpiled);
+        let module = Module::parse(Source::from_bytes(code.as_bytes()), None, ctx).unwrap();
+        let promise = module.load_link_evaluate(ctx);
+        ctx.run_jobs();
+
+        let PromiseState::Rejected(error) = promise.state() else {
+            panic!("module didn't throw");
+        };
+        let (location, code_frame) = error_location(error.as_object().unwrap(), ctx).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(location, format!("{}:7:5", path.display()));
+        assert!(code_frame
+            .unwrap()
+            .contains("> 7 |     throw new TypeError(`Missing name`);"));
+    }
+}
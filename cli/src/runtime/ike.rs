@@ -1,3 +1,4 @@
+use super::errors::transpile_error;
 use super::meta::Meta;
 use crate::globals::ALLOWED_EXTENSIONS;
 use crate::runtime::toml::parse_toml;
@@ -26,6 +27,11 @@ impl IkeGlobalObject {
             JsValue::from(js_string!(std::env::consts::OS)),
             Attribute::all(),
         );
+        obj.property(
+            js_string!("arch"),
+            JsValue::from(js_string!(std::env::consts::ARCH)),
+            Attribute::all(),
+        );
         obj.property(js_string!("version"), js_string!(VERSION), Attribute::all());
         obj.function(create_method!(Self::exit), js_string!("exit"), 1);
         obj.function(
@@ -216,14 +222,14 @@ impl IkeGlobalObject {
 
             match result {
                 Ok(transpiled) => Ok(JsValue::from(js_string!(transpiled))),
-                Err(e) => throw!(typ, "{}", e.to_string()),
+                Err(err) => Err(transpile_error(err, ctx)),
             }
         } else {
             let result = transpile(&path);
 
             match result {
                 Ok(transpiled) => Ok(JsValue::from(js_string!(transpiled))),
-                Err(e) => throw!(typ, "{}", e.to_string()),
+                Err(err) => Err(transpile_error(err, ctx)),
             }
         }
     }
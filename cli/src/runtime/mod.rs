@@ -1,11 +1,17 @@
 pub mod buffer;
 pub mod call;
+pub mod commonjs;
 pub mod console;
+pub mod data_module;
+pub mod env;
+pub mod errors;
 pub mod ike;
 pub mod meta;
 pub mod modules;
+pub mod permissions;
 pub mod queue;
 pub mod runtime;
+pub mod source_map;
 pub mod terminal;
 pub mod toml;
 pub mod uuid;
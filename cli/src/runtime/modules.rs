@@ -1,21 +1,49 @@
-use crate::transpiler::transpile;
+use super::{
+    commonjs::{esm_wrapper, is_commonjs},
+    data_module::{split_import_type, ImportType},
+    errors::transpile_error,
+    source_map::SourceMaps,
+};
+use crate::{
+    import_map::{import_map, ImportMap},
+    install::store::PackageStore,
+    remote::{net_host, resolve_url, RemoteCache, RemoteError},
+    resolver::IkeResolver,
+    transpiler::{transpile_file, transpile_source, SyntaxError},
+};
 use boa_engine::{
     js_string, module::ModuleLoader, Context, JsError, JsNativeError, JsResult, JsString, Module,
     Source,
 };
 use boa_gc::GcRefCell;
-use oxc_resolver::{EnforceExtension, ResolveOptions, Resolver};
+use ike_core::permissions::{
+    check_permission, permission_denied, request_permission, PermissionKind, PermissionState,
+};
 use rustc_hash::FxHashMap;
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
-    vec,
 };
+use url::Url;
+
+/// What an import specifier points to, see [`IkeModuleLoader::resolve_specifier`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolvedSpecifier {
+    /// A module shipped with ike, e.g. `@std/path` or `node:fs`.
+    Builtin(String),
+    Remote(Url),
+    File(PathBuf),
+}
 
 #[derive(Debug, Default)]
 pub struct IkeModuleLoader {
     module_map: GcRefCell<FxHashMap<PathBuf, Module>>,
-    // root: PathBuf,
+    resolver: IkeResolver,
+    store: PackageStore,
+    import_map: ImportMap,
+    remote: RemoteCache,
+    source_maps: SourceMaps,
+    root: PathBuf,
 }
 
 // ! js folder is generated by the unbuild package.
@@ -31,6 +59,14 @@ lazy_static::lazy_static! {
         m.insert("@std/format", include_str!("js\\format\\index.js").to_string());
         m.insert("@std/streams", include_str!("js\\streams\\index.js").to_string());
         m.insert("@std/uuid", include_str!("js\\uuid\\index.js").to_string());
+        m.insert("node:buffer", include_str!("js\\node\\buffer\\index.js").to_string());
+        m.insert("node:events", include_str!("js\\node\\events\\index.js").to_string());
+        m.insert("node:fs", include_str!("js\\node\\fs\\index.js").to_string());
+        m.insert("node:fs/promises", include_str!("js\\node\\fs\\promises\\index.js").to_string());
+        m.insert("node:os", include_str!("js\\node\\os\\index.js").to_string());
+        m.insert("node:path", include_str!("js\\node\\path\\index.js").to_string());
+        m.insert("node:process", include_str!("js\\node\\process\\index.js").to_string());
+        m.insert("node:util", include_str!("js\\node\\util\\index.js").to_string());
         m
     };
 }
@@ -38,17 +74,68 @@ lazy_static::lazy_static! {
 impl IkeModuleLoader {
     pub fn new<P: AsRef<Path>>(root: P) -> JsResult<Self> {
         let root = root.as_ref();
-        let _ = root.canonicalize().map_err(|e| {
+        let absolute = root.canonicalize().map_err(|e| {
             JsNativeError::typ()
                 .with_message(format!("could not set module root `{}`", root.display()))
                 .with_cause(JsError::from_opaque(js_string!(e.to_string()).into()))
         })?;
         Ok(Self {
-            // root: absolute,
+            root: absolute,
             module_map: GcRefCell::default(),
+            resolver: IkeResolver::default(),
+            store: PackageStore::default(),
+            import_map: import_map(),
+            remote: RemoteCache::default(),
+            source_maps: SourceMaps::default(),
         })
     }
 
+    /// Resolves `specifier` imported from `referrer` (the root without one) through the
+    /// import map, the builtin modules, remote URLs and finally the resolver.
+    pub fn resolve_specifier(
+        &self,
+        specifier: &str,
+        referrer: Option<&Path>,
+    ) -> JsResult<ResolvedSpecifier> {
+        // The import map is consulted before anything else, so it can also alias builtins
+        let referrer_path = referrer
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| self.root.clone());
+        let spec = self
+            .import_map
+            .resolve(specifier, &referrer_path)
+            .unwrap_or_else(|| specifier.to_string());
+
+        if is_builtin_module(&spec) {
+            return Ok(ResolvedSpecifier::Builtin(spec));
+        }
+
+        let url = resolve_url(&spec, &referrer_path.to_string_lossy())
+            .map_err(|err| JsNativeError::typ().with_message(err.to_string()))?;
+        if let Some(url) = url {
+            return Ok(ResolvedSpecifier::Remote(url));
+        }
+
+        let ref_path = match referrer {
+            Some(path) => path.parent().unwrap_or(path).to_path_buf(),
+            None => self.root.clone(),
+        };
+        let file = self.resolver.resolve(&ref_path, &spec).map_err(|err| {
+            JsNativeError::typ().with_message(format!(
+                "Failed to resolve \"{spec}\" from {}: {}",
+                ref_path.display(),
+                err
+            ))
+        })?;
+
+        Ok(ResolvedSpecifier::File(file))
+    }
+
+    /// Source maps of the modules transpiled by this loader.
+    pub fn source_maps(&self) -> SourceMaps {
+        self.source_maps.clone()
+    }
+
     #[inline]
     pub fn insert(&self, path: PathBuf, module: Module) {
         self.module_map.borrow_mut().insert(path, module);
@@ -58,6 +145,94 @@ impl IkeModuleLoader {
     pub fn get(&self, path: &Path) -> Option<Module> {
         self.module_map.borrow().get(path).cloned()
     }
+
+    /// Loads a `http(s)://` module through the remote cache. Modules are registered under
+    /// the requested URL and the one after redirects, which is also used as their path so
+    /// relative imports resolve against it.
+    fn load_remote(
+        &self,
+        url: Url,
+        import_type: Option<ImportType>,
+        context: &mut Context,
+    ) -> JsResult<Module> {
+        let import_type = import_type.or_else(|| ImportType::from_extension(Path::new(url.path())));
+        let requested = match import_type {
+            Some(import_type) => PathBuf::from(import_type.module_key(Path::new(url.as_str()))),
+            None => PathBuf::from(url.as_str()),
+        };
+        if let Some(module) = self.get(&requested) {
+            return Ok(module);
+        }
+
+        // Cached modules are used without net access, only downloads need it
+        if self.remote.requires_network(&url) {
+            check_permission(PermissionKind::Net, Some(&net_host(&url)), context)?;
+        }
+
+        // Redirects can lead to other hosts, which need net access as well
+        let remote = self
+            .remote
+            .load(&url, |url| {
+                request_permission(PermissionKind::Net, Some(&net_host(url)))
+                    == PermissionState::Granted
+            })
+            .map_err(|err| match err {
+                RemoteError::NetDenied(host) => {
+                    permission_denied(PermissionKind::Net, Some(&host), context)
+                }
+                err => JsNativeError::typ().with_message(err.to_string()).into(),
+            })?;
+        let path = PathBuf::from(remote.url.as_str());
+
+        if let Some(import_type) = import_type {
+            let source = import_type.module_source(remote.source.as_bytes(), &path)?;
+            let reader = Source::from_bytes(source.as_bytes()).with_path(&path);
+            let module = Module::parse(reader, None, context)?;
+            self.insert(requested, module.clone());
+            return Ok(module);
+        }
+
+        let transpiled =
+            transpile_source(&remote.virtual_path(), &remote.source).map_err(|mut err| {
+                // Point syntax errors at the URL instead of the virtual file name
+                if let Some(syntax_error) = err.downcast_mut::<SyntaxError>() {
+                    syntax_error.file = path.clone();
+                }
+                transpile_error(err, context)
+            })?;
+        let code = self.source_maps.prepare(&path, &transpiled);
+
+        let reader = Source::from_bytes(code.as_bytes()).with_path(&path);
+        let module = Module::parse(reader, None, context)?;
+
+        if path != requested {
+            self.insert(path, module.clone());
+        }
+        self.insert(requested, module.clone());
+        Ok(module)
+    }
+
+    /// Loads a JSON, text, bytes or TOML file as a module with a default export.
+    fn load_data(
+        &self,
+        file: &Path,
+        import_type: ImportType,
+        context: &mut Context,
+    ) -> JsResult<Module> {
+        let key = PathBuf::from(import_type.module_key(file));
+        if let Some(module) = self.get(&key) {
+            return Ok(module);
+        }
+
+        let content = std::fs::read(file).map_err(|err| {
+            JsNativeError::error().with_message(format!("Failed to read {}: {err}", file.display()))
+        })?;
+        let source = import_type.module_source(&content, file)?;
+        let reader = Source::from_bytes(source.as_bytes()).with_path(file);
+        let module = Module::parse(reader, None, context)?;
+        self.insert(key, module.clone());
+        Ok(module)
+    }
 }
 
 impl ModuleLoader for IkeModuleLoader {
@@ -68,81 +243,69 @@ impl ModuleLoader for IkeModuleLoader {
         finish_load: Box<dyn FnOnce(JsResult<Module>, &mut Context)>,
         context: &mut Context,
     ) {
-        let spec = specifier.to_std_string_escaped();
-
-        if is_builtin_module(&spec) {
-            let bytes = BUILTIN_MODULES.get(spec.as_str()).unwrap().as_bytes();
-            let source = Source::from_bytes(bytes);
-            let module = Module::parse(source, None, context);
+        let (import_type, spec) = match split_import_type(&specifier.to_std_string_escaped()) {
+            Ok(split) => split,
+            Err(err) => return finish_load(Err(err), context),
+        };
 
-            finish_load(module, context);
-        } else {
+        // Modules registered by the runtime, e.g. `module:fs/fs.js`
+        if import_type.is_none() {
             if let Some(module) = self.get(Path::new(&spec)) {
-                let result = (|| Ok(module))();
+                return finish_load(Ok(module), context);
+            }
+        }
 
+        let file = match self.resolve_specifier(&spec, referrer.path()) {
+            Ok(ResolvedSpecifier::Builtin(name)) => {
+                let bytes = BUILTIN_MODULES.get(name.as_str()).unwrap().as_bytes();
+                let source = Source::from_bytes(bytes);
+                let module = Module::parse(source, None, context);
+
+                return finish_load(module, context);
+            }
+            Ok(ResolvedSpecifier::Remote(url)) => {
+                let result = self.load_remote(url, import_type, context);
                 return finish_load(result, context);
             }
+            Ok(ResolvedSpecifier::File(file)) => file,
+            Err(err) => return finish_load(Err(err), context),
+        };
 
-            let ref_path = match referrer.path().unwrap().parent() {
-                Some(parent) => parent,
-                None => {
-                    finish_load(
-                        Err(JsNativeError::typ()
-                            .with_message("Failed to get parent directory")
-                            .into()),
-                        context,
-                    );
-                    return;
-                }
-            };
-
-            let options = ResolveOptions {
-                enforce_extension: EnforceExtension::Disabled,
-                condition_names: vec!["node".into(), "import".into()],
-                extensions: vec![
-                    ".js".into(),
-                    ".mjs".into(),
-                    ".ts".into(),
-                    ".mts".into(),
-                    ".cjs".into(),
-                    ".cts".into(),
-                ],
-                ..ResolveOptions::default()
-            };
-
-            // TODO: implement our own resolver because of the difference in package.json and ike.toml
-            match Resolver::new(options).resolve(ref_path, &spec) {
-                Err(error) => println!("Error: {error}"),
-                Ok(resolution) => {
-                    let result = (|| -> JsResult<Module> {
-                        let file = resolution.full_path();
-                        let transpiled = transpile(&file)
-                            .map_err(|err| {
-                                JsNativeError::error()
-                                    .with_message(err.to_string())
-                                    .with_cause(JsError::from_opaque(
-                                        js_string!(err.to_string()).into(),
-                                    ))
-                            })
-                            .unwrap();
-
-                        let reader =
-                            Source::from_bytes(transpiled.as_bytes()).with_path(&Path::new(&file));
-                        let module = Module::parse(reader, None, context)
-                            .map_err(|err| {
-                                JsNativeError::syntax()
-                                    .with_message(format!("could not parse module `{spec}`"))
-                                    .with_cause(err)
-                            })
-                            .unwrap();
-                        self.insert(file, module.clone());
-                        Ok(module)
-                    })();
-
-                    finish_load(result, context);
-                }
+        let result = (|| -> JsResult<Module> {
+            if let Some(import_type) = import_type.or_else(|| ImportType::from_extension(&file)) {
+                return self.load_data(&file, import_type, context);
             }
-        }
+
+            if let Some(module) = self.get(&file) {
+                return Ok(module);
+            }
+
+            self.store.verify_file(&file).map_err(|_| {
+                JsNativeError::error().with_message(format!(
+                    "Integrity check failed: {} was modified after it was installed, run `ike install` to restore it",
+                    file.display()
+                ))
+            })?;
+
+            // CommonJS runs through `require`, importers get a wrapper with its exports
+            if is_commonjs(&file) {
+                let wrapper = esm_wrapper(&file);
+                let reader = Source::from_bytes(wrapper.as_bytes()).with_path(&file);
+                let module = Module::parse(reader, None, context)?;
+                self.insert(file, module.clone());
+                return Ok(module);
+            }
+
+            let transpiled = transpile_file(&file).map_err(|err| transpile_error(err, context))?;
+            let code = self.source_maps.prepare(&file, &transpiled);
+
+            let reader = Source::from_bytes(code.as_bytes()).with_path(&Path::new(&file));
+            let module = Module::parse(reader, None, context)?;
+            self.insert(file, module.clone());
+            Ok(module)
+        })();
+
+        finish_load(result, context);
     }
 
     fn register_module(&self, specifier: JsString, module: Module) {
@@ -161,3 +324,117 @@ impl ModuleLoader for IkeModuleLoader {
 pub fn is_builtin_module(specifier: &str) -> bool {
     BUILTIN_MODULES.contains_key(specifier)
 }
+
+pub fn builtin_module_source(specifier: &str) -> Option<&'static str> {
+    BUILTIN_MODULES.get(specifier).map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::{tests::serve, CacheSetting};
+    use boa_engine::builtins::promise::PromiseState;
+    use ike_core::permissions::{set_permissions, Permissions};
+    use std::{rc::Rc, sync::atomic::Ordering};
+
+    #[test]
+    fn test_load_remote_permission() {
+        let (server, requests) = serve("export const answer: number = 42;");
+        let url = Url::parse(&format!("{server}/mod.ts")).unwrap();
+        let dir = std::env::temp_dir().join(format!("ike-modules-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let loader = IkeModuleLoader {
+            remote: RemoteCache::new(dir.clone(), CacheSetting::Use),
+            ..Default::default()
+        };
+        let context = &mut Context::default();
+
+        set_permissions(Permissions {
+            prompt: false,
+            ..Default::default()
+        });
+        assert!(loader.load_remote(url.clone(), None, context).is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+
+        let mut permissions = Permissions {
+            prompt: false,
+            ..Default::default()
+        };
+        permissions.grant(PermissionKind::Net, Some(&net_host(&url)));
+        set_permissions(permissions);
+        assert!(loader.load_remote(url.clone(), None, context).is_ok());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // Cached modules don't need net access
+        set_permissions(Permissions {
+            prompt: false,
+            ..Default::default()
+        });
+        let loader = IkeModuleLoader {
+            remote: RemoteCache::new(dir.clone(), CacheSetting::Use),
+            ..Default::default()
+        };
+        assert!(loader.load_remote(url, None, context).is_ok());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_import_map_relative_keys() {
+        let dir = std::env::temp_dir().join(format!("ike-import-map-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(
+            dir.join("import_map.json"),
+            r#"{ "imports": { "./src/config.ts": "./src/config.prod.ts" } }"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("src/config.ts"), "export const env = 'dev';").unwrap();
+        std::fs::write(dir.join("src/config.prod.ts"), "export const env = 'prod';").unwrap();
+
+        let loader = Rc::new(IkeModuleLoader {
+            import_map: ImportMap::from_file(&dir.join("import_map.json")).unwrap(),
+            root: dir.clone(),
+            ..Default::default()
+        });
+        let ctx = &mut Context::builder().module_loader(loader).build().unwrap();
+
+        let main = dir.join("src/main.ts");
+        let source = Source::from_bytes(b"export { env } from './config.ts';").with_path(&main);
+        let module = Module::parse(source, None, ctx).unwrap();
+        let promise = module.load_link_evaluate(ctx);
+        ctx.run_jobs();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(promise.state(), PromiseState::Fulfilled(_)));
+        let env = module.namespace(ctx).get(js_string!("env"), ctx).unwrap();
+        assert_eq!(env.to_string(ctx).unwrap().to_std_string_escaped(), "prod");
+    }
+
+    #[test]
+    fn test_unsupported_file_type() {
+        let dir = std::env::temp_dir().join(format!("ike-file-type-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("styles.css"), "body { margin: 0; }").unwrap();
+
+        let loader = Rc::new(IkeModuleLoader {
+            root: dir.clone(),
+            ..Default::default()
+        });
+        let ctx = &mut Context::builder().module_loader(loader).build().unwrap();
+
+        let main = dir.join("main.ts");
+        let source = Source::from_bytes(b"import './styles.css';").with_path(&main);
+        let module = Module::parse(source, None, ctx).unwrap();
+        let promise = module.load_link_evaluate(ctx);
+        ctx.run_jobs();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let PromiseState::Rejected(err) = promise.state() else {
+            panic!("importing a stylesheet didn't fail");
+        };
+        let message = err.to_string(ctx).unwrap().to_std_string_escaped();
+        assert!(message.contains("Unsupported file type"), "{message}");
+    }
+}
@@ -0,0 +1,100 @@
+use crate::create_method;
+use boa_engine::{
+    js_string, object::ObjectInitializer, Context, JsData, JsNativeError, JsObject, JsResult,
+    JsValue, NativeFunction,
+};
+use boa_gc::{Finalize, Trace};
+use ike_core::permissions::{
+    query_permission, request_permission, revoke_permission, PermissionKind, PermissionState,
+};
+use ike_core::{str_from_jsvalue, throw};
+
+#[derive(Debug, Default, Trace, Finalize, JsData)]
+pub struct IkePermissions {}
+
+impl IkePermissions {
+    pub fn init(ctx: &mut Context) -> JsObject {
+        ObjectInitializer::with_native_data(Self::default(), ctx)
+            .function(create_method!(Self::query), js_string!("query"), 1)
+            .function(create_method!(Self::request), js_string!("request"), 1)
+            .function(create_method!(Self::revoke), js_string!("revoke"), 1)
+            .build()
+    }
+
+    pub fn query(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+        let (kind, resource) = Self::descriptor_from_args(args, "query", ctx)?;
+        let state = query_permission(kind, resource.as_deref());
+
+        Ok(Self::state_to_value(state))
+    }
+
+    pub fn request(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+        let (kind, resource) = Self::descriptor_from_args(args, "request", ctx)?;
+        let state = request_permission(kind, resource.as_deref());
+
+        Ok(Self::state_to_value(state))
+    }
+
+    pub fn revoke(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+        let (kind, resource) = Self::descriptor_from_args(args, "revoke", ctx)?;
+        let state = revoke_permission(kind, resource.as_deref());
+
+        Ok(Self::state_to_value(state))
+    }
+
+    fn state_to_value(state: PermissionState) -> JsValue {
+        JsValue::from(js_string!(state.as_str()))
+    }
+
+    // Descriptors follow the shape `{ name: "read", path: "./file.txt" }`
+    fn descriptor_from_args(
+        args: &[JsValue],
+        method: &str,
+        ctx: &mut Context,
+    ) -> JsResult<(PermissionKind, Option<String>)> {
+        let descriptor = match args.first().and_then(|arg| arg.as_object()) {
+            Some(descriptor) => descriptor.clone(),
+            None => throw!(
+                typ,
+                "Ike.permissions.{}: Expected a descriptor object",
+                method
+            ),
+        };
+
+        let name = descriptor.get(js_string!("name"), ctx)?;
+        if !name.is_string() {
+            throw!(
+                typ,
+                "Ike.permissions.{}: Expected descriptor.name to be a string",
+                method
+            );
+        }
+        let name = str_from_jsvalue!(name, ctx);
+
+        let kind = match PermissionKind::from_name(&name) {
+            Some(kind) => kind,
+            None => throw!(
+                typ,
+                "Ike.permissions.{}: Unknown permission {:?}, expected one of read, write, net, env, run",
+                method,
+                name
+            ),
+        };
+
+        let field = match kind {
+            PermissionKind::Read | PermissionKind::Write => "path",
+            PermissionKind::Net => "host",
+            PermissionKind::Env => "variable",
+            PermissionKind::Run => "command",
+        };
+
+        let resource = descriptor.get(js_string!(field), ctx)?;
+        let resource = if resource.is_undefined() {
+            None
+        } else {
+            Some(str_from_jsvalue!(resource, ctx))
+        };
+
+        Ok((kind, resource))
+    }
+}
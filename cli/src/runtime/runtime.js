@@ -92,6 +92,13 @@ import {
   removeSync,
 } from "module:fs/fs.js";
 
+class PermissionDenied extends Error {
+  constructor(message) {
+    super(message);
+    this.name = "PermissionDenied";
+  }
+}
+
 const ikeExports = {
   createDir,
   createDirSync,
@@ -106,6 +113,10 @@ const ikeExports = {
   removeSync,
 
   path: await import("@std/path"),
+
+  errors: {
+    PermissionDenied,
+  },
 };
 
 for (const prop in ikeExports) {
@@ -1,21 +1,25 @@
 use super::{
     call::rust_function,
+    commonjs::{is_commonjs, require_file, CommonJs},
     console::Console,
+    env::IkeEnv,
+    errors::{print_error, transpile_error},
     ike::IkeGlobalObject,
     meta::Meta,
     modules::IkeModuleLoader,
+    permissions::IkePermissions,
     queue::Queue,
+    source_map::SourceMaps,
     terminal::{Terminal, TerminalStdin},
 };
 use crate::testing::js::JsTest;
-use crate::transpiler::transpile;
+use crate::transpiler::transpile_file;
 use boa_engine::{
     builtins::promise::PromiseState, js_str, js_string, module, property::Attribute, Context,
-    JsNativeError, JsObject, JsResult, JsStr, JsString, JsValue, Module, NativeFunction, Source,
+    JsObject, JsResult, JsStr, JsString, JsValue, Module, NativeFunction, Source,
 };
 use fs::FsModule;
-use ike_core::{get_prototype_name, js_str_to_string, throw, ModuleTrait};
-use ike_logger::{cond_log, Logger};
+use ike_core::ModuleTrait;
 use smol::LocalExecutor;
 use std::{
     path::{Path, PathBuf},
@@ -24,6 +28,16 @@ use std::{
 use web::WebModule;
 
 pub fn start_runtime(file: &PathBuf, context: Option<&mut Context>) -> JsResult<()> {
+    start_runtime_with_source(file, None, context)
+}
+
+/// Runs `source` as the already transpiled code of `file` instead of reading it, e.g.
+/// the program embedded in a compiled executable.
+pub fn start_runtime_with_source(
+    file: &PathBuf,
+    source: Option<&str>,
+    context: Option<&mut Context>,
+) -> JsResult<()> {
     let queue = Rc::new(Queue::new(LocalExecutor::new()));
     let module_loader = Rc::new(IkeModuleLoader::new(std::env::current_dir().unwrap())?);
     let ctx = match context {
@@ -42,11 +56,33 @@ pub fn start_runtime(file: &PathBuf, context: Option<&mut Context>) -> JsResult<
     let script_module = Module::parse(script_source, None, ctx)?;
     evaulte_module(ctx, script_module)?;
 
-    let transpiled = match transpile(file) {
+    if let Some(source) = source {
+        let reader = Source::from_bytes(source.as_bytes()).with_path(file);
+        let module = Module::parse(reader, None, ctx)?;
+
+        return evaulte_module(ctx, module);
+    }
+
+    if is_commonjs(file) {
+        require_file(file, ctx)?;
+        ctx.run_jobs();
+
+        return Ok(());
+    }
+
+    let transpiled = match transpile_file(file) {
         Ok(transpiler) => transpiler,
-        Err(e) => throw!(typ, "Failed to transpile: {:?}", e),
+        Err(err) => {
+            let err = transpile_error(err, ctx).to_opaque(ctx);
+            print_error(&err, "error", 0, ctx);
+            std::process::exit(1);
+        }
+    };
+    let code = match SourceMaps::from_context(ctx) {
+        Some(source_maps) => source_maps.prepare(file, &transpiled),
+        None => transpiled.code,
     };
-    let reader = Source::from_bytes(transpiled.as_bytes()).with_path(&Path::new(&file));
+    let reader = Source::from_bytes(code.as_bytes()).with_path(&Path::new(&file));
     let module = Module::parse(reader, None, ctx)?;
 
     evaulte_module(ctx, module)?;
@@ -55,6 +91,8 @@ pub fn start_runtime(file: &PathBuf, context: Option<&mut Context>) -> JsResult<
 }
 
 pub fn load_modules(ctx: &mut Context, module_loader: Rc<IkeModuleLoader>) -> JsResult<()> {
+    module_loader.source_maps().init(ctx);
+
     let modules: Vec<(&dyn ModuleTrait, Rc<IkeModuleLoader>)> = vec![
         (&WebModule, Rc::clone(&module_loader)),
         (&FsModule, module_loader),
@@ -107,25 +145,7 @@ pub fn evaulte_module(ctx: &mut Context, module: Module) -> JsResult<()> {
         PromiseState::Fulfilled(v) => {
             assert_eq!(v, JsValue::undefined())
         }
-        PromiseState::Rejected(err) => {
-            let obj = err.to_object(ctx).unwrap();
-            let proto = match obj.prototype() {
-                Some(proto) => proto,
-                None => {
-                    panic!("Error object has no prototype");
-                }
-            };
-            let str_name = get_prototype_name!(proto, ctx);
-
-            let message = obj.get(js_string!("message"), ctx).unwrap();
-            cond_log!(
-                true,
-                true,
-                "<r><red>error<r><d>({})<r>: {}",
-                str_name,
-                js_str_to_string!(message.to_string(ctx).unwrap())
-            );
-        }
+        PromiseState::Rejected(err) => print_error(&err, "error", 0, ctx),
     }
 
     Ok(())
@@ -208,25 +228,19 @@ pub fn setup_context(ctx: &mut Context, file: Option<&PathBuf>) {
     let ike = ctx.global_object().get(js_string!("Ike"), ctx).unwrap();
     let ike = ike.as_object().unwrap();
 
-    let env_vars = std::env::vars();
-    let env_obj = JsObject::default();
-
-    for (key, value) in env_vars {
-        env_obj
-            .set(
-                js_string!(key),
-                JsValue::from(js_string!(value)),
-                false,
-                ctx,
-            )
-            .expect("Failed to set env var");
-    }
-    ike.set(js_string!("env"), env_obj, false, ctx)
+    let env = IkeEnv::init(ctx);
+    ike.set(js_string!("env"), env, false, ctx)
         .expect("Failed to set Ike.env");
 
+    let permissions = IkePermissions::init(ctx);
+    ike.set(js_string!("permissions"), permissions, false, ctx)
+        .expect("Failed to set Ike.permissions");
+
     let stdin = TerminalStdin::init(ctx);
     ike.set(js_string!("stdin"), stdin, false, ctx)
         .expect("Failed to set Ike.stdin");
+
+    CommonJs::init(ctx);
 }
 
 pub fn get_current_path(ctx: &mut Context) -> JsValue {
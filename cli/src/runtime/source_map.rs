@@ -0,0 +1,320 @@
+use crate::{
+    bundler::emit::apply_edits,
+    transpiler::{line_column, Transpiled},
+};
+use boa_engine::{
+    js_string,
+    object::{builtins::JsProxy, FunctionObjectBuilder},
+    property::{Attribute, PropertyDescriptor},
+    Context, JsData, JsObject, JsResult, JsValue, NativeFunction,
+};
+use boa_gc::{Finalize, Trace};
+use oxc_allocator::Allocator;
+use oxc_ast::{ast::ThrowStatement, visit::walk, Visit};
+use oxc_parser::Parser;
+use oxc_sourcemap::SourceMap;
+use oxc_span::{GetSpan, SourceType, Span};
+use regex::{Captures, Regex};
+use rustc_hash::FxHashMap;
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::LazyLock,
+};
+
+/// Global the code of `throw` statements passes the thrown value through, see
+/// [`SourceMaps::prepare`]. It's read-only and can't be redefined, so user code can't
+/// replace it and break every `throw`.
+const THROW_SITE: &str = "__ikeThrowSite";
+
+/// `path:line:column` as printed in stack frames.
+static POSITION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?P<path>[^\s()@]+):(?P<line>\d+):(?P<column>\d+)").unwrap());
+
+/// Mappings of a transpiled module as `(generated line, generated column, line, column)`,
+/// zero based and sorted by the generated position.
+#[derive(Debug)]
+struct Mappings(Vec<(u32, u32, u32, u32)>);
+
+impl Mappings {
+    fn new(source_map: &SourceMap) -> Self {
+        let mut mappings: Vec<_> = source_map
+            .get_tokens()
+            .map(|token| {
+                (
+                    token.get_dst_line(),
+                    token.get_dst_col(),
+                    token.get_src_line(),
+                    token.get_src_col(),
+                )
+            })
+            .collect();
+        mappings.sort_unstable();
+
+        Self(mappings)
+    }
+
+    /// Original position of the closest mapping at or before the generated position,
+    /// as long as it's on the same line.
+    fn lookup(&self, line: u32, column: u32) -> Option<(u32, u32)> {
+        let index = self.0.partition_point(|&(dst_line, dst_column, ..)| {
+            (dst_line, dst_column) <= (line, column)
+        });
+        let &(dst_line, _, src_line, src_column) = self.0.get(index.checked_sub(1)?)?;
+
+        (dst_line == line).then_some((src_line, src_column))
+    }
+}
+
+/// Source maps of every transpiled module, keyed by the module path.
+///
+/// The module loader owns them and a handle is stored in the realm, so errors can be
+/// remapped wherever they are reported.
+#[derive(Debug, Clone, Default, Trace, Finalize, JsData)]
+pub struct SourceMaps {
+    #[unsafe_ignore_trace]
+    maps: Rc<RefCell<FxHashMap<PathBuf, Rc<Mappings>>>>,
+}
+
+impl SourceMaps {
+    /// Keeps the source map of a transpiled module and returns its code, with every
+    /// `throw` recording where it is in the original source.
+    ///
+    /// boa doesn't know the position of the code that threw, so errors would otherwise
+    /// have no location. Only `throw` statements of transpiled modules are located,
+    /// errors raised by the engine itself (e.g. calling `undefined`) stay unmapped.
+    pub fn prepare(&self, path: &Path, transpiled: &Transpiled) -> String {
+        let Some(source_map) = &transpiled.source_map else {
+            return transpiled.code.clone();
+        };
+        let mappings = Rc::new(Mappings::new(source_map));
+        self.maps
+            .borrow_mut()
+            .insert(path.to_path_buf(), mappings.clone());
+
+        locate_throws(path, &transpiled.code, &mappings)
+    }
+
+    /// Maps a one based line and column of the generated code back to the original source.
+    pub fn remap(&self, path: &Path, line: u32, column: u32) -> Option<(u32, u32)> {
+        let mappings = self.maps.borrow().get(path)?.clone();
+        let (line, column) = mappings.lookup(line.checked_sub(1)?, column.saturating_sub(1))?;
+
+        Some((line + 1, column + 1))
+    }
+
+    /// Rewrites every `path:line:column` of a transpiled module in `stack`.
+    pub fn remap_stack(&self, stack: &str) -> String {
+        POSITION
+            .replace_all(stack, |captures: &Captures| {
+                let path = &captures["path"];
+                let line = captures["line"].parse().unwrap_or_default();
+                let column = captures["column"].parse().unwrap_or_default();
+
+                match self.remap(Path::new(path), line, column) {
+                    Some((line, column)) => format!("{path}:{line}:{column}"),
+                    None => captures[0].to_string(),
+                }
+            })
+            .to_string()
+    }
+
+    pub fn init(&self, ctx: &mut Context) {
+        ctx.realm().host_defined_mut().insert(self.clone());
+
+        let function =
+            FunctionObjectBuilder::new(ctx.realm(), NativeFunction::from_fn_ptr(throw_site))
+                .name(js_string!(THROW_SITE))
+                .length(4)
+                .build();
+        // Fails when the realm has it already, which is the same function
+        let _ = ctx.register_global_property(js_string!(THROW_SITE), function, Attribute::empty());
+    }
+
+    pub fn from_context(ctx: &Context) -> Option<Self> {
+        ctx.realm().host_defined().get::<Self>().cloned()
+    }
+}
+
+/// Wraps the argument of every `throw` in `code` with a call to [`throw_site`], passing
+/// the original position of the statement.
+fn locate_throws(path: &Path, code: &str, mappings: &Mappings) -> String {
+    if !code.contains("throw") {
+        return code.to_string();
+    }
+
+    let allocator = Allocator::default();
+    let source_type = SourceType::default().with_module(true);
+    let ret = Parser::new(&allocator, code, source_type).parse();
+    // CommonJS may return at the top level, it's only wrapped in a function later
+    if !ret.errors.is_empty() {
+        return code.to_string();
+    }
+
+    let mut finder = ThrowFinder::default();
+    finder.visit_program(&ret.program);
+
+    let file = serde_json::to_string(&path.to_string_lossy()).unwrap();
+    let mut edits = Vec::new();
+    for (statement, argument) in finder.throws {
+        let (line, column) = line_column(code, statement.start as usize);
+        let Some((line, column)) = mappings.lookup(line as u32 - 1, column as u32 - 1) else {
+            continue;
+        };
+
+        edits.push((
+            Span::new(argument.start, argument.start),
+            format!("{THROW_SITE}(("),
+        ));
+        edits.push((
+            Span::new(argument.end, argument.end),
+            format!("), {file}, {}, {})", line + 1, column + 1),
+        ));
+    }
+
+    apply_edits(code, edits)
+}
+
+/// Spans of every `throw` statement and its argument.
+#[derive(Default)]
+struct ThrowFinder {
+    throws: Vec<(Span, Span)>,
+}
+
+impl<'a> Visit<'a> for ThrowFinder {
+    fn visit_throw_statement(&mut self, stmt: &ThrowStatement<'a>) {
+        self.throws.push((stmt.span, stmt.argument.span()));
+        walk::walk_throw_statement(self, stmt);
+    }
+}
+
+/// `__ikeThrowSite(error, fileName, lineNumber, columnNumber)`, records where an error
+/// was thrown unless it was thrown before, and returns it. Proxies are returned as they
+/// are, looking at them would run their traps.
+fn throw_site(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
+    let error = args.first().cloned().unwrap_or_default();
+    let Some(object) = error.as_object() else {
+        return Ok(error);
+    };
+    if JsProxy::from_object(object.clone()).is_ok() {
+        return Ok(error);
+    }
+    if object.has_own_property(js_string!("lineNumber"), ctx)? {
+        return Ok(error);
+    }
+
+    for (index, key) in ["fileName", "lineNumber", "columnNumber"]
+        .iter()
+        .enumerate()
+    {
+        let value = args.get(index + 1).cloned().unwrap_or_default();
+        // Frozen objects can be thrown too, they just don't get a location
+        let _ = object.define_property_or_throw(
+            js_string!(*key),
+            PropertyDescriptor::builder()
+                .value(value)
+                .writable(true)
+                .enumerable(false)
+                .configurable(true)
+                .build(),
+            ctx,
+        );
+    }
+
+    Ok(error)
+}
+
+/// Frames of the `stack` of an error with positions remapped to the original sources.
+pub fn error_stack(error: &JsObject, ctx: &mut Context) -> Vec<String> {
+    let stack = match error.get(js_string!("stack"), ctx) {
+        Ok(stack) => stack,
+        Err(_) => return Vec::new(),
+    };
+    let Some(stack) = stack.as_string().map(|stack| stack.to_std_string_escaped()) else {
+        return Vec::new();
+    };
+    let stack = match SourceMaps::from_context(ctx) {
+        Some(source_maps) => source_maps.remap_stack(&stack),
+        None => stack,
+    };
+
+    // The first line repeats the name and message, which are printed already
+    stack
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("at "))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transpiler::transpile_source;
+
+    #[test]
+    fn test_remap_stack() {
+        let source = "type Id = string;\n\ninterface User {\n  id: Id;\n}\n\nexport const fail = (): never => {\n  throw new Error('boom');\n};\n";
+        let path = Path::new("/app/user.ts");
+        let transpiled = transpile_source(path, source).unwrap();
+        let generated_line = transpiled
+            .code
+            .lines()
+            .position(|line| line.contains("throw"))
+            .unwrap() as u32
+            + 1;
+
+        let source_maps = SourceMaps::default();
+        source_maps.prepare(path, &transpiled);
+
+        let column = transpiled
+            .code
+            .lines()
+            .nth(generated_line as usize - 1)
+            .unwrap()
+            .find("throw")
+            .unwrap()
+            + 1;
+        let stack = format!("at fail (/app/user.ts:{generated_line}:{column})");
+
+        assert_eq!(
+            source_maps.remap_stack(&stack),
+            "at fail (/app/user.ts:8:3)"
+        );
+        assert_eq!(
+            source_maps.remap_stack("at main (/app/other.ts:1:1)"),
+            "at main (/app/other.ts:1:1)"
+        );
+    }
+
+    #[test]
+    fn test_throw_site_is_read_only() {
+        let ctx = &mut Context::default();
+        let source_maps = SourceMaps::default();
+        source_maps.init(ctx);
+        let run = |path: &str, source: &str, ctx: &mut Context| {
+            let transpiled = transpile_source(Path::new(path), source).unwrap();
+            let code = source_maps.prepare(Path::new(path), &transpiled);
+            ctx.eval(boa_engine::Source::from_bytes(code.as_bytes()))
+                .unwrap();
+        };
+
+        run(
+            "/app/main.ts",
+            "try {\n  globalThis.__ikeThrowSite = undefined;\n} catch {}\ntry {\n  throw new Error('boom');\n} catch (err) {\n  globalThis.line = err.lineNumber;\n}\n",
+            ctx,
+        );
+        let line = ctx.global_object().get(js_string!("line"), ctx).unwrap();
+        assert_eq!(line.to_u32(ctx).unwrap(), 5);
+
+        // Proxies are thrown without running their traps
+        run(
+            "/app/proxy.js",
+            "let traps = 0;\nconst proxy = new Proxy(new Error('proxied'), {\n  getOwnPropertyDescriptor() { traps++; },\n  defineProperty() { traps++; return true; },\n});\ntry {\n  throw proxy;\n} catch {}\nglobalThis.traps = traps;\n",
+            ctx,
+        );
+        let traps = ctx.global_object().get(js_string!("traps"), ctx).unwrap();
+        assert_eq!(traps.to_u32(ctx).unwrap(), 0);
+    }
+}
@@ -0,0 +1,338 @@
+import { atob, btoa, isAscii, isUtf8 } from '@std/buffer';
+import { InvalidArgTypeError } from '@std/_internal_';
+
+type Encoding =
+  | 'utf8'
+  | 'utf-8'
+  | 'hex'
+  | 'base64'
+  | 'base64url'
+  | 'latin1'
+  | 'binary'
+  | 'ascii'
+  | 'ucs2'
+  | 'ucs-2'
+  | 'utf16le'
+  | 'utf-16le';
+
+const ENCODINGS = [
+  'utf8',
+  'utf-8',
+  'hex',
+  'base64',
+  'base64url',
+  'latin1',
+  'binary',
+  'ascii',
+  'ucs2',
+  'ucs-2',
+  'utf16le',
+  'utf-16le',
+];
+
+const normalizeEncoding = (encoding?: string): Encoding => {
+  if (encoding === undefined || encoding === null) return 'utf8';
+
+  const lower = String(encoding).toLowerCase();
+  if (!ENCODINGS.includes(lower)) {
+    throw new TypeError(`Unknown encoding: ${encoding}`);
+  }
+
+  return lower as Encoding;
+};
+
+const encode = (string: string, encoding?: string): Uint8Array => {
+  switch (normalizeEncoding(encoding)) {
+    case 'hex': {
+      const length = string.length >>> 1;
+      const bytes = new Uint8Array(length);
+      for (let i = 0; i < length; i++) {
+        const byte = parseInt(string.slice(i * 2, i * 2 + 2), 16);
+        if (Number.isNaN(byte)) return bytes.subarray(0, i);
+        bytes[i] = byte;
+      }
+      return bytes;
+    }
+    case 'base64':
+    case 'base64url': {
+      let base64 = string.replace(/-/g, '+').replace(/_/g, '/');
+      base64 = base64.replace(/[^A-Za-z0-9+/]/g, '');
+      base64 += '='.repeat((4 - (base64.length % 4)) % 4);
+      const binary = atob(base64);
+      return Uint8Array.from(binary, (char) => char.charCodeAt(0));
+    }
+    case 'latin1':
+    case 'binary':
+    case 'ascii':
+      return Uint8Array.from(string, (char) => char.charCodeAt(0) & 0xff);
+    case 'ucs2':
+    case 'ucs-2':
+    case 'utf16le':
+    case 'utf-16le': {
+      const bytes = new Uint8Array(string.length * 2);
+      for (let i = 0; i < string.length; i++) {
+        const code = string.charCodeAt(i);
+        bytes[i * 2] = code & 0xff;
+        bytes[i * 2 + 1] = code >>> 8;
+      }
+      return bytes;
+    }
+    default:
+      return new TextEncoder().encode(string);
+  }
+};
+
+const decode = (bytes: Uint8Array, encoding?: string): string => {
+  switch (normalizeEncoding(encoding)) {
+    case 'hex':
+      return Array.from(bytes, (byte) => byte.toString(16).padStart(2, '0')).join(
+        '',
+      );
+    case 'base64':
+      return btoa(latin1(bytes));
+    case 'base64url':
+      return btoa(latin1(bytes))
+        .replace(/\+/g, '-')
+        .replace(/\//g, '_')
+        .replace(/=+$/, '');
+    case 'latin1':
+    case 'binary':
+      return latin1(bytes);
+    case 'ascii':
+      return latin1(bytes.map((byte) => byte & 0x7f));
+    case 'ucs2':
+    case 'ucs-2':
+    case 'utf16le':
+    case 'utf-16le': {
+      let string = '';
+      for (let i = 0; i + 1 < bytes.length; i += 2) {
+        string += String.fromCharCode(bytes[i] | (bytes[i + 1] << 8));
+      }
+      return string;
+    }
+    default:
+      return new TextDecoder().decode(bytes);
+  }
+};
+
+const latin1 = (bytes: Uint8Array): string => {
+  let string = '';
+  // chunked, so big buffers don't exceed the argument limit
+  for (let i = 0; i < bytes.length; i += 0x1000) {
+    string += String.fromCharCode(...bytes.subarray(i, i + 0x1000));
+  }
+  return string;
+};
+
+class Buffer extends Uint8Array {
+  static from(
+    value: string | ArrayLike<number> | ArrayBufferLike | Uint8Array,
+    encodingOrOffset?: string | number,
+    length?: number,
+  ): Buffer {
+    if (typeof value === 'string') {
+      const bytes = encode(value, encodingOrOffset as string);
+      return new Buffer(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+    }
+
+    if (value instanceof ArrayBuffer || value instanceof SharedArrayBuffer) {
+      const offset = (encodingOrOffset as number) ?? 0;
+      return new Buffer(value, offset, length ?? value.byteLength - offset);
+    }
+
+    if (ArrayBuffer.isView(value) || Array.isArray(value)) {
+      const buffer = new Buffer((value as ArrayLike<number>).length);
+      buffer.set(value as ArrayLike<number>);
+      return buffer;
+    }
+
+    if (value && typeof value === 'object' && (value as any).type === 'Buffer') {
+      return Buffer.from((value as any).data);
+    }
+
+    throw new InvalidArgTypeError(
+      'value',
+      ['string', 'Buffer', 'ArrayBuffer', 'Array'],
+      value,
+    );
+  }
+
+  static alloc(size: number, fill?: string | number, encoding?: string): Buffer {
+    const buffer = new Buffer(size);
+    if (fill !== undefined) buffer.fill(fill as any, 0, size, encoding);
+    return buffer;
+  }
+
+  static allocUnsafe(size: number): Buffer {
+    return new Buffer(size);
+  }
+
+  static isBuffer(value: unknown): value is Buffer {
+    return value instanceof Buffer;
+  }
+
+  static isEncoding(encoding: string): boolean {
+    return typeof encoding === 'string' && ENCODINGS.includes(encoding.toLowerCase());
+  }
+
+  static byteLength(value: string | ArrayBufferView | ArrayBuffer, encoding?: string): number {
+    if (typeof value === 'string') return encode(value, encoding).byteLength;
+    return value.byteLength;
+  }
+
+  static concat(list: Uint8Array[], totalLength?: number): Buffer {
+    const length =
+      totalLength ?? list.reduce((sum, buffer) => sum + buffer.length, 0);
+    const result = Buffer.alloc(length);
+
+    let offset = 0;
+    for (const buffer of list) {
+      if (offset >= length) break;
+      result.set(buffer.subarray(0, length - offset), offset);
+      offset += buffer.length;
+    }
+
+    return result;
+  }
+
+  static compare(a: Uint8Array, b: Uint8Array): number {
+    const length = Math.min(a.length, b.length);
+    for (let i = 0; i < length; i++) {
+      if (a[i] !== b[i]) return a[i] < b[i] ? -1 : 1;
+    }
+    return Math.sign(a.length - b.length);
+  }
+
+  toString(encoding?: string, start = 0, end = this.length): string {
+    return decode(this.subarray(start, end), encoding);
+  }
+
+  toJSON(): { type: 'Buffer'; data: number[] } {
+    return { type: 'Buffer', data: Array.from(this) };
+  }
+
+  equals(other: Uint8Array): boolean {
+    return Buffer.compare(this, other) === 0;
+  }
+
+  compare(other: Uint8Array): number {
+    return Buffer.compare(this, other);
+  }
+
+  // Unlike Uint8Array, Buffer#slice doesn't copy
+  slice(start?: number, end?: number): Buffer {
+    return this.subarray(start, end) as Buffer;
+  }
+
+  write(string: string, offset = 0, length?: number, encoding?: string): number {
+    const bytes = encode(string, encoding);
+    const count = Math.min(bytes.length, length ?? Infinity, this.length - offset);
+    this.set(bytes.subarray(0, count), offset);
+    return count;
+  }
+
+  copy(target: Uint8Array, targetStart = 0, sourceStart = 0, sourceEnd = this.length): number {
+    const bytes = this.subarray(sourceStart, sourceEnd);
+    const count = Math.min(bytes.length, target.length - targetStart);
+    target.set(bytes.subarray(0, count), targetStart);
+    return count;
+  }
+
+  // @ts-ignore: Node accepts strings and encodings here
+  fill(value: string | number, start = 0, end = this.length, encoding?: string): this {
+    if (typeof value !== 'string') {
+      return super.fill(value, start, end);
+    }
+
+    const bytes = encode(value, encoding);
+    if (bytes.length === 0) return super.fill(0, start, end);
+    for (let i = start; i < end; i++) {
+      this[i] = bytes[(i - start) % bytes.length];
+    }
+    return this;
+  }
+
+  // @ts-ignore: Node accepts strings and buffers here
+  indexOf(value: string | number | Uint8Array, byteOffset = 0, encoding?: string): number {
+    if (typeof value === 'number') return super.indexOf(value, byteOffset);
+
+    const needle = typeof value === 'string' ? encode(value, encoding) : value;
+    outer: for (let i = byteOffset; i <= this.length - needle.length; i++) {
+      for (let j = 0; j < needle.length; j++) {
+        if (this[i + j] !== needle[j]) continue outer;
+      }
+      return i;
+    }
+    return -1;
+  }
+
+  // @ts-ignore: Node accepts strings and buffers here
+  includes(value: string | number | Uint8Array, byteOffset = 0, encoding?: string): boolean {
+    return this.indexOf(value, byteOffset, encoding) !== -1;
+  }
+
+  readUInt8(offset = 0): number {
+    return this.view().getUint8(offset);
+  }
+
+  readUInt16LE(offset = 0): number {
+    return this.view().getUint16(offset, true);
+  }
+
+  readUInt16BE(offset = 0): number {
+    return this.view().getUint16(offset);
+  }
+
+  readUInt32LE(offset = 0): number {
+    return this.view().getUint32(offset, true);
+  }
+
+  readUInt32BE(offset = 0): number {
+    return this.view().getUint32(offset);
+  }
+
+  readInt32LE(offset = 0): number {
+    return this.view().getInt32(offset, true);
+  }
+
+  readInt32BE(offset = 0): number {
+    return this.view().getInt32(offset);
+  }
+
+  writeUInt8(value: number, offset = 0): number {
+    this.view().setUint8(offset, value);
+    return offset + 1;
+  }
+
+  writeUInt16LE(value: number, offset = 0): number {
+    this.view().setUint16(offset, value, true);
+    return offset + 2;
+  }
+
+  writeUInt16BE(value: number, offset = 0): number {
+    this.view().setUint16(offset, value);
+    return offset + 2;
+  }
+
+  writeUInt32LE(value: number, offset = 0): number {
+    this.view().setUint32(offset, value, true);
+    return offset + 4;
+  }
+
+  writeUInt32BE(value: number, offset = 0): number {
+    this.view().setUint32(offset, value);
+    return offset + 4;
+  }
+
+  private view(): DataView {
+    return new DataView(this.buffer, this.byteOffset, this.byteLength);
+  }
+}
+
+const kMaxLength = 2 ** 32 - 1;
+
+const constants = { MAX_LENGTH: kMaxLength };
+
+export { Buffer, atob, btoa, constants, isAscii, isUtf8, kMaxLength };
+
+export default { Buffer, atob, btoa, constants, isAscii, isUtf8, kMaxLength };
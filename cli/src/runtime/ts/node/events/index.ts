@@ -0,0 +1,187 @@
+type Listener = (...args: any[]) => void;
+
+interface WrappedListener extends Listener {
+  listener: Listener;
+}
+
+const kEvents = Symbol('events');
+
+class EventEmitter {
+  static defaultMaxListeners = 10;
+
+  private [kEvents]: Map<string | symbol, Listener[]> = new Map();
+  private maxListeners?: number;
+
+  on(event: string | symbol, listener: Listener): this {
+    return this.addListener(event, listener);
+  }
+
+  addListener(event: string | symbol, listener: Listener): this {
+    return this.add(event, listener, false);
+  }
+
+  prependListener(event: string | symbol, listener: Listener): this {
+    return this.add(event, listener, true);
+  }
+
+  once(event: string | symbol, listener: Listener): this {
+    return this.add(event, this.onceWrapper(event, listener), false);
+  }
+
+  prependOnceListener(event: string | symbol, listener: Listener): this {
+    return this.add(event, this.onceWrapper(event, listener), true);
+  }
+
+  off(event: string | symbol, listener: Listener): this {
+    return this.removeListener(event, listener);
+  }
+
+  removeListener(event: string | symbol, listener: Listener): this {
+    const listeners = this[kEvents].get(event);
+    if (!listeners) return this;
+
+    const index = listeners.findLastIndex(
+      (l) => l === listener || (l as WrappedListener).listener === listener,
+    );
+    if (index === -1) return this;
+
+    listeners.splice(index, 1);
+    if (listeners.length === 0) this[kEvents].delete(event);
+    if (this[kEvents].has('removeListener')) {
+      this.emit('removeListener', event, listener);
+    }
+
+    return this;
+  }
+
+  removeAllListeners(event?: string | symbol): this {
+    if (event === undefined) {
+      this[kEvents].clear();
+    } else {
+      this[kEvents].delete(event);
+    }
+
+    return this;
+  }
+
+  emit(event: string | symbol, ...args: any[]): boolean {
+    const listeners = this[kEvents].get(event);
+
+    if (!listeners || listeners.length === 0) {
+      if (event === 'error') {
+        const err = args[0];
+        if (err instanceof Error) throw err;
+        throw new Error(`Unhandled error. (${String(err)})`);
+      }
+
+      return false;
+    }
+
+    // copied, so listeners added or removed while emitting don't affect this emit
+    for (const listener of [...listeners]) {
+      listener.apply(this, args);
+    }
+
+    return true;
+  }
+
+  listeners(event: string | symbol): Listener[] {
+    return (this[kEvents].get(event) ?? []).map(
+      (l) => (l as WrappedListener).listener ?? l,
+    );
+  }
+
+  rawListeners(event: string | symbol): Listener[] {
+    return [...(this[kEvents].get(event) ?? [])];
+  }
+
+  listenerCount(event: string | symbol): number {
+    return this[kEvents].get(event)?.length ?? 0;
+  }
+
+  eventNames(): (string | symbol)[] {
+    return [...this[kEvents].keys()];
+  }
+
+  setMaxListeners(n: number): this {
+    this.maxListeners = n;
+    return this;
+  }
+
+  getMaxListeners(): number {
+    return this.maxListeners ?? EventEmitter.defaultMaxListeners;
+  }
+
+  private add(event: string | symbol, listener: Listener, prepend: boolean): this {
+    if (typeof listener !== 'function') {
+      throw new TypeError(
+        `The "listener" argument must be of type function. Received type ${typeof listener}`,
+      );
+    }
+
+    if (this[kEvents].has('newListener')) {
+      this.emit(
+        'newListener',
+        event,
+        (listener as WrappedListener).listener ?? listener,
+      );
+    }
+
+    const listeners = this[kEvents].get(event) ?? [];
+    if (prepend) {
+      listeners.unshift(listener);
+    } else {
+      listeners.push(listener);
+    }
+    this[kEvents].set(event, listeners);
+
+    const max = this.getMaxListeners();
+    if (max > 0 && listeners.length === max + 1) {
+      console.warn(
+        `MaxListenersExceededWarning: Possible EventEmitter memory leak detected. ${listeners.length} ${String(event)} listeners added. Use emitter.setMaxListeners() to increase limit`,
+      );
+    }
+
+    return this;
+  }
+
+  private onceWrapper(event: string | symbol, listener: Listener): Listener {
+    const emitter = this;
+    const wrapped = function (this: unknown, ...args: any[]) {
+      emitter.removeListener(event, wrapped);
+      listener.apply(this, args);
+    } as WrappedListener;
+    wrapped.listener = listener;
+
+    return wrapped;
+  }
+}
+
+/**
+ * Resolves with the arguments of the next `event`, rejects if `error` is emitted first.
+ */
+const once = (emitter: EventEmitter, event: string | symbol): Promise<any[]> => {
+  return new Promise((resolve, reject) => {
+    const onError = (err: unknown) => {
+      emitter.removeListener(event, onEvent);
+      reject(err);
+    };
+    const onEvent = (...args: any[]) => {
+      if (event !== 'error') emitter.removeListener('error', onError);
+      resolve(args);
+    };
+
+    emitter.once(event, onEvent);
+    if (event !== 'error') emitter.once('error', onError);
+  });
+};
+
+const listenerCount = (emitter: EventEmitter, event: string | symbol): number =>
+  emitter.listenerCount(event);
+
+// `require("events")` returns the class itself, with the helpers attached
+Object.assign(EventEmitter, { EventEmitter, once, listenerCount });
+
+export { EventEmitter, listenerCount, once };
+
+export default EventEmitter;
@@ -0,0 +1,214 @@
+import {
+  createDir,
+  createDirSync,
+  existsSync,
+  readFile as readBytes,
+  readFileSync as readBytesSync,
+  remove,
+  removeSync,
+} from 'module:fs/fs.js';
+import { Buffer } from 'node:buffer';
+
+type Callback<T = void> = (err: Error | null, value?: T) => void;
+
+type ReadOptions = string | { encoding?: string | null; flag?: string } | null;
+
+type MakeDirectoryOptions = number | { recursive?: boolean; mode?: number };
+
+type RemoveOptions = { recursive?: boolean; force?: boolean };
+
+const toPath = (path: string | URL): string => {
+  if (path instanceof URL) {
+    if (path.protocol !== 'file:') {
+      throw new TypeError('The URL must be of scheme file');
+    }
+    return decodeURIComponent(path.pathname);
+  }
+
+  return String(path);
+};
+
+const encodingOf = (options?: ReadOptions): string | null => {
+  if (typeof options === 'string') return options;
+  return options?.encoding ?? null;
+};
+
+const toContent = (bytes: Uint8Array, options?: ReadOptions): string | Buffer => {
+  const buffer = Buffer.from(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+  const encoding = encodingOf(options);
+
+  return encoding ? buffer.toString(encoding) : buffer;
+};
+
+const notFound = (syscall: string, path: string): Error => {
+  const err = new Error(`ENOENT: no such file or directory, ${syscall} '${path}'`);
+  return Object.assign(err, { code: 'ENOENT', errno: -2, syscall, path });
+};
+
+const mkdirOptions = (options?: MakeDirectoryOptions) => {
+  if (typeof options === 'number') return { recursive: false, mode: options };
+  return { recursive: options?.recursive ?? false, mode: options?.mode };
+};
+
+/**
+ * Node callbacks come last and the arguments before them are optional.
+ */
+const splitCallback = <T>(args: any[]): [any, Callback<T>] => {
+  const callback = args.pop();
+  if (typeof callback !== 'function') {
+    throw new TypeError('The "cb" argument must be of type function');
+  }
+
+  return [args[0], callback];
+};
+
+const withCallback = <T>(promise: Promise<T>, callback: Callback<T>) => {
+  promise.then(
+    (value) => callback(null, value),
+    (err) => callback(err),
+  );
+};
+
+const readFileSync = (path: string | URL, options?: ReadOptions) => {
+  return toContent(readBytesSync(toPath(path)), options);
+};
+
+const existsSyncCompat = (path: string | URL): boolean => {
+  try {
+    return existsSync(toPath(path));
+  } catch {
+    return false;
+  }
+};
+
+const accessSync = (path: string | URL, _mode?: number): void => {
+  const file = toPath(path);
+  if (!existsSync(file)) throw notFound('access', file);
+};
+
+const mkdirSync = (path: string | URL, options?: MakeDirectoryOptions): void => {
+  createDirSync(toPath(path), mkdirOptions(options));
+};
+
+const rmSync = (path: string | URL, options: RemoveOptions = {}): void => {
+  const file = toPath(path);
+  if (!existsSync(file)) {
+    if (options.force) return;
+    throw notFound('rm', file);
+  }
+
+  removeSync(file, { recursive: options.recursive ?? false });
+};
+
+const unlinkSync = (path: string | URL): void => {
+  rmSync(path);
+};
+
+const rmdirSync = (path: string | URL, options: { recursive?: boolean } = {}): void => {
+  rmSync(path, { recursive: options.recursive });
+};
+
+const promises = {
+  async readFile(path: string | URL, options?: ReadOptions) {
+    return toContent(await readBytes(toPath(path)), options);
+  },
+
+  async access(path: string | URL, mode?: number): Promise<void> {
+    accessSync(path, mode);
+  },
+
+  async mkdir(path: string | URL, options?: MakeDirectoryOptions): Promise<void> {
+    await createDir(toPath(path), mkdirOptions(options));
+  },
+
+  async rm(path: string | URL, options: RemoveOptions = {}): Promise<void> {
+    const file = toPath(path);
+    if (!existsSync(file)) {
+      if (options.force) return;
+      throw notFound('rm', file);
+    }
+
+    await remove(file, { recursive: options.recursive ?? false });
+  },
+
+  async unlink(path: string | URL): Promise<void> {
+    await promises.rm(path);
+  },
+
+  async rmdir(path: string | URL, options: { recursive?: boolean } = {}): Promise<void> {
+    await promises.rm(path, { recursive: options.recursive });
+  },
+};
+
+const readFile = (path: string | URL, ...args: any[]): void => {
+  const [options, callback] = splitCallback<string | Buffer>(args);
+  withCallback(promises.readFile(path, options), callback);
+};
+
+const access = (path: string | URL, ...args: any[]): void => {
+  const [mode, callback] = splitCallback(args);
+  withCallback(promises.access(path, mode), callback);
+};
+
+const exists = (path: string | URL, callback: (exists: boolean) => void): void => {
+  queueMicrotask(() => callback(existsSyncCompat(path)));
+};
+
+const mkdir = (path: string | URL, ...args: any[]): void => {
+  const [options, callback] = splitCallback(args);
+  withCallback(promises.mkdir(path, options), callback);
+};
+
+const rm = (path: string | URL, ...args: any[]): void => {
+  const [options, callback] = splitCallback(args);
+  withCallback(promises.rm(path, options), callback);
+};
+
+const unlink = (path: string | URL, callback: Callback): void => {
+  withCallback(promises.unlink(path), callback);
+};
+
+const rmdir = (path: string | URL, ...args: any[]): void => {
+  const [options, callback] = splitCallback(args);
+  withCallback(promises.rmdir(path, options), callback);
+};
+
+const constants = { F_OK: 0, R_OK: 4, W_OK: 2, X_OK: 1 };
+
+export {
+  access,
+  accessSync,
+  constants,
+  exists,
+  existsSyncCompat as existsSync,
+  mkdir,
+  mkdirSync,
+  promises,
+  readFile,
+  readFileSync,
+  rm,
+  rmSync,
+  rmdir,
+  rmdirSync,
+  unlink,
+  unlinkSync,
+};
+
+export default {
+  access,
+  accessSync,
+  constants,
+  exists,
+  existsSync: existsSyncCompat,
+  mkdir,
+  mkdirSync,
+  promises,
+  readFile,
+  readFileSync,
+  rm,
+  rmSync,
+  rmdir,
+  rmdirSync,
+  unlink,
+  unlinkSync,
+};
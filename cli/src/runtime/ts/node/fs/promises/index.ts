@@ -0,0 +1,7 @@
+import { promises } from 'node:fs';
+
+const { access, mkdir, readFile, rm, rmdir, unlink } = promises;
+
+export { access, mkdir, readFile, rm, rmdir, unlink };
+
+export default promises;
@@ -0,0 +1,70 @@
+const PLATFORMS: Record<string, string> = {
+  macos: 'darwin',
+  windows: 'win32',
+};
+
+const TYPES: Record<string, string> = {
+  linux: 'Linux',
+  macos: 'Darwin',
+  windows: 'Windows_NT',
+  freebsd: 'FreeBSD',
+  openbsd: 'OpenBSD',
+  netbsd: 'NetBSD',
+  solaris: 'SunOS',
+  android: 'Linux',
+};
+
+const ARCHS: Record<string, string> = {
+  x86_64: 'x64',
+  x86: 'ia32',
+  aarch64: 'arm64',
+  powerpc64: 'ppc64',
+  loongarch64: 'loong64',
+};
+
+const EOL = Ike.isWindows() ? '\r\n' : '\n';
+
+const devNull = Ike.isWindows() ? '\\\\.\\nul' : '/dev/null';
+
+const platform = (): string => PLATFORMS[Ike.os] ?? Ike.os;
+
+const type = (): string => TYPES[Ike.os] ?? Ike.os;
+
+const arch = (): string => ARCHS[Ike.arch] ?? Ike.arch;
+
+const endianness = (): 'BE' | 'LE' => {
+  const bytes = new Uint8Array(new Uint16Array([1]).buffer);
+  return bytes[0] === 1 ? 'LE' : 'BE';
+};
+
+const homedir = (): string => {
+  const home = Ike.isWindows() ? Ike.env.get('USERPROFILE') : Ike.env.get('HOME');
+  return home ?? '';
+};
+
+const tmpdir = (): string => {
+  if (Ike.isWindows()) {
+    const dir =
+      Ike.env.get('TEMP') ?? Ike.env.get('TMP') ?? `${Ike.env.get('SystemRoot') ?? 'C:\\Windows'}\\temp`;
+    return dir.length > 1 && dir.endsWith('\\') && !dir.endsWith(':\\') ? dir.slice(0, -1) : dir;
+  }
+
+  const dir = Ike.env.get('TMPDIR') ?? Ike.env.get('TMP') ?? Ike.env.get('TEMP') ?? '/tmp';
+  return dir.length > 1 && dir.endsWith('/') ? dir.slice(0, -1) : dir;
+};
+
+const userInfo = () => {
+  const username = Ike.env.get('USER') ?? Ike.env.get('USERNAME') ?? '';
+
+  return {
+    uid: Ike.uid() ?? -1,
+    gid: Ike.gid() ?? -1,
+    username,
+    homedir: homedir(),
+    shell: Ike.isWindows() ? null : (Ike.env.get('SHELL') ?? null),
+  };
+};
+
+export { EOL, arch, devNull, endianness, homedir, platform, tmpdir, type, userInfo };
+
+export default { EOL, arch, devNull, endianness, homedir, platform, tmpdir, type, userInfo };
@@ -0,0 +1,41 @@
+import path from '@std/path';
+
+// Ike normalizes paths to forward slashes on every platform, so both flavours share one implementation
+const posix = { ...path, sep: '/', delimiter: ':' };
+const win32 = { ...path, sep: '\\', delimiter: ';' };
+
+const {
+  basename,
+  delimiter,
+  dirname,
+  extname,
+  format,
+  isAbsolute,
+  join,
+  normalize,
+  parse,
+  relative,
+  resolve,
+  sep,
+  toNamespacedPath,
+} = path;
+
+export {
+  basename,
+  delimiter,
+  dirname,
+  extname,
+  format,
+  isAbsolute,
+  join,
+  normalize,
+  parse,
+  posix,
+  relative,
+  resolve,
+  sep,
+  toNamespacedPath,
+  win32,
+};
+
+export default { ...path, posix, win32 };
@@ -0,0 +1,91 @@
+import { EventEmitter } from 'node:events';
+import { arch, platform } from 'node:os';
+
+const version = `v${Ike.version}`;
+
+// there is no high resolution clock yet, times are measured from when the module was loaded
+const startTime = Date.now();
+
+class Process extends EventEmitter {
+  readonly env = Ike.env;
+  readonly argv = ['ike'];
+  readonly execArgv: string[] = [];
+  readonly pid = Ike.pid;
+  readonly platform = platform();
+  readonly arch = arch();
+  readonly version = version;
+  readonly versions = { ike: Ike.version };
+  readonly release = { name: 'ike' };
+
+  get exitCode(): number {
+    return Ike.exitCode;
+  }
+
+  set exitCode(code: number) {
+    Ike.setExitCode(code);
+  }
+
+  cwd(): string {
+    return Ike.cwd();
+  }
+
+  exit(code?: number): never {
+    const exitCode = code ?? Ike.exitCode;
+    this.emit('exit', exitCode);
+    return Ike.exit(exitCode);
+  }
+
+  getuid(): number {
+    return Ike.uid() ?? -1;
+  }
+
+  getgid(): number {
+    return Ike.gid() ?? -1;
+  }
+
+  nextTick(callback: (...args: any[]) => void, ...args: any[]): void {
+    queueMicrotask(() => callback(...args));
+  }
+
+  emitWarning(warning: string | Error): void {
+    const message = warning instanceof Error ? `${warning.name}: ${warning.message}` : `Warning: ${warning}`;
+    console.warn(message);
+  }
+
+  hrtime(previous?: [number, number]): [number, number] {
+    const now = Date.now() - startTime;
+    let seconds = Math.floor(now / 1e3);
+    let nanoseconds = Math.floor((now % 1e3) * 1e6);
+
+    if (previous) {
+      seconds -= previous[0];
+      nanoseconds -= previous[1];
+      if (nanoseconds < 0) {
+        seconds -= 1;
+        nanoseconds += 1e9;
+      }
+    }
+
+    return [seconds, nanoseconds];
+  }
+
+  uptime(): number {
+    return (Date.now() - startTime) / 1e3;
+  }
+}
+
+const process = new Process();
+
+// `process.hrtime.bigint()` is a property of the method, like in Node
+Object.assign(process.hrtime, {
+  bigint: () => BigInt(Date.now() - startTime) * 1_000_000n,
+});
+
+const { env, argv, pid } = process;
+const cwd = process.cwd.bind(process);
+const exit = process.exit.bind(process);
+const nextTick = process.nextTick.bind(process);
+
+export { argv, cwd, env, exit, nextTick, pid, process };
+
+export default process;
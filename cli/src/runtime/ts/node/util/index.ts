@@ -0,0 +1,171 @@
+import { format, inspect } from '@std/inspect';
+import {
+  isArrayBuffer,
+  isDataView,
+  isSharedArrayBuffer,
+  isTypedArray,
+} from '@std/_internal_';
+
+const kCustomPromisify = Symbol.for('nodejs.util.promisify.custom');
+
+type Callback = (err: unknown, value?: any) => void;
+
+const promisify = (fn: (...args: any[]) => void): ((...args: any[]) => Promise<any>) => {
+  if (typeof fn !== 'function') {
+    throw new TypeError('The "original" argument must be of type function');
+  }
+
+  const custom = (fn as any)[kCustomPromisify];
+  if (typeof custom === 'function') return custom;
+
+  return function (this: unknown, ...args: any[]) {
+    return new Promise((resolve, reject) => {
+      fn.call(this, ...args, (err: unknown, value: unknown) => {
+        if (err) reject(err);
+        else resolve(value);
+      });
+    });
+  };
+};
+
+promisify.custom = kCustomPromisify;
+
+const callbackify = (fn: (...args: any[]) => Promise<any>): ((...args: any[]) => void) => {
+  if (typeof fn !== 'function') {
+    throw new TypeError('The "original" argument must be of type function');
+  }
+
+  return function (this: unknown, ...args: any[]) {
+    const callback = args.pop() as Callback;
+    if (typeof callback !== 'function') {
+      throw new TypeError('The last argument must be of type function');
+    }
+
+    fn.apply(this, args).then(
+      (value) => queueMicrotask(() => callback(null, value)),
+      (err) => queueMicrotask(() => callback(err ?? new Error('Promise was rejected with a falsy value'))),
+    );
+  };
+};
+
+const deprecate = <T extends (...args: any[]) => any>(fn: T, message: string): T => {
+  let warned = false;
+
+  return function (this: unknown, ...args: any[]) {
+    if (!warned) {
+      warned = true;
+      console.warn(`DeprecationWarning: ${message}`);
+    }
+    return fn.apply(this, args);
+  } as T;
+};
+
+const inherits = (ctor: Function, superCtor: Function): void => {
+  Object.defineProperty(ctor, 'super_', {
+    value: superCtor,
+    writable: true,
+    configurable: true,
+  });
+  Object.setPrototypeOf(ctor.prototype, superCtor.prototype);
+};
+
+const isDeepStrictEqual = (a: unknown, b: unknown): boolean => {
+  if (Object.is(a, b)) return true;
+  if (typeof a !== 'object' || typeof b !== 'object' || a === null || b === null) {
+    return false;
+  }
+  if (Object.getPrototypeOf(a) !== Object.getPrototypeOf(b)) return false;
+
+  if (a instanceof Date) return a.getTime() === (b as Date).getTime();
+  if (a instanceof RegExp) return String(a) === String(b);
+  if (a instanceof Map) {
+    const other = b as Map<unknown, unknown>;
+    if (a.size !== other.size) return false;
+    for (const [key, value] of a) {
+      if (!other.has(key) || !isDeepStrictEqual(value, other.get(key))) return false;
+    }
+    return true;
+  }
+  if (a instanceof Set) {
+    const other = b as Set<unknown>;
+    return a.size === other.size && [...a].every((value) => other.has(value));
+  }
+
+  const keysA = Reflect.ownKeys(a);
+  const keysB = Reflect.ownKeys(b);
+  if (keysA.length !== keysB.length) return false;
+
+  return keysA.every(
+    (key) => keysB.includes(key) && isDeepStrictEqual((a as any)[key], (b as any)[key]),
+  );
+};
+
+const toTag = (value: unknown) => Object.prototype.toString.call(value);
+
+const types = {
+  isArrayBuffer,
+  isSharedArrayBuffer,
+  isDataView,
+  isTypedArray,
+  isAnyArrayBuffer: (value: unknown) => isArrayBuffer(value) || isSharedArrayBuffer(value),
+  isArrayBufferView: (value: unknown) => ArrayBuffer.isView(value),
+  isUint8Array: (value: unknown) => value instanceof Uint8Array,
+  isPromise: (value: unknown) => value instanceof Promise,
+  isDate: (value: unknown) => value instanceof Date,
+  isRegExp: (value: unknown) => value instanceof RegExp,
+  isMap: (value: unknown) => value instanceof Map,
+  isSet: (value: unknown) => value instanceof Set,
+  isWeakMap: (value: unknown) => value instanceof WeakMap,
+  isWeakSet: (value: unknown) => value instanceof WeakSet,
+  isNativeError: (value: unknown) => value instanceof Error,
+  isAsyncFunction: (value: unknown) => toTag(value) === '[object AsyncFunction]',
+  isGeneratorFunction: (value: unknown) =>
+    toTag(value) === '[object GeneratorFunction]' ||
+    toTag(value) === '[object AsyncGeneratorFunction]',
+  isBoxedPrimitive: (value: unknown) =>
+    value instanceof Number ||
+    value instanceof String ||
+    value instanceof Boolean ||
+    value instanceof BigInt ||
+    value instanceof Symbol,
+};
+
+const debuglog = (section: string): ((...args: any[]) => void) => {
+  const enabled = (Ike.env.get('NODE_DEBUG') ?? '')
+    .split(',')
+    .some((name) => name.trim().toLowerCase() === section.toLowerCase());
+
+  return (...args: any[]) => {
+    if (enabled) console.error(`${section.toUpperCase()} ${Ike.pid}: ${format(...args)}`);
+  };
+};
+
+const isArray = Array.isArray;
+
+export {
+  callbackify,
+  debuglog,
+  deprecate,
+  format,
+  inherits,
+  inspect,
+  isArray,
+  isDeepStrictEqual,
+  promisify,
+  types,
+};
+
+export default {
+  callbackify,
+  debuglog,
+  deprecate,
+  format,
+  inherits,
+  inspect,
+  isArray,
+  isDeepStrictEqual,
+  promisify,
+  types,
+  TextDecoder,
+  TextEncoder,
+};
@@ -0,0 +1,241 @@
+use crate::{cli::cli::Cli, globals::VERSION, runtime::runtime::start_runtime_with_source};
+use ike_core::permissions::{set_permissions, PermissionKind, Permissions};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+use thiserror::Error;
+
+/// Marks an ike binary with an embedded program. The trailer is the magic followed by
+/// the length of the payload before it, as a little endian u64.
+const MAGIC: &[u8; 16] = b"ike-standalone-1";
+const TRAILER_LEN: usize = MAGIC.len() + 8;
+
+#[derive(Debug, Error)]
+pub enum StandaloneError {
+    #[error("Failed to read the ike executable: {0}")]
+    FailedToReadExecutable(String),
+    #[error("Failed to write <cyan>{0}<r>: {1}")]
+    FailedToWrite(String, String),
+    #[error("The embedded program is corrupted: {0}")]
+    InvalidPayload(String),
+}
+
+/// ike.toml metadata and the flags the program was compiled with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Metadata {
+    pub name: String,
+    pub version: String,
+    /// Permission flags, e.g. `--allow-read=/tmp`, parsed like the ones of `ike run`.
+    pub permissions: Vec<String>,
+    /// Version of ike the program was compiled with.
+    pub ike_version: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Standalone {
+    pub metadata: Metadata,
+    /// The bundled program.
+    pub code: String,
+}
+
+static METADATA: OnceLock<Metadata> = OnceLock::new();
+
+/// Metadata of the running program when it's a compiled executable.
+pub fn metadata() -> Option<&'static Metadata> {
+    METADATA.get()
+}
+
+impl Standalone {
+    /// Reads the program embedded in the current executable, if there's one.
+    pub fn find() -> Result<Option<Self>, StandaloneError> {
+        let exe = std::env::current_exe()
+            .map_err(|err| StandaloneError::FailedToReadExecutable(err.to_string()))?;
+        let mut file = File::open(&exe)
+            .map_err(|err| StandaloneError::FailedToReadExecutable(err.to_string()))?;
+
+        let Some(payload_len) = payload_len(&mut file)
+            .map_err(|err| StandaloneError::FailedToReadExecutable(err.to_string()))?
+        else {
+            return Ok(None);
+        };
+
+        let mut payload = vec![0; payload_len as usize];
+        file.seek(SeekFrom::End(-((TRAILER_LEN as u64 + payload_len) as i64)))
+            .and_then(|_| file.read_exact(&mut payload))
+            .map_err(|err| StandaloneError::InvalidPayload(err.to_string()))?;
+
+        let standalone = serde_json::from_slice(&payload)
+            .map_err(|err| StandaloneError::InvalidPayload(err.to_string()))?;
+
+        Ok(Some(standalone))
+    }
+
+    /// Copies the ike executable to `output` with this program appended to it.
+    pub fn write(&self, output: &Path) -> Result<(), StandaloneError> {
+        let exe = std::env::current_exe()
+            .map_err(|err| StandaloneError::FailedToReadExecutable(err.to_string()))?;
+        let mut binary = std::fs::read(&exe)
+            .map_err(|err| StandaloneError::FailedToReadExecutable(err.to_string()))?;
+
+        // Compiling from a compiled program shouldn't stack payloads
+        let mut file = File::open(&exe)
+            .map_err(|err| StandaloneError::FailedToReadExecutable(err.to_string()))?;
+        if let Ok(Some(payload_len)) = payload_len(&mut file) {
+            binary.truncate(binary.len() - TRAILER_LEN - payload_len as usize);
+        }
+
+        let payload = serde_json::to_vec(self).unwrap();
+        binary.extend_from_slice(&payload);
+        binary.extend_from_slice(MAGIC);
+        binary.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+
+        let failed = |err: std::io::Error| {
+            StandaloneError::FailedToWrite(output.display().to_string(), err.to_string())
+        };
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent).map_err(failed)?;
+        }
+        std::fs::write(output, &binary).map_err(failed)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(output, std::fs::Permissions::from_mode(0o755))
+                .map_err(failed)?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs the embedded program with the permissions it was compiled with.
+    pub fn run(self) -> anyhow::Result<()> {
+        let matches = clap::Command::new(self.metadata.name.clone())
+            .no_binary_name(true)
+            .args(Cli::permission_args())
+            .try_get_matches_from(&self.metadata.permissions)
+            .map_err(|err| StandaloneError::InvalidPayload(err.to_string()))?;
+        set_permissions(Cli::new().parse_permissions(&matches));
+
+        // Named after the program, next to the executable
+        let exe = std::env::current_exe()?;
+        let entry = exe
+            .parent()
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(format!("{}.js", self.metadata.name));
+
+        let _ = METADATA.set(self.metadata);
+        start_runtime_with_source(&entry, Some(&self.code), None)?;
+
+        Ok(())
+    }
+}
+
+impl Metadata {
+    pub fn new(name: String, version: String, permissions: &Permissions) -> Self {
+        Self {
+            name,
+            version,
+            permissions: permission_flags(permissions),
+            ike_version: VERSION.to_string(),
+        }
+    }
+}
+
+/// Flags that grant `permissions` when parsed again.
+fn permission_flags(permissions: &Permissions) -> Vec<String> {
+    let mut flags = Vec::new();
+
+    for kind in PermissionKind::ALL {
+        let permission = permissions.get(kind);
+        if permission.granted_all {
+            flags.push(kind.flag().to_string());
+        }
+
+        let mut granted: Vec<&String> = permission.granted.iter().collect();
+        granted.sort();
+        for resource in granted {
+            flags.push(format!("{}={resource}", kind.flag()));
+        }
+    }
+    if !permissions.prompt {
+        flags.push("--no-prompt".to_string());
+    }
+
+    flags
+}
+
+/// Length of the payload when `file` ends with a trailer.
+fn payload_len(file: &mut File) -> std::io::Result<Option<u64>> {
+    let len = file.metadata()?.len();
+    if len < TRAILER_LEN as u64 {
+        return Ok(None);
+    }
+
+    let mut trailer = [0; TRAILER_LEN];
+    file.seek(SeekFrom::End(-(TRAILER_LEN as i64)))?;
+    file.read_exact(&mut trailer)?;
+
+    if &trailer[..MAGIC.len()] != MAGIC {
+        return Ok(None);
+    }
+
+    let payload_len = u64::from_le_bytes(trailer[MAGIC.len()..].try_into().unwrap());
+    if payload_len > len - TRAILER_LEN as u64 {
+        return Ok(None);
+    }
+
+    Ok(Some(payload_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_flags() {
+        let mut permissions = Permissions::default();
+        permissions.grant(PermissionKind::Net, None);
+        permissions.grant(PermissionKind::Env, Some("HOME"));
+        permissions.prompt = false;
+
+        let flags = permission_flags(&permissions);
+        assert_eq!(
+            flags,
+            vec!["--allow-net", "--allow-env=HOME", "--no-prompt"]
+        );
+
+        let matches = clap::Command::new("tool")
+            .no_binary_name(true)
+            .args(Cli::permission_args())
+            .try_get_matches_from(&flags)
+            .unwrap();
+        let parsed = Cli::new().parse_permissions(&matches);
+        assert!(parsed.net.granted_all);
+        assert!(parsed.env.granted.contains("HOME"));
+        assert!(!parsed.prompt);
+    }
+
+    #[test]
+    fn test_payload_len() {
+        let path = std::env::temp_dir().join(format!("ike-standalone-{}", std::process::id()));
+        let mut content = b"binary".to_vec();
+        content.extend_from_slice(b"{}");
+        content.extend_from_slice(MAGIC);
+        content.extend_from_slice(&2u64.to_le_bytes());
+        std::fs::write(&path, &content).unwrap();
+
+        let mut file = File::open(&path).unwrap();
+        assert_eq!(payload_len(&mut file).unwrap(), Some(2));
+
+        std::fs::write(&path, b"binary").unwrap();
+        let mut file = File::open(&path).unwrap();
+        assert_eq!(payload_len(&mut file).unwrap(), None);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
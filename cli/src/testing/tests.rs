@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     path::{Path, PathBuf},
     rc::Rc,
     time::Instant,
@@ -9,23 +9,24 @@ use boa_engine::{
     builtins::promise::PromiseState,
     js_string,
     object::builtins::{JsArray, JsFunction},
-    Context, JsNativeError, JsResult, JsValue, Module, Source,
+    Context, JsResult, JsValue, Module, Source,
 };
-use ike_core::{get_prototype_name, js_str_to_string, throw};
 use smol::LocalExecutor;
 
-use ike_logger::{cond_log, log, new_line, print_indent, Logger};
+use ike_core::permissions::{get_permissions, set_permissions};
+use ike_logger::{log, new_line, print_indent, Logger};
 
 use crate::{
     cli::run_command::Entry,
     format::format_time,
     runtime::{
+        errors::{print_error, transpile_error},
         modules::IkeModuleLoader,
         queue::Queue,
-        runtime::{setup_context, update_meta_property},
+        runtime::{get_current_path, setup_context, update_meta_property},
     },
 };
-use crate::{runtime::runtime::evaulte_module, transpiler::transpile};
+use crate::{runtime::runtime::evaulte_module, transpiler::transpile_file};
 use crate::{runtime::runtime::load_modules, utils::compare_paths};
 
 lazy_static::lazy_static! {
@@ -82,6 +83,7 @@ pub fn run_tests(paths: Vec<PathBuf>, root: PathBuf) -> JsResult<()> {
         .build()
         .unwrap();
 
+    let source_maps = module_loader.source_maps();
     load_modules(ctx, module_loader)?;
     setup_context(ctx, None);
     let start_time = Instant::now();
@@ -95,16 +97,28 @@ pub fn run_tests(paths: Vec<PathBuf>, root: PathBuf) -> JsResult<()> {
 
     evaulte_module(ctx, script_module)?;
 
+    // All files share the process, so every file starts with the permissions the runner
+    // was started with and revocations don't leak into the files that run after it
+    let permissions = get_permissions();
+    let mut permissions_by_file: HashMap<String, _> = HashMap::new();
+
     for path in paths {
         let entry = Entry::new(true, Some(path.clone()), None);
         update_meta_property(ctx, &entry.path.clone().unwrap());
         let path = entry.path.unwrap().as_path().to_path_buf();
 
-        let transpiled = match transpile(&path) {
-            Ok(transpiler) => transpiler,
-            Err(e) => throw!(typ, "Failed to transpile: {:?}", e),
+        let transpiled = match transpile_file(&path) {
+            Ok(transpiled) => transpiled,
+            Err(err) => {
+                let err = transpile_error(err, ctx).to_opaque(ctx);
+                print_error(&err, "error", 0, ctx);
+                results.failed += 1;
+                results.files += 1;
+                continue;
+            }
         };
-        let reader = Source::from_bytes(transpiled.as_bytes()).with_path(&Path::new(&path));
+        let code = source_maps.prepare(&path, &transpiled);
+        let reader = Source::from_bytes(code.as_bytes()).with_path(&Path::new(&path));
         let module = Module::parse(reader, None, ctx)?;
         let promise = module.load_link_evaluate(ctx);
 
@@ -115,27 +129,15 @@ pub fn run_tests(paths: Vec<PathBuf>, root: PathBuf) -> JsResult<()> {
             PromiseState::Fulfilled(v) => {
                 assert_eq!(v, JsValue::undefined())
             }
-            PromiseState::Rejected(err) => {
-                let obj = err.to_object(ctx).unwrap();
-                let proto = match obj.prototype() {
-                    Some(proto) => proto,
-                    None => {
-                        panic!("Error object has no prototype");
-                    }
-                };
-                let str_name = get_prototype_name!(proto, ctx);
-
-                let message = obj.get(js_string!("message"), ctx).unwrap();
-                cond_log!(
-                    true,
-                    true,
-                    "<r><red>error<r><d>({})<r>: {}",
-                    str_name,
-                    js_str_to_string!(message.to_string(ctx).unwrap())
-                );
-            }
+            PromiseState::Rejected(err) => print_error(&err, "error", 0, ctx),
         }
 
+        let current_path = get_current_path(ctx)
+            .to_string(ctx)?
+            .to_std_string_escaped();
+        permissions_by_file.insert(current_path, get_permissions());
+        set_permissions(permissions.clone());
+
         results.files += 1;
     }
 
@@ -184,7 +186,7 @@ pub fn run_tests(paths: Vec<PathBuf>, root: PathBuf) -> JsResult<()> {
             .push(test_val);
     }
 
-    let mut tests_by_files: HashMap<String, HashMap<String, Vec<JsValue>>> = HashMap::new();
+    let mut tests_by_files: BTreeMap<String, BTreeMap<String, Vec<JsValue>>> = BTreeMap::new();
 
     for (path_str, alone_tests) in alone_tests_by_file {
         tests_by_files
@@ -204,6 +206,11 @@ pub fn run_tests(paths: Vec<PathBuf>, root: PathBuf) -> JsResult<()> {
         let path_buf = PathBuf::from(path_str.clone());
         let path = strip_prefix_from_path(root.clone(), path_buf.clone());
         log!("{} <r><d>{}<r>", ICONS["skip"], path.display());
+        set_permissions(
+            permissions_by_file
+                .remove(&path_str)
+                .unwrap_or_else(|| permissions.clone()),
+        );
 
         if !global_before_all_val.is_undefined() {
             let global_before_all =
@@ -428,24 +435,6 @@ pub fn run_single_test(test: JsValue, ctx: &mut Context, results: &mut TestResul
 
     if status.eq("fail") {
         let error = result_obj.get(js_string!("error"), ctx).unwrap();
-        let error = error.as_object().unwrap();
-
-        let proto = match error.prototype() {
-            Some(proto) => proto,
-            None => {
-                panic!("No prototype found for test result object");
-            }
-        };
-        let str_name = get_prototype_name!(proto, ctx);
-
-        let message = error.get(js_string!("message"), ctx).unwrap();
-        cond_log!(
-            true,
-            true,
-            "{}<r><red>└─ error<r><d>({})<r>: {}",
-            "  ".repeat(indent),
-            str_name,
-            js_str_to_string!(message.to_string(ctx).unwrap())
-        );
+        print_error(&error, "└─ error", indent, ctx);
     }
 }
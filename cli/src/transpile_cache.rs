@@ -0,0 +1,152 @@
+use crate::{cache::cache_dir, globals::VERSION, transpiler::Transpiled};
+use oxc_sourcemap::SourceMap;
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Transpiled modules stored on disk, so unchanged files skip the parser and transformer.
+///
+/// Entries are keyed by the hash of the source, the path (it ends up in the source map),
+/// the transpiler options and the ike version, so they never have to be invalidated.
+#[derive(Debug, Clone)]
+pub struct TranspileCache {
+    dir: PathBuf,
+}
+
+impl Default for TranspileCache {
+    fn default() -> Self {
+        Self::new(cache_dir().join("transpiled"))
+    }
+}
+
+impl TranspileCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn key(&self, path: &Path, source_text: &str, options: &str) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            VERSION.as_bytes(),
+            options.as_bytes(),
+            path.to_string_lossy().as_bytes(),
+            source_text.as_bytes(),
+        ] {
+            // Length prefixed, so moving bytes between parts changes the key
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part);
+        }
+
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<Transpiled> {
+        let entry = self.entry(key);
+        let code = std::fs::read_to_string(&entry).ok()?;
+        let source_map = match std::fs::read_to_string(entry.with_extension("js.map")) {
+            Ok(json) => Some(SourceMap::from_json_string(&json).ok()?),
+            Err(_) => None,
+        };
+
+        Some(Transpiled { code, source_map })
+    }
+
+    pub fn set(&self, key: &str, transpiled: &Transpiled) -> std::io::Result<()> {
+        let entry = self.entry(key);
+        std::fs::create_dir_all(&self.dir)?;
+
+        // The map is written first, an entry without code is never read
+        if let Some(source_map) = &transpiled.source_map {
+            let json = source_map
+                .to_json_string()
+                .map_err(|err| std::io::Error::other(err.to_string()))?;
+            write_atomic(&entry.with_extension("js.map"), json.as_bytes())?;
+        }
+
+        write_atomic(&entry, transpiled.code.as_bytes())
+    }
+
+    /// Removes every entry, returns how many modules were cached.
+    pub fn clean(&self) -> std::io::Result<usize> {
+        if !self.dir.exists() {
+            return Ok(0);
+        }
+
+        let count = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "js"))
+            .count();
+        std::fs::remove_dir_all(&self.dir)?;
+
+        Ok(count)
+    }
+
+    fn entry(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.js"))
+    }
+}
+
+/// Writes through a temporary file in the same directory, so processes sharing the
+/// cache never read a partially written entry.
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp = path.with_file_name(format!(
+        ".{name}.{}.{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    std::fs::write(&temp, contents)?;
+    std::fs::rename(&temp, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transpiler::transpile_source;
+
+    #[test]
+    fn test_transpile_cache() {
+        let dir = std::env::temp_dir().join(format!("ike-transpile-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let cache = TranspileCache::new(dir.clone());
+
+        let path = Path::new("/app/main.ts");
+        let source = "export const answer: number = 42;\n";
+        let key = cache.key(path, source, "");
+        assert_ne!(key, cache.key(path, "export const answer = 41;\n", ""));
+        assert_ne!(key, cache.key(Path::new("/app/other.ts"), source, ""));
+        assert_ne!(key, cache.key(path, source, "jsx=classic"));
+        assert!(cache.get(&key).is_none());
+
+        let transpiled = transpile_source(path, source).unwrap();
+        cache.set(&key, &transpiled).unwrap();
+        // Overwriting an entry leaves no temporary files behind
+        cache.set(&key, &transpiled).unwrap();
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+
+        let cached = cache.get(&key).unwrap();
+        assert_eq!(cached.code, transpiled.code);
+        assert_eq!(
+            cached.source_map.unwrap().to_json_string().unwrap(),
+            transpiled.source_map.unwrap().to_json_string().unwrap()
+        );
+
+        assert_eq!(cache.clean().unwrap(), 1);
+        assert!(cache.get(&key).is_none());
+    }
+}
@@ -1,31 +1,139 @@
+use crate::runtime::data_module::IMPORT_TYPE_PREFIX;
+use crate::transpile_cache::TranspileCache;
 use anyhow::Result;
 use ike_fs::read_to_string;
 use ike_fs::FsError::FailedToReadFileWithError;
+use ike_toml::{CompilerConfig, JsxRuntime};
 use oxc_allocator::Allocator;
+use oxc_ast::{
+    ast::{
+        Expression, ImportAttributeKey, ImportExpression, ObjectExpression, ObjectPropertyKind,
+        Program, PropertyKey, Statement, WithClause,
+    },
+    visit::walk_mut,
+    VisitMut,
+};
 use oxc_codegen::CodeGenerator;
+use oxc_diagnostics::OxcDiagnostic;
 use oxc_parser::Parser;
-use oxc_span::SourceType;
+use oxc_sourcemap::SourceMap;
+use oxc_span::{Atom, SourceType};
 use oxc_transformer::{
-    ArrowFunctionsOptions, ES2015Options, ReactOptions, TransformOptions, Transformer,
-    TypeScriptOptions,
+    ArrowFunctionsOptions, ES2015Options, ReactJsxRuntime, ReactOptions, TransformOptions,
+    Transformer, TypeScriptOptions,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
 };
-use std::path::PathBuf;
+use thiserror::Error;
 
-pub fn transpile_with_text(path: &PathBuf, source_text: String) -> Result<String> {
-    let allocator = Allocator::default();
-    let source_type = SourceType::from_path(path).unwrap();
+/// Lines of source shown above and below the error in a code frame.
+const CODE_FRAME_CONTEXT: usize = 2;
+
+/// Parse error of a module, pointing at the offending code.
+#[derive(Debug, Clone, Error)]
+#[error("{message} ({}:{line}:{column})", file.display())]
+pub struct SyntaxError {
+    pub file: PathBuf,
+    pub message: String,
+    /// One based line of the error.
+    pub line: usize,
+    /// One based column of the error, in characters.
+    pub column: usize,
+    /// Plain text excerpt of the source with the error underlined.
+    pub code_frame: String,
+}
+
+impl SyntaxError {
+    pub fn new(file: &Path, source_text: &str, error: OxcDiagnostic) -> Self {
+        let report = error.with_source_code(source_text.to_string());
+        let (offset, length) = report
+            .labels()
+            .and_then(|mut labels| labels.next())
+            .map(|label| (label.offset(), label.len()))
+            .unwrap_or_default();
+        let mut message = report.to_string();
+        if let Some(help) = report.help() {
+            message = format!("{message}, {help}");
+        }
 
-    let ret = Parser::new(&allocator, &source_text, source_type).parse();
+        let (line, column) = line_column(source_text, offset);
 
-    if !ret.errors.is_empty() {
-        for error in ret.errors {
-            let error = error.with_source_code(source_text.clone());
-            println!("{error:?}");
+        Self {
+            file: file.to_path_buf(),
+            message,
+            line,
+            column,
+            code_frame: code_frame(source_text, line, column, length),
         }
     }
+}
 
-    let mut program = ret.program;
-    let transform_options = TransformOptions {
+/// A module with an extension the transpiler doesn't know, e.g. `import './styles.css'`.
+#[derive(Debug, Clone, Error)]
+#[error(
+    "Unsupported file type: {} is not a JavaScript or TypeScript module, import it with a type like `with {{ type: \"text\" }}`",
+    .0.display()
+)]
+pub struct UnsupportedFileType(pub PathBuf);
+
+/// One based line and column, in characters, of a byte `offset` in `source_text`.
+pub fn line_column(source_text: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(source_text.len());
+    let before = &source_text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    let column = source_text[line_start..offset].chars().count() + 1;
+
+    (line, column)
+}
+
+/// Excerpt of `source_text` around a one based `line`, with `length` characters from
+/// `column` underlined.
+pub fn code_frame(source_text: &str, line: usize, column: usize, length: usize) -> String {
+    let lines: Vec<&str> = source_text.lines().collect();
+    let first = line.saturating_sub(CODE_FRAME_CONTEXT).max(1);
+    let last = (line + CODE_FRAME_CONTEXT).min(lines.len().max(line));
+    let width = last.to_string().len();
+
+    let mut frame = Vec::new();
+    for number in first..=last {
+        let text = lines.get(number - 1).copied().unwrap_or_default();
+        let marker = if number == line { '>' } else { ' ' };
+        frame.push(format!("{marker} {number:>width$} | {text}"));
+
+        if number == line {
+            let remaining = text.chars().count().saturating_sub(column - 1).max(1);
+            let underline = "^".repeat(length.clamp(1, remaining));
+            frame.push(format!(
+                "  {:>width$} | {}{underline}",
+                "",
+                " ".repeat(column - 1)
+            ));
+        }
+    }
+
+    frame.join("\n")
+}
+
+/// Identifies the transform options of [`transpile_source`] in transpile cache keys,
+/// change it whenever the defaults of [`transform_options`] change.
+const OPTIONS_KEY: &str = "typescript;arrow-functions;jsx;jsx-self;import-attributes";
+
+static COMPILER_CONFIG: LazyLock<Mutex<CompilerConfig>> = LazyLock::new(Default::default);
+
+/// Sets the `[compiler]` options of ike.toml for every module transpiled afterwards.
+pub fn set_compiler_config(config: CompilerConfig) {
+    *COMPILER_CONFIG.lock().unwrap() = config;
+}
+
+pub fn compiler_config() -> CompilerConfig {
+    COMPILER_CONFIG.lock().unwrap().clone()
+}
+
+fn transform_options(config: &CompilerConfig) -> TransformOptions {
+    let mut options = TransformOptions {
         typescript: TypeScriptOptions::default(),
         es2015: ES2015Options {
             arrow_function: Some(ArrowFunctionsOptions::default()),
@@ -38,27 +146,309 @@ pub fn transpile_with_text(path: &PathBuf, source_text: String) -> Result<String
         },
         ..Default::default()
     };
+
+    if let Some(runtime) = config.jsx {
+        options.react.runtime = match runtime {
+            JsxRuntime::Classic => ReactJsxRuntime::Classic,
+            JsxRuntime::Automatic => ReactJsxRuntime::Automatic,
+        };
+    }
+    options.react.import_source = config.jsx_import_source.clone();
+    options.react.pragma = config.jsx_pragma.clone();
+    options.react.pragma_frag = config.jsx_pragma_frag.clone();
+
+    // The typescript transform keeps imports used by the pragma alive
+    if let Some(pragma) = &config.jsx_pragma {
+        options.typescript.jsx_pragma = pragma.clone().into();
+    }
+    if let Some(pragma_frag) = &config.jsx_pragma_frag {
+        options.typescript.jsx_pragma_frag = pragma_frag.clone().into();
+    }
+
+    // Arrow functions are lowered by default, every supported target runs them
+    if config.target.is_some() {
+        options.es2015.arrow_function = None;
+    }
+
+    options
+}
+
+/// Output of the transpiler, with a source map pointing back at the original source.
+pub struct Transpiled {
+    pub code: String,
+    pub source_map: Option<SourceMap>,
+}
+
+pub fn transpile_source(path: &Path, source_text: &str) -> Result<Transpiled> {
+    transpile_source_with(path, source_text, &compiler_config())
+}
+
+pub fn transpile_source_with(
+    path: &Path,
+    source_text: &str,
+    config: &CompilerConfig,
+) -> Result<Transpiled> {
+    let allocator = Allocator::default();
+    let source_type =
+        SourceType::from_path(path).map_err(|_| UnsupportedFileType(path.to_path_buf()))?;
+
+    let ret = Parser::new(&allocator, source_text, source_type).parse();
+
+    // Transforming a broken program only produces more confusing errors
+    if let Some(error) = ret.errors.into_iter().next() {
+        return Err(SyntaxError::new(path, source_text, error).into());
+    }
+
+    let mut program = ret.program;
+    rewrite_import_attributes(&mut program, &allocator);
+
+    let transform_options = transform_options(config);
     let _ = Transformer::new(
         &allocator,
         path,
         source_type,
-        &source_text,
+        source_text,
         ret.trivias.clone(),
         transform_options,
     )
     .build(&mut program);
-    let printed = CodeGenerator::new().build(&program).source_text;
+    let printed = CodeGenerator::new()
+        .enable_source_map(&path.to_string_lossy(), source_text)
+        .build(&program);
+
+    Ok(Transpiled {
+        code: printed.source_text,
+        source_map: printed.source_map,
+    })
+}
+
+pub fn transpile_with_text(path: &PathBuf, source_text: String) -> Result<String> {
+    Ok(transpile_source(path, &source_text)?.code)
+}
 
-    Ok(printed)
+/// Moves the `type` import attribute into the specifier, see [`IMPORT_TYPE_PREFIX`].
+/// Dynamic imports are rewritten too when their specifier is a string literal.
+fn rewrite_import_attributes<'a>(program: &mut Program<'a>, allocator: &'a Allocator) {
+    DynamicImportAttributes { allocator }.visit_program(program);
+
+    for statement in program.body.iter_mut() {
+        let (source, with_clause) = match statement {
+            Statement::ImportDeclaration(decl) => {
+                let decl = &mut **decl;
+                (&mut decl.source, &mut decl.with_clause)
+            }
+            Statement::ExportAllDeclaration(decl) => {
+                let decl = &mut **decl;
+                (&mut decl.source, &mut decl.with_clause)
+            }
+            Statement::ExportNamedDeclaration(decl) => {
+                let decl = &mut **decl;
+                match decl.source.as_mut() {
+                    Some(source) => (source, &mut decl.with_clause),
+                    None => continue,
+                }
+            }
+            _ => continue,
+        };
+
+        let Some(import_type) = with_clause.as_ref().and_then(import_type) else {
+            continue;
+        };
+        let specifier = format!("{IMPORT_TYPE_PREFIX}{import_type}:{}", source.value);
+        source.value = Atom::from(&*allocator.alloc_str(&specifier));
+        *with_clause = None;
+    }
+}
+
+struct DynamicImportAttributes<'a> {
+    allocator: &'a Allocator,
+}
+
+impl<'a> VisitMut<'a> for DynamicImportAttributes<'a> {
+    fn visit_import_expression(&mut self, expr: &mut ImportExpression<'a>) {
+        let import_type = expr.arguments.first().and_then(dynamic_import_type);
+
+        if let (Expression::StringLiteral(source), Some(import_type)) =
+            (&mut expr.source, import_type)
+        {
+            let specifier = format!("{IMPORT_TYPE_PREFIX}{import_type}:{}", source.value);
+            source.value = Atom::from(&*self.allocator.alloc_str(&specifier));
+            expr.arguments.clear();
+        }
+
+        walk_mut::walk_import_expression(self, expr);
+    }
+}
+
+/// `type` of the options of a dynamic import, `import(url, { with: { type: "json" } })`.
+fn dynamic_import_type(options: &Expression) -> Option<String> {
+    let Expression::ObjectExpression(options) = options else {
+        return None;
+    };
+    let Expression::ObjectExpression(with) = object_property(options, "with")? else {
+        return None;
+    };
+
+    match object_property(with, "type")? {
+        Expression::StringLiteral(literal) => Some(literal.value.to_string()),
+        _ => None,
+    }
+}
+
+fn object_property<'b, 'a>(
+    object: &'b ObjectExpression<'a>,
+    name: &str,
+) -> Option<&'b Expression<'a>> {
+    object.properties.iter().find_map(|property| {
+        let ObjectPropertyKind::ObjectProperty(property) = property else {
+            return None;
+        };
+        let key = match &property.key {
+            PropertyKey::StaticIdentifier(identifier) => identifier.name.as_str(),
+            PropertyKey::StringLiteral(literal) => literal.value.as_str(),
+            _ => return None,
+        };
+
+        (key == name).then_some(&property.value)
+    })
+}
+
+fn import_type(with_clause: &WithClause) -> Option<String> {
+    with_clause.with_entries.iter().find_map(|attribute| {
+        let key = match &attribute.key {
+            ImportAttributeKey::Identifier(identifier) => identifier.name.as_str(),
+            ImportAttributeKey::StringLiteral(literal) => literal.value.as_str(),
+        };
+
+        (key == "type").then(|| attribute.value.value.to_string())
+    })
 }
 
 pub fn transpile(path: &PathBuf) -> Result<String> {
-    let source_text = match read_to_string(path) {
+    Ok(transpile_file(path)?.code)
+}
+
+pub fn transpile_file(path: &Path) -> Result<Transpiled> {
+    let source_text = match read_to_string(&path.to_path_buf()) {
         Ok(content) => content,
         Err(e) => {
             return Err(FailedToReadFileWithError(e.to_string()).into());
         }
     };
 
-    transpile_with_text(path, source_text)
+    let cache = TranspileCache::default();
+    let config = compiler_config();
+    let key = cache.key(path, &source_text, &format!("{OPTIONS_KEY};{config:?}"));
+    if let Some(transpiled) = cache.get(&key) {
+        return Ok(transpiled);
+    }
+
+    let transpiled = transpile_source_with(path, &source_text, &config)?;
+    // A read-only cache directory only costs the next run some time
+    let _ = cache.set(&key, &transpiled);
+
+    Ok(transpiled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ike_toml::EsTarget;
+
+    #[test]
+    fn test_syntax_error() {
+        let source = "const a = 1;\nconst b = ;\nconst c = 3;\n";
+        let err = transpile_source(Path::new("/app/main.ts"), source)
+            .err()
+            .unwrap();
+        let err = err.downcast_ref::<SyntaxError>().unwrap();
+
+        assert_eq!(err.file, PathBuf::from("/app/main.ts"));
+        assert_eq!((err.line, err.column), (2, 11));
+        assert_eq!(
+            err.code_frame,
+            "  1 | const a = 1;\n> 2 | const b = ;\n    |           ^\n  3 | const c = 3;"
+        );
+    }
+
+    #[test]
+    fn test_unsupported_file_type() {
+        let err = transpile_source(Path::new("/app/styles.css"), "body { margin: 0; }")
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            err.downcast_ref::<UnsupportedFileType>().unwrap().0,
+            PathBuf::from("/app/styles.css")
+        );
+    }
+
+    #[test]
+    fn test_import_attributes() {
+        let path = Path::new("/app/main.ts");
+        let source = "import data from './data.json' with { type: 'json' };\nconst text = await import('./msg.txt', { with: { type: 'text' } });\nconst other = await import('./other.ts');\n";
+
+        let code = transpile_source(path, source).unwrap().code;
+        assert!(code.contains(&format!("from \"{IMPORT_TYPE_PREFIX}json:./data.json\"")));
+        assert!(code.contains(&format!("import(\"{IMPORT_TYPE_PREFIX}text:./msg.txt\")")));
+        assert!(code.contains("import(\"./other.ts\")"));
+    }
+
+    #[test]
+    fn test_compiler_config() {
+        let path = Path::new("/app/view.tsx");
+        let source = "export const view = () => <><div /></>;\n";
+
+        let classic = CompilerConfig {
+            jsx: Some(JsxRuntime::Classic),
+            jsx_pragma: Some("h".to_string()),
+            jsx_pragma_frag: Some("Fragment".to_string()),
+            target: Some(EsTarget::Es2022),
+            ..Default::default()
+        };
+        let code = transpile_source_with(path, source, &classic).unwrap().code;
+        assert!(code.contains("h(Fragment"));
+        assert!(code.contains("=>"));
+
+        let automatic = CompilerConfig {
+            jsx: Some(JsxRuntime::Automatic),
+            jsx_import_source: Some("preact".to_string()),
+            ..Default::default()
+        };
+        let code = transpile_source_with(path, source, &automatic)
+            .unwrap()
+            .code;
+        assert!(code.contains("preact/jsx-runtime"));
+        assert!(!code.contains("=>"));
+    }
+
+    #[test]
+    fn test_target() {
+        let path = Path::new("/app/main.ts");
+        let source = "export const twice = (x: number) => x * 2;
+";
+
+        let code = transpile_source_with(path, source, &CompilerConfig::default())
+            .unwrap()
+            .code;
+        assert!(!code.contains("=>"));
+
+        let es2022 = CompilerConfig {
+            target: Some(EsTarget::Es2022),
+            ..Default::default()
+        };
+        let code = transpile_source_with(path, source, &es2022).unwrap().code;
+        assert!(code.contains("(x) => x * 2"));
+    }
+
+    #[test]
+    fn test_class_fields() {
+        let path = Path::new("/app/main.ts");
+        let source = "export class Counter {\n  count: number = 0;\n}\n";
+
+        // Fields are emitted as written, which defines them like `useDefineForClassFields`
+        let code = transpile_source(path, source).unwrap().code;
+        assert!(code.contains("count = 0;"));
+        assert!(!code.contains("this.count"));
+    }
 }
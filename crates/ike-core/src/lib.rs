@@ -3,6 +3,7 @@ use exposed::ExposedFunction;
 pub mod exposed;
 pub mod macros;
 pub mod module;
+pub mod permissions;
 pub mod promise;
 
 pub trait ModuleTrait {
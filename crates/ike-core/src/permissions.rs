@@ -0,0 +1,626 @@
+use boa_engine::{js_string, Context, JsError, JsNativeError, JsResult, JsValue};
+use ike_logger::{elog, Logger};
+use std::collections::HashSet;
+use std::io::{stdin, IsTerminal};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PermissionKind {
+    Read,
+    Write,
+    Net,
+    Env,
+    /// Running subprocesses, reserved for a process API. Nothing checks it yet.
+    Run,
+}
+
+impl PermissionKind {
+    pub const ALL: [PermissionKind; 5] = [
+        PermissionKind::Read,
+        PermissionKind::Write,
+        PermissionKind::Net,
+        PermissionKind::Env,
+        PermissionKind::Run,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PermissionKind::Read => "read",
+            PermissionKind::Write => "write",
+            PermissionKind::Net => "net",
+            PermissionKind::Env => "env",
+            PermissionKind::Run => "run",
+        }
+    }
+
+    pub fn flag(&self) -> &'static str {
+        match self {
+            PermissionKind::Read => "--allow-read",
+            PermissionKind::Write => "--allow-write",
+            PermissionKind::Net => "--allow-net",
+            PermissionKind::Env => "--allow-env",
+            PermissionKind::Run => "--allow-run",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            PermissionKind::Read => "read access",
+            PermissionKind::Write => "write access",
+            PermissionKind::Net => "network access",
+            PermissionKind::Env => "env access",
+            PermissionKind::Run => "run access",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionState {
+    Granted,
+    Denied,
+    Prompt,
+}
+
+impl PermissionState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PermissionState::Granted => "granted",
+            PermissionState::Denied => "denied",
+            PermissionState::Prompt => "prompt",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UnaryPermission {
+    pub granted_all: bool,
+    pub granted: HashSet<String>,
+    pub denied_all: bool,
+    pub denied: HashSet<String>,
+}
+
+impl UnaryPermission {
+    pub fn allow_all() -> Self {
+        Self {
+            granted_all: true,
+            ..Default::default()
+        }
+    }
+
+    fn matches(kind: PermissionKind, list: &HashSet<String>, resource: &str) -> bool {
+        list.iter().any(|entry| match kind {
+            PermissionKind::Read | PermissionKind::Write => {
+                Path::new(resource).starts_with(Path::new(entry))
+            }
+            PermissionKind::Net => entry == resource || net_host(resource) == entry,
+            PermissionKind::Run => {
+                entry == resource
+                    || Path::new(resource)
+                        .file_stem()
+                        .is_some_and(|stem| stem.to_string_lossy() == entry.as_str())
+            }
+            PermissionKind::Env => entry == resource,
+        })
+    }
+
+    pub fn query(&self, kind: PermissionKind, resource: Option<&str>) -> PermissionState {
+        if self.denied_all {
+            return PermissionState::Denied;
+        }
+
+        match resource {
+            Some(resource) => {
+                if Self::matches(kind, &self.denied, resource) {
+                    PermissionState::Denied
+                } else if self.granted_all || Self::matches(kind, &self.granted, resource) {
+                    PermissionState::Granted
+                } else {
+                    PermissionState::Prompt
+                }
+            }
+            // Access to every resource is gone once one of them was revoked
+            None if !self.denied.is_empty() => PermissionState::Denied,
+            None if self.granted_all => PermissionState::Granted,
+            None => PermissionState::Prompt,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Permissions {
+    pub read: UnaryPermission,
+    pub write: UnaryPermission,
+    pub net: UnaryPermission,
+    pub env: UnaryPermission,
+    pub run: UnaryPermission,
+    pub prompt: bool,
+}
+
+impl Default for Permissions {
+    fn default() -> Self {
+        Self {
+            read: UnaryPermission::default(),
+            write: UnaryPermission::default(),
+            net: UnaryPermission::default(),
+            env: UnaryPermission::default(),
+            run: UnaryPermission::default(),
+            prompt: true,
+        }
+    }
+}
+
+impl Permissions {
+    pub fn allow_all() -> Self {
+        Self {
+            read: UnaryPermission::allow_all(),
+            write: UnaryPermission::allow_all(),
+            net: UnaryPermission::allow_all(),
+            env: UnaryPermission::allow_all(),
+            run: UnaryPermission::allow_all(),
+            prompt: true,
+        }
+    }
+
+    pub fn get(&self, kind: PermissionKind) -> &UnaryPermission {
+        match kind {
+            PermissionKind::Read => &self.read,
+            PermissionKind::Write => &self.write,
+            PermissionKind::Net => &self.net,
+            PermissionKind::Env => &self.env,
+            PermissionKind::Run => &self.run,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: PermissionKind) -> &mut UnaryPermission {
+        match kind {
+            PermissionKind::Read => &mut self.read,
+            PermissionKind::Write => &mut self.write,
+            PermissionKind::Net => &mut self.net,
+            PermissionKind::Env => &mut self.env,
+            PermissionKind::Run => &mut self.run,
+        }
+    }
+
+    /// Grants access to the resource, or to every resource of the kind when `resource` is `None`.
+    pub fn grant(&mut self, kind: PermissionKind, resource: Option<&str>) {
+        let permission = self.get_mut(kind);
+
+        match resource {
+            Some(resource) => {
+                permission
+                    .granted
+                    .insert(normalize_resource(kind, resource));
+            }
+            None => permission.granted_all = true,
+        }
+    }
+
+    /// Denies access to the resource, or to every resource of the kind when `resource` is `None`.
+    pub fn deny(&mut self, kind: PermissionKind, resource: Option<&str>) {
+        let permission = self.get_mut(kind);
+
+        match resource {
+            Some(resource) => {
+                permission.denied.insert(normalize_resource(kind, resource));
+            }
+            None => permission.denied_all = true,
+        }
+    }
+
+    /// Drops a previously granted access. Revoked permissions can't be granted again.
+    pub fn revoke(&mut self, kind: PermissionKind, resource: Option<&str>) {
+        let permission = self.get_mut(kind);
+
+        match resource {
+            Some(resource) => {
+                let resource = normalize_resource(kind, resource);
+                permission.granted.remove(&resource);
+                permission.denied.insert(resource);
+            }
+            None => {
+                permission.granted_all = false;
+                permission.granted.clear();
+                permission.denied_all = true;
+            }
+        }
+    }
+
+    pub fn query(&self, kind: PermissionKind, resource: Option<&str>) -> PermissionState {
+        let resource = resource.map(|resource| normalize_resource(kind, resource));
+
+        self.get(kind).query(kind, resource.as_deref())
+    }
+}
+
+static PERMISSIONS: LazyLock<Mutex<Permissions>> = LazyLock::new(Default::default);
+
+pub fn set_permissions(permissions: Permissions) {
+    *PERMISSIONS.lock().unwrap() = permissions;
+}
+
+pub fn get_permissions() -> Permissions {
+    PERMISSIONS.lock().unwrap().clone()
+}
+
+pub fn with_permissions<R>(f: impl FnOnce(&mut Permissions) -> R) -> R {
+    f(&mut PERMISSIONS.lock().unwrap())
+}
+
+pub fn query_permission(kind: PermissionKind, resource: Option<&str>) -> PermissionState {
+    with_permissions(|permissions| permissions.query(kind, resource))
+}
+
+/// Checks if the script is allowed to access the resource and prompts the user if it's not
+/// decided yet. Throws a `PermissionDenied` error when access is denied.
+pub fn check_permission(
+    kind: PermissionKind,
+    resource: Option<&str>,
+    ctx: &mut Context,
+) -> JsResult<()> {
+    match request_permission(kind, resource) {
+        PermissionState::Granted => Ok(()),
+        _ => Err(permission_denied(kind, resource, ctx)),
+    }
+}
+
+/// Returns the current state, prompting the user first if the permission isn't decided yet.
+pub fn request_permission(kind: PermissionKind, resource: Option<&str>) -> PermissionState {
+    match query_permission(kind, resource) {
+        PermissionState::Prompt if can_prompt() => prompt_permission(kind, resource),
+        PermissionState::Prompt => PermissionState::Denied,
+        state => state,
+    }
+}
+
+pub fn revoke_permission(kind: PermissionKind, resource: Option<&str>) -> PermissionState {
+    with_permissions(|permissions| {
+        permissions.revoke(kind, resource);
+        permissions.query(kind, resource)
+    })
+}
+
+pub fn can_prompt() -> bool {
+    with_permissions(|permissions| permissions.prompt) && stdin().is_terminal()
+}
+
+/// Asks the user for access and records the answer for the rest of the process.
+pub fn prompt_permission(kind: PermissionKind, resource: Option<&str>) -> PermissionState {
+    let target = match resource {
+        Some(resource) => format!("{} to <cyan>\"{}\"<r>", kind.description(), resource),
+        None => kind.description().to_string(),
+    };
+
+    loop {
+        elog!(
+            warn,
+            wt,
+            "ike requests {}. Allow? <d>[y/n/A] (y = yes, n = no, A = allow all {} permissions)<r> ",
+            target,
+            kind.name()
+        );
+
+        let mut answer = String::new();
+        if stdin().read_line(&mut answer).is_err() {
+            return PermissionState::Denied;
+        }
+
+        let state = match answer.trim() {
+            "y" | "Y" => {
+                with_permissions(|permissions| permissions.grant(kind, resource));
+                PermissionState::Granted
+            }
+            "A" => {
+                with_permissions(|permissions| permissions.grant(kind, None));
+                PermissionState::Granted
+            }
+            "n" | "N" => {
+                with_permissions(|permissions| permissions.deny(kind, resource));
+                PermissionState::Denied
+            }
+            _ => continue,
+        };
+
+        return state;
+    }
+}
+
+/// Creates an instance of `Ike.errors.PermissionDenied`, falling back to a plain `Error`
+/// when the runtime globals are not set up yet.
+pub fn permission_denied(
+    kind: PermissionKind,
+    resource: Option<&str>,
+    ctx: &mut Context,
+) -> JsError {
+    let message = match resource {
+        Some(resource) => format!(
+            "Requires {} to \"{}\", run again with the {} flag",
+            kind.description(),
+            resource,
+            kind.flag()
+        ),
+        None => format!(
+            "Requires {}, run again with the {} flag",
+            kind.description(),
+            kind.flag()
+        ),
+    };
+
+    let constructor = ctx
+        .global_object()
+        .get(js_string!("Ike"), ctx)
+        .ok()
+        .and_then(|ike| ike.as_object().cloned())
+        .and_then(|ike| ike.get(js_string!("errors"), ctx).ok())
+        .and_then(|errors| errors.as_object().cloned())
+        .and_then(|errors| errors.get(js_string!("PermissionDenied"), ctx).ok())
+        .and_then(|constructor| constructor.as_constructor().cloned());
+
+    if let Some(constructor) = constructor {
+        let args = [JsValue::from(js_string!(message.clone()))];
+        if let Ok(error) = constructor.construct(&args, None, ctx) {
+            return JsError::from_opaque(error.into());
+        }
+    }
+
+    JsNativeError::error().with_message(message).into()
+}
+
+/// Paths are made absolute and normalized so `./a/../b` and `b` are treated the same.
+pub fn normalize_resource(kind: PermissionKind, resource: &str) -> String {
+    match kind {
+        PermissionKind::Read | PermissionKind::Write => {
+            let path = Path::new(resource);
+            let path = if path.is_absolute() {
+                path.to_path_buf()
+            } else {
+                std::env::current_dir()
+                    .map(|cwd| cwd.join(path))
+                    .unwrap_or_else(|_| path.to_path_buf())
+            };
+
+            normalize_path(&path).to_string_lossy().to_string()
+        }
+        PermissionKind::Net => resource.trim().to_lowercase(),
+        PermissionKind::Env | PermissionKind::Run => resource.trim().to_string(),
+    }
+}
+
+/// Host of a `host[:port]` resource, IPv6 addresses keep their brackets.
+fn net_host(resource: &str) -> &str {
+    match resource.find(']') {
+        Some(end) if resource.starts_with('[') => &resource[..=end],
+        _ => resource.split(':').next().unwrap_or(resource),
+    }
+}
+
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component.as_os_str()),
+        }
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(entries: &[&str]) -> HashSet<String> {
+        entries.iter().map(|entry| entry.to_string()).collect()
+    }
+
+    #[test]
+    fn test_matches() {
+        let paths = set(&["/app/src"]);
+        assert!(UnaryPermission::matches(
+            PermissionKind::Read,
+            &paths,
+            "/app/src"
+        ));
+        assert!(UnaryPermission::matches(
+            PermissionKind::Write,
+            &paths,
+            "/app/src/main.ts"
+        ));
+        assert!(!UnaryPermission::matches(
+            PermissionKind::Read,
+            &paths,
+            "/app/srcs"
+        ));
+        assert!(!UnaryPermission::matches(
+            PermissionKind::Read,
+            &paths,
+            "/app"
+        ));
+
+        let hosts = set(&["example.com", "localhost:8080"]);
+        assert!(UnaryPermission::matches(
+            PermissionKind::Net,
+            &hosts,
+            "example.com"
+        ));
+        assert!(UnaryPermission::matches(
+            PermissionKind::Net,
+            &hosts,
+            "example.com:443"
+        ));
+        assert!(UnaryPermission::matches(
+            PermissionKind::Net,
+            &hosts,
+            "localhost:8080"
+        ));
+        assert!(!UnaryPermission::matches(
+            PermissionKind::Net,
+            &hosts,
+            "localhost:3000"
+        ));
+        assert!(!UnaryPermission::matches(
+            PermissionKind::Net,
+            &hosts,
+            "api.example.com"
+        ));
+
+        let ipv6 = set(&["[::1]", "[fe80::1]:8080"]);
+        assert!(UnaryPermission::matches(
+            PermissionKind::Net,
+            &ipv6,
+            "[::1]:3000"
+        ));
+        assert!(UnaryPermission::matches(
+            PermissionKind::Net,
+            &ipv6,
+            "[fe80::1]:8080"
+        ));
+        assert!(!UnaryPermission::matches(
+            PermissionKind::Net,
+            &ipv6,
+            "[fe80::1]:3000"
+        ));
+        assert!(!UnaryPermission::matches(
+            PermissionKind::Net,
+            &ipv6,
+            "[fe80::2]"
+        ));
+
+        let variables = set(&["HOME"]);
+        assert!(UnaryPermission::matches(
+            PermissionKind::Env,
+            &variables,
+            "HOME"
+        ));
+        assert!(!UnaryPermission::matches(
+            PermissionKind::Env,
+            &variables,
+            "home"
+        ));
+
+        let commands = set(&["git"]);
+        assert!(UnaryPermission::matches(
+            PermissionKind::Run,
+            &commands,
+            "/usr/bin/git"
+        ));
+        assert!(!UnaryPermission::matches(
+            PermissionKind::Run,
+            &commands,
+            "gitk"
+        ));
+    }
+
+    #[test]
+    fn test_normalize_resource() {
+        assert_eq!(
+            normalize_resource(PermissionKind::Read, "/app/./src/../lib"),
+            "/app/lib"
+        );
+        assert_eq!(
+            normalize_resource(PermissionKind::Write, "out"),
+            std::env::current_dir()
+                .unwrap()
+                .join("out")
+                .to_string_lossy()
+        );
+        assert_eq!(
+            normalize_resource(PermissionKind::Net, " Example.COM:443 "),
+            "example.com:443"
+        );
+        assert_eq!(normalize_resource(PermissionKind::Env, " HOME "), "HOME");
+    }
+
+    #[test]
+    fn test_query() {
+        let mut permissions = Permissions::default();
+        assert_eq!(
+            permissions.query(PermissionKind::Read, None),
+            PermissionState::Prompt
+        );
+        assert_eq!(
+            permissions.query(PermissionKind::Read, Some("/app")),
+            PermissionState::Prompt
+        );
+
+        permissions.grant(PermissionKind::Read, Some("/app"));
+        assert_eq!(
+            permissions.query(PermissionKind::Read, Some("/app/src/../main.ts")),
+            PermissionState::Granted
+        );
+        assert_eq!(
+            permissions.query(PermissionKind::Read, Some("/etc/passwd")),
+            PermissionState::Prompt
+        );
+        assert_eq!(
+            permissions.query(PermissionKind::Write, Some("/app")),
+            PermissionState::Prompt
+        );
+
+        permissions.grant(PermissionKind::Env, None);
+        assert_eq!(
+            permissions.query(PermissionKind::Env, Some("HOME")),
+            PermissionState::Granted
+        );
+        assert_eq!(
+            permissions.query(PermissionKind::Env, None),
+            PermissionState::Granted
+        );
+
+        permissions.deny(PermissionKind::Net, None);
+        permissions.grant(PermissionKind::Net, Some("example.com"));
+        assert_eq!(
+            permissions.query(PermissionKind::Net, Some("example.com")),
+            PermissionState::Denied
+        );
+    }
+
+    #[test]
+    fn test_deny_over_grant() {
+        let mut permissions = Permissions::allow_all();
+        permissions.deny(PermissionKind::Read, Some("/app/secrets"));
+
+        assert_eq!(
+            permissions.query(PermissionKind::Read, Some("/app/secrets/key.pem")),
+            PermissionState::Denied
+        );
+        assert_eq!(
+            permissions.query(PermissionKind::Read, Some("/app/src")),
+            PermissionState::Granted
+        );
+        assert_eq!(
+            permissions.query(PermissionKind::Read, None),
+            PermissionState::Denied
+        );
+        assert_eq!(
+            permissions.query(PermissionKind::Write, None),
+            PermissionState::Granted
+        );
+
+        permissions.grant(PermissionKind::Net, Some("example.com"));
+        permissions.deny(PermissionKind::Net, Some("example.com:8080"));
+        assert_eq!(
+            permissions.query(PermissionKind::Net, Some("example.com:8080")),
+            PermissionState::Denied
+        );
+        assert_eq!(
+            permissions.query(PermissionKind::Net, Some("example.com:443")),
+            PermissionState::Granted
+        );
+
+        permissions.revoke(PermissionKind::Env, Some("HOME"));
+        assert_eq!(
+            permissions.query(PermissionKind::Env, Some("HOME")),
+            PermissionState::Denied
+        );
+    }
+}
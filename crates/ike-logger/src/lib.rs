@@ -56,6 +56,11 @@ lazy_static::lazy_static! {
     };
 }
 
+/// Escapes `<` and `>`, so text that isn't ours (e.g. source code) isn't read as markup.
+pub fn escape(text: &str) -> String {
+    text.replace('<', "\\<").replace('>', "\\>")
+}
+
 pub fn pretty_fmt(fmt_str: &str) -> String {
     let mut new_fmt = String::new();
     let mut chars = fmt_str.chars().peekable();
@@ -311,6 +316,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            pretty_fmt(&format!("<red>{}<r>", escape("Array<string> > 0"))),
+            "\x1b[31mArray<string> > 0\x1b[0m"
+        );
+    }
+
     #[test]
     fn test_logger() {
         log!(info, "This is an info message");
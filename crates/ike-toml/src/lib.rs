@@ -1,11 +1,16 @@
 use ike_fs::{find_nearest_file, read_to_string};
 use ike_logger::{elog, Logger};
-use serde::Deserialize;
-use std::{collections::HashMap, path::PathBuf};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    net::Ipv6Addr,
+    path::{Path, PathBuf},
+};
 use thiserror::Error;
 
 #[derive(Deserialize, Debug, Default, Clone)]
 pub struct IkeTomlStruct {
+    #[serde(default)]
     pub package: IkePackage,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dependencies: Option<HashMap<String, DependencyOrString>>,
@@ -16,6 +21,142 @@ pub struct IkeTomlStruct {
     pub tasks: Option<HashMap<String, String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exports: Option<HashMap<String, HashMap<String, String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<EnvConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<HashMap<String, PermissionOrList>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<WorkspaceConfig>,
+    /// Import map entries, specifier (or prefix ending with `/`) to target.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub imports: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<HashMap<String, HashMap<String, String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compiler: Option<CompilerConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fmt: Option<FmtConfig>,
+    /// Rule names of `ike lint` to their severity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lint: Option<HashMap<String, LintSeverity>>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct WorkspaceConfig {
+    /// Glob patterns of member directories, relative to the workspace root.
+    #[serde(default)]
+    pub members: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct EnvConfig {
+    #[serde(default)]
+    #[serde(rename = "env-files")]
+    pub env_files: Vec<String>,
+    #[serde(default)]
+    pub r#override: bool,
+}
+
+/// Transpiler options, named like their `tsconfig.json` counterparts. Unset options keep
+/// the transpiler defaults. Options the transpiler can't apply (decorators, class field
+/// semantics, targets that need syntax lowered) aren't accepted.
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CompilerConfig {
+    pub jsx: Option<JsxRuntime>,
+    /// Module the automatic runtime imports `jsx` from, e.g. `preact`.
+    pub jsx_import_source: Option<String>,
+    /// Function the classic runtime calls for elements, e.g. `h`.
+    pub jsx_pragma: Option<String>,
+    /// Component the classic runtime uses for fragments, e.g. `Fragment`.
+    pub jsx_pragma_frag: Option<String>,
+    pub target: Option<EsTarget>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JsxRuntime {
+    /// `React.createElement` calls, or whatever `jsxPragma` names.
+    Classic,
+    /// `jsx` calls imported from `jsxImportSource`.
+    Automatic,
+}
+
+/// ECMAScript version the emitted code has to run on. Older versions would need syntax
+/// to be lowered, which the transpiler doesn't do.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum EsTarget {
+    Es2022,
+    Es2023,
+    Es2024,
+    EsNext,
+}
+
+/// Options of `ike fmt`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FmtConfig {
+    /// Spaces per indentation level.
+    pub indent_width: usize,
+    /// Width import and export lists get wrapped at, other code keeps its line breaks.
+    pub line_width: usize,
+    pub quotes: QuoteStyle,
+    /// Whether statements end with a semicolon.
+    pub semicolons: bool,
+}
+
+impl Default for FmtConfig {
+    fn default() -> Self {
+        Self {
+            indent_width: 2,
+            line_width: 80,
+            quotes: QuoteStyle::Double,
+            semicolons: true,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QuoteStyle {
+    #[default]
+    Double,
+    Single,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Off,
+    Warn,
+    Error,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum PermissionOrList {
+    Bool(bool),
+    List(Vec<String>),
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum PermissionGrant {
+    #[default]
+    None,
+    All,
+    List(Vec<String>),
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ParsedPermissions {
+    pub read: PermissionGrant,
+    pub write: PermissionGrant,
+    pub net: PermissionGrant,
+    pub env: PermissionGrant,
+    pub run: PermissionGrant,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -60,6 +201,15 @@ pub struct PackageRepository {
 pub struct IkeToml {
     pub toml: ParsedIkeTomlStruct,
     pub file_path: Option<PathBuf>,
+    /// ike.toml of the workspace this package belongs to, if any.
+    pub workspace_root: Option<PathBuf>,
+}
+
+/// A workspace root along with all of its members.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub root: IkeToml,
+    pub members: Vec<IkeToml>,
 }
 
 #[derive(Deserialize, Debug, Default, Clone)]
@@ -74,6 +224,9 @@ pub struct Dependency {
     pub branch: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rev: Option<String>,
+    /// Use the workspace member with the same name.
+    #[serde(default)]
+    pub workspace: bool,
 }
 
 #[derive(Deserialize, Debug, Default, Clone)]
@@ -90,18 +243,48 @@ pub struct ParsedIkeTomlStruct {
     pub dev_dependencies: HashMap<String, Dependency>,
     pub tasks: HashMap<String, String>,
     pub exports: Option<HashMap<String, Export>>,
+    pub env: EnvConfig,
+    pub permissions: ParsedPermissions,
+    pub workspace: Option<WorkspaceConfig>,
+    pub imports: HashMap<String, String>,
+    pub scopes: HashMap<String, HashMap<String, String>>,
+    pub compiler: CompilerConfig,
+    pub fmt: FmtConfig,
+    pub lint: HashMap<String, LintSeverity>,
 }
 
 #[derive(Debug, Error)]
 pub enum IkeTomlError {
-    #[error("Dependency {0} must have at least one of 'version', 'path', or 'git'")]
+    #[error("Dependency {0} must have at least one of 'version', 'path', 'git' or 'workspace'")]
     MissingDependencyFields(String),
     #[error("Dependency {0} has conflicting fields: 'version', 'path', and 'git' cannot be used together")]
     ConflictingDependencyFields(String),
     #[error("Dependency {0} has conflicting fields: 'git', 'branch', 'rev', and 'path' cannot be used together")]
     ConflictingGitFields(String),
+    #[error("Dependency {0} has conflicting fields: 'workspace' cannot be used with 'version', 'path' or 'git'")]
+    ConflictingWorkspaceFields(String),
     #[error("Failed to parse dependencies: {0}")]
     FailedToParseDependencies(String),
+    #[error("Failed to parse ike.toml: {0}")]
+    FailedToParseToml(String),
+    #[error("Unknown permission '{0}', expected one of 'read', 'write', 'net', 'env' or 'run'")]
+    UnknownPermission(String),
+    #[error("Permission '{0}' contains an empty entry")]
+    EmptyPermissionEntry(String),
+    #[error("Invalid host '{0}' in 'net' permission, expected 'hostname' or 'hostname:port'")]
+    InvalidPermissionHost(String),
+    #[error("Invalid variable name '{0}' in 'env' permission")]
+    InvalidPermissionEnvName(String),
+    #[error("Invalid workspace member pattern '{0}'")]
+    InvalidWorkspacePattern(String),
+    #[error("Workspace member {0} has no ike.toml")]
+    MissingWorkspaceMember(PathBuf),
+    #[error("Workspace members {1} and {2} are both named '{0}'")]
+    DuplicateWorkspaceMember(String, PathBuf, PathBuf),
+    #[error("Compiler option '{0}' only applies to the {1} JSX runtime")]
+    ConflictingJsxOptions(String, String),
+    #[error("Formatter option '{0}' must be {1}")]
+    InvalidFmtOption(String, String),
 }
 
 impl IkeTomlStruct {
@@ -121,9 +304,19 @@ impl IkeTomlStruct {
                         git: None,
                         branch: None,
                         rev: None,
+                        workspace: false,
                     },
                 };
 
+                if dep.workspace {
+                    if dep.version.is_some() || dep.path.is_some() || dep.git.is_some() {
+                        return Err(IkeTomlError::ConflictingWorkspaceFields(name));
+                    }
+
+                    parsed_deps.insert(name, dep);
+                    continue;
+                }
+
                 if dep.version.is_none() && dep.path.is_none() && dep.git.is_none() {
                     return Err(IkeTomlError::MissingDependencyFields(name));
                 }
@@ -148,6 +341,133 @@ impl IkeTomlStruct {
         Ok(parsed_deps)
     }
 
+    fn parse_permissions(
+        &self,
+        permissions: Option<HashMap<String, PermissionOrList>>,
+    ) -> Result<ParsedPermissions, IkeTomlError> {
+        let mut parsed = ParsedPermissions::default();
+
+        for (name, value) in permissions.unwrap_or_default() {
+            let grant = match value {
+                PermissionOrList::Bool(true) => PermissionGrant::All,
+                PermissionOrList::Bool(false) => PermissionGrant::None,
+                PermissionOrList::List(entries) => {
+                    for entry in entries.iter() {
+                        Self::validate_permission_entry(&name, entry)?;
+                    }
+
+                    PermissionGrant::List(entries)
+                }
+            };
+
+            match name.as_str() {
+                "read" => parsed.read = grant,
+                "write" => parsed.write = grant,
+                "net" => parsed.net = grant,
+                "env" => parsed.env = grant,
+                "run" => parsed.run = grant,
+                _ => return Err(IkeTomlError::UnknownPermission(name)),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    fn validate_permission_entry(name: &str, entry: &str) -> Result<(), IkeTomlError> {
+        if entry.trim().is_empty() {
+            return Err(IkeTomlError::EmptyPermissionEntry(name.to_string()));
+        }
+
+        match name {
+            "net" => {
+                // IPv6 addresses are bracketed, `[::1]:8080`
+                let (valid_host, port) = match entry.strip_prefix('[') {
+                    Some(rest) => match rest.split_once(']') {
+                        Some((ip, rest)) => {
+                            let port = rest.strip_prefix(':');
+                            let valid_ip = ip.parse::<Ipv6Addr>().is_ok();
+                            (valid_ip && (rest.is_empty() || port.is_some()), port)
+                        }
+                        None => (false, None),
+                    },
+                    None => {
+                        let (host, port) = match entry.rsplit_once(':') {
+                            Some((host, port)) => (host, Some(port)),
+                            None => (entry, None),
+                        };
+                        let valid_host = !host.is_empty()
+                            && host
+                                .chars()
+                                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+                        (valid_host, port)
+                    }
+                };
+                let valid_port = port.is_none_or(|port| port.parse::<u16>().is_ok());
+
+                if !valid_host || !valid_port {
+                    return Err(IkeTomlError::InvalidPermissionHost(entry.to_string()));
+                }
+            }
+            "env"
+                if entry.contains('=')
+                    || entry.contains('\0')
+                    || entry.contains(char::is_whitespace) =>
+            {
+                return Err(IkeTomlError::InvalidPermissionEnvName(entry.to_string()));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn parse_compiler(
+        &self,
+        compiler: Option<CompilerConfig>,
+    ) -> Result<CompilerConfig, IkeTomlError> {
+        let compiler = compiler.unwrap_or_default();
+
+        let conflict = match compiler.jsx {
+            Some(JsxRuntime::Classic) if compiler.jsx_import_source.is_some() => {
+                Some(("jsxImportSource", "automatic"))
+            }
+            Some(JsxRuntime::Automatic) if compiler.jsx_pragma.is_some() => {
+                Some(("jsxPragma", "classic"))
+            }
+            Some(JsxRuntime::Automatic) if compiler.jsx_pragma_frag.is_some() => {
+                Some(("jsxPragmaFrag", "classic"))
+            }
+            _ => None,
+        };
+        if let Some((option, runtime)) = conflict {
+            return Err(IkeTomlError::ConflictingJsxOptions(
+                option.to_string(),
+                runtime.to_string(),
+            ));
+        }
+
+        Ok(compiler)
+    }
+
+    fn parse_fmt(&self, fmt: Option<FmtConfig>) -> Result<FmtConfig, IkeTomlError> {
+        let fmt = fmt.unwrap_or_default();
+
+        if fmt.indent_width == 0 || fmt.indent_width > 16 {
+            return Err(IkeTomlError::InvalidFmtOption(
+                "indentWidth".to_string(),
+                "between 1 and 16".to_string(),
+            ));
+        }
+        if fmt.line_width < 20 {
+            return Err(IkeTomlError::InvalidFmtOption(
+                "lineWidth".to_string(),
+                "at least 20".to_string(),
+            ));
+        }
+
+        Ok(fmt)
+    }
+
     pub fn to_parsed(self) -> Result<ParsedIkeTomlStruct, IkeTomlError> {
         let parsed_dependencies = self
             .parse_dependencies(self.dependencies.clone())
@@ -157,6 +477,10 @@ impl IkeTomlStruct {
             .parse_dependencies(self.dev_dependencies.clone())
             .map_err(|e| IkeTomlError::FailedToParseDependencies(e.to_string()))?;
 
+        let parsed_permissions = self.parse_permissions(self.permissions.clone())?;
+        let parsed_compiler = self.parse_compiler(self.compiler.clone())?;
+        let parsed_fmt = self.parse_fmt(self.fmt.clone())?;
+
         let parsed_exports = self.exports.map(|exports| {
             exports
                 .iter()
@@ -178,6 +502,14 @@ impl IkeTomlStruct {
             dev_dependencies: parsed_dev_dependencies,
             tasks: self.tasks.unwrap_or_default(),
             exports: parsed_exports,
+            env: self.env.unwrap_or_default(),
+            permissions: parsed_permissions,
+            workspace: self.workspace,
+            imports: self.imports.unwrap_or_default(),
+            scopes: self.scopes.unwrap_or_default(),
+            compiler: parsed_compiler,
+            fmt: parsed_fmt,
+            lint: self.lint.unwrap_or_default(),
         })
     }
 }
@@ -185,15 +517,71 @@ impl IkeTomlStruct {
 impl IkeToml {
     pub fn from_file(file_path: PathBuf) -> Result<Self, IkeTomlError> {
         let toml_str = read_to_string(&file_path).expect("Could not read file");
-        let serialized_toml: IkeTomlStruct = toml::from_str(&toml_str).unwrap();
+        let serialized_toml: IkeTomlStruct = toml::from_str(&toml_str)
+            .map_err(|e| IkeTomlError::FailedToParseToml(e.to_string()))?;
         let parsed_toml = serialized_toml.to_parsed()?;
 
         Ok(Self {
             toml: parsed_toml,
             file_path: Some(file_path),
+            workspace_root: None,
         })
     }
 
+    pub fn dir(&self) -> Option<&Path> {
+        self.file_path.as_deref().and_then(|path| path.parent())
+    }
+
+    pub fn is_workspace_root(&self) -> bool {
+        self.toml.workspace.is_some()
+    }
+
+    /// Walks up from a package to the ike.toml of the workspace that lists it as a member.
+    pub fn find_workspace_root(&self) -> Option<PathBuf> {
+        let file_path = self.file_path.as_ref()?;
+        if self.is_workspace_root() {
+            return Some(file_path.clone());
+        }
+
+        let package_dir = self.dir()?.to_path_buf();
+        let mut dir = package_dir.parent()?.to_path_buf();
+
+        loop {
+            let candidate = dir.join("ike.toml");
+            if candidate.is_file() {
+                if let Ok(root) = IkeToml::from_file(candidate.clone()) {
+                    let is_member = root.toml.workspace.as_ref().is_some_and(|config| {
+                        member_dirs(&dir, config)
+                            .unwrap_or_default()
+                            .iter()
+                            .any(|member| same_path(member, &package_dir))
+                    });
+
+                    if is_member {
+                        return Some(candidate);
+                    }
+                }
+            }
+
+            if !dir.pop() {
+                return None;
+            }
+        }
+    }
+
+    /// Loads the workspace this package belongs to (or is the root of).
+    pub fn workspace(&self) -> Result<Option<Workspace>, IkeTomlError> {
+        let root_path = match &self.workspace_root {
+            Some(root_path) => Some(root_path.clone()),
+            None => self.find_workspace_root(),
+        };
+
+        match root_path {
+            Some(root_path) => Workspace::load(root_path).map(Some),
+            None => Ok(None),
+        }
+    }
+
     pub fn find_nearest_from(dir: PathBuf) -> Option<Self> {
         let file_path = find_nearest_file(dir, "ike.toml");
 
@@ -202,7 +590,10 @@ impl IkeToml {
                 let toml = IkeToml::from_file(file_path);
 
                 match toml {
-                    Ok(toml) => Some(toml),
+                    Ok(mut toml) => {
+                        toml.workspace_root = toml.find_workspace_root();
+                        Some(toml)
+                    }
                     Err(e) => {
                         elog!(error, "{}", e);
                         None
@@ -213,3 +604,334 @@ impl IkeToml {
         }
     }
 }
+
+impl Workspace {
+    pub fn load(root_path: PathBuf) -> Result<Self, IkeTomlError> {
+        let mut root = IkeToml::from_file(root_path.clone())?;
+        root.workspace_root = Some(root_path.clone());
+
+        let root_dir = root_path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let config = root.toml.workspace.clone().unwrap_or_default();
+        let mut members: Vec<IkeToml> = Vec::new();
+
+        for dir in member_dirs(&root_dir, &config)? {
+            let manifest_path = dir.join("ike.toml");
+            if !manifest_path.is_file() {
+                return Err(IkeTomlError::MissingWorkspaceMember(dir));
+            }
+
+            let mut member = IkeToml::from_file(manifest_path)?;
+            member.workspace_root = Some(root_path.clone());
+
+            if let Some(existing) = members
+                .iter()
+                .find(|existing| existing.toml.package.name == member.toml.package.name)
+            {
+                return Err(IkeTomlError::DuplicateWorkspaceMember(
+                    member.toml.package.name,
+                    existing.dir().unwrap_or(Path::new("")).to_path_buf(),
+                    dir,
+                ));
+            }
+
+            members.push(member);
+        }
+
+        Ok(Self { root, members })
+    }
+
+    pub fn root_dir(&self) -> &Path {
+        self.root.dir().unwrap_or(Path::new(""))
+    }
+
+    pub fn member(&self, name: &str) -> Option<&IkeToml> {
+        self.members
+            .iter()
+            .find(|member| member.toml.package.name == name)
+    }
+
+    /// Members whose name or path (relative to the workspace root) matches one of the
+    /// glob patterns. No patterns selects every member.
+    pub fn filter(&self, patterns: &[String]) -> Result<Vec<&IkeToml>, IkeTomlError> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                glob::Pattern::new(pattern)
+                    .map_err(|_| IkeTomlError::InvalidWorkspacePattern(pattern.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(self
+            .members
+            .iter()
+            .filter(|member| {
+                let relative = member
+                    .dir()
+                    .and_then(|dir| dir.strip_prefix(self.root_dir()).ok())
+                    .map(|dir| dir.to_string_lossy().replace('\\', "/"))
+                    .unwrap_or_default();
+
+                patterns.is_empty()
+                    || patterns.iter().any(|pattern| {
+                        pattern.matches(&member.toml.package.name) || pattern.matches(&relative)
+                    })
+            })
+            .collect())
+    }
+}
+
+/// Expands the member globs of a workspace into directories, sorted for stable output.
+fn member_dirs(root_dir: &Path, config: &WorkspaceConfig) -> Result<Vec<PathBuf>, IkeTomlError> {
+    let expand = |patterns: &[String]| -> Result<Vec<PathBuf>, IkeTomlError> {
+        let mut dirs = Vec::new();
+
+        for pattern in patterns {
+            let full = root_dir.join(pattern);
+            let paths = glob::glob(&full.to_string_lossy())
+                .map_err(|_| IkeTomlError::InvalidWorkspacePattern(pattern.clone()))?;
+
+            dirs.extend(paths.filter_map(Result::ok).filter(|path| path.is_dir()));
+        }
+
+        Ok(dirs)
+    };
+
+    let excluded = expand(&config.exclude)?;
+    let mut dirs: Vec<PathBuf> = expand(&config.members)?
+        .into_iter()
+        .filter(|dir| !excluded.iter().any(|excluded| same_path(excluded, dir)))
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+
+    Ok(dirs)
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<ParsedIkeTomlStruct, IkeTomlError> {
+        let toml: IkeTomlStruct = toml::from_str(source).unwrap();
+        toml.to_parsed()
+    }
+
+    #[test]
+    fn test_parse_permissions() {
+        let parsed = parse(
+            r#"
+            [package]
+            name = "app"
+            version = "0.1.0"
+
+            [permissions]
+            read = true
+            write = ["./out"]
+            net = ["example.com", "localhost:8080", "[::1]:3000"]
+            env = ["HOME"]
+            run = ["git"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            parsed.permissions,
+            ParsedPermissions {
+                read: PermissionGrant::All,
+                write: PermissionGrant::List(vec!["./out".to_string()]),
+                net: PermissionGrant::List(vec![
+                    "example.com".to_string(),
+                    "localhost:8080".to_string(),
+                    "[::1]:3000".to_string()
+                ]),
+                env: PermissionGrant::List(vec!["HOME".to_string()]),
+                run: PermissionGrant::List(vec!["git".to_string()]),
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_permissions() {
+        let with_permissions = |permissions: &str| {
+            parse(&format!(
+                "[package]\nname = \"app\"\nversion = \"0.1.0\"\n[permissions]\n{}",
+                permissions
+            ))
+        };
+
+        assert!(matches!(
+            with_permissions("ffi = true"),
+            Err(IkeTomlError::UnknownPermission(_))
+        ));
+        assert!(matches!(
+            with_permissions("read = [\"\"]"),
+            Err(IkeTomlError::EmptyPermissionEntry(_))
+        ));
+        for host in [
+            "https://example.com",
+            "::1",
+            "[::1]8080",
+            "[::1]:http",
+            "[nope]",
+        ] {
+            assert!(matches!(
+                with_permissions(&format!("net = [\"{host}\"]")),
+                Err(IkeTomlError::InvalidPermissionHost(_))
+            ));
+        }
+        assert!(with_permissions("net = [\"[::1]\"]").is_ok());
+        assert!(matches!(
+            with_permissions("env = [\"A=B\"]"),
+            Err(IkeTomlError::InvalidPermissionEnvName(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_workspace() {
+        let parsed = parse(
+            r#"
+            [workspace]
+            members = ["packages/*"]
+            exclude = ["packages/legacy"]
+            "#,
+        )
+        .unwrap();
+
+        let workspace = parsed.workspace.unwrap();
+        assert_eq!(workspace.members, vec!["packages/*".to_string()]);
+        assert_eq!(workspace.exclude, vec!["packages/legacy".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_workspace_dependency() {
+        let parsed = parse(
+            r#"
+            [package]
+            name = "web"
+            version = "0.1.0"
+
+            [dependencies]
+            utils = { workspace = true }
+            "#,
+        )
+        .unwrap();
+
+        assert!(parsed.dependencies["utils"].workspace);
+
+        assert!(matches!(
+            parse(
+                r#"
+                [dependencies]
+                utils = { workspace = true, version = "1.0.0" }
+                "#,
+            ),
+            Err(IkeTomlError::FailedToParseDependencies(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_compiler() {
+        let parsed = parse(
+            r#"
+            [compiler]
+            jsx = "classic"
+            jsxPragma = "h"
+            jsxPragmaFrag = "Fragment"
+            target = "es2022"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            parsed.compiler,
+            CompilerConfig {
+                jsx: Some(JsxRuntime::Classic),
+                jsx_import_source: None,
+                jsx_pragma: Some("h".to_string()),
+                jsx_pragma_frag: Some("Fragment".to_string()),
+                target: Some(EsTarget::Es2022),
+            }
+        );
+        assert_eq!(parse("").unwrap().compiler, CompilerConfig::default());
+
+        assert!(matches!(
+            parse(
+                r#"
+                [compiler]
+                jsx = "classic"
+                jsxImportSource = "preact"
+                "#,
+            ),
+            Err(IkeTomlError::ConflictingJsxOptions(_, _))
+        ));
+
+        for unsupported in [
+            "decorators = \"legacy\"",
+            "decorators = \"stage3\"",
+            "useDefineForClassFields = false",
+            "target = \"es5\"",
+            "target = \"es2021\"",
+        ] {
+            assert!(matches!(
+                parse(&format!("[compiler]\n{unsupported}\n")),
+                Err(IkeTomlError::FailedToParseToml(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_fmt() {
+        let parsed = parse(
+            r#"
+            [fmt]
+            indentWidth = 4
+            quotes = "single"
+            semicolons = false
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            parsed.fmt,
+            FmtConfig {
+                indent_width: 4,
+                line_width: 80,
+                quotes: QuoteStyle::Single,
+                semicolons: false,
+            }
+        );
+        assert_eq!(parse("").unwrap().fmt, FmtConfig::default());
+
+        assert!(matches!(
+            parse(
+                r#"
+                [fmt]
+                indentWidth = 0
+                "#,
+            ),
+            Err(IkeTomlError::InvalidFmtOption(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_parse_lint() {
+        let parsed = parse(
+            r#"
+            [lint]
+            no-debugger = "off"
+            no-unused-vars = "error"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(parsed.lint["no-debugger"], LintSeverity::Off);
+        assert_eq!(parsed.lint["no-unused-vars"], LintSeverity::Error);
+    }
+}
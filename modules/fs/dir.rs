@@ -6,6 +6,7 @@ use ike_core::promise::base_promise;
 use std::path::Path;
 
 use crate::{resolve_path_from_args, FileSystem};
+use ike_core::permissions::PermissionKind;
 
 pub fn create_dir_sync_ex(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
     let (path, recursive, mode) = resolve_create_dir_args(args, ctx)?;
@@ -54,7 +55,7 @@ pub fn resolve_create_dir_args(
     args: &[JsValue],
     ctx: &mut Context,
 ) -> JsResult<(String, bool, u32)> {
-    let path = resolve_path_from_args(args, ctx)?;
+    let path = resolve_path_from_args(args, PermissionKind::Write, ctx)?;
     let path = &path.to_std_string().unwrap();
     let recursive = get_recursive_flag(args, ctx)?;
     let mode = args
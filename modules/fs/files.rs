@@ -6,12 +6,13 @@ use smol::block_on;
 use std::path::Path;
 
 use crate::{resolve_path_from_args, FileSystem};
+use ike_core::permissions::PermissionKind;
 
 use super::{open_file, File};
 use ike_core::throw;
 
 pub fn read_file_sync_ex(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
-    let str_path = resolve_path_from_args(args, ctx)?;
+    let str_path = resolve_path_from_args(args, PermissionKind::Read, ctx)?;
     let str_path = &str_path.to_std_string().unwrap();
     let path = Path::new(&str_path);
 
@@ -45,7 +46,7 @@ pub async fn read_file_async_base(path: &Path, ctx: &mut Context) -> anyhow::Res
 }
 
 pub fn read_file_async_ex(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
-    let str_path = resolve_path_from_args(args, ctx)?;
+    let str_path = resolve_path_from_args(args, PermissionKind::Read, ctx)?;
     let str_path = &str_path.to_std_string().unwrap();
     let path = Path::new(&str_path);
 
@@ -83,7 +84,7 @@ pub fn read_text_file_sync_ex(
     args: &[JsValue],
     ctx: &mut Context,
 ) -> JsResult<JsValue> {
-    let str_path = resolve_path_from_args(args, ctx)?;
+    let str_path = resolve_path_from_args(args, PermissionKind::Read, ctx)?;
     let str_path = &str_path.to_std_string().unwrap();
     let path = Path::new(&str_path);
 
@@ -118,7 +119,7 @@ pub fn read_text_file_async_ex(
     args: &[JsValue],
     ctx: &mut Context,
 ) -> JsResult<JsValue> {
-    let str_path = resolve_path_from_args(args, ctx)?;
+    let str_path = resolve_path_from_args(args, PermissionKind::Read, ctx)?;
     let str_path = &str_path.to_std_string().unwrap();
     let path = Path::new(&str_path);
 
@@ -155,7 +156,7 @@ pub fn read_text_file_async_ex(
 // TODO: implement FsFile and return it in both create_file_sync and create_file_async
 
 pub fn create_file_sync_ex(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
-    let str_path = resolve_path_from_args(args, ctx)?;
+    let str_path = resolve_path_from_args(args, PermissionKind::Write, ctx)?;
     let str_path = &str_path.to_std_string().unwrap();
     let path = Path::new(&str_path);
 
@@ -166,7 +167,7 @@ pub fn create_file_sync_ex(_: &JsValue, args: &[JsValue], ctx: &mut Context) ->
 }
 
 pub fn create_file_async_ex(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
-    let str_path = resolve_path_from_args(args, ctx)?;
+    let str_path = resolve_path_from_args(args, PermissionKind::Write, ctx)?;
     let str_path = str_path.to_std_string().unwrap();
     let path = Path::new(&str_path);
 
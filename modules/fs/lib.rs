@@ -4,6 +4,7 @@ use boa_engine::object::builtins::JsPromise;
 use boa_engine::{Context, JsNativeError, JsResult, JsString, JsValue};
 use dir::get_recursive_flag;
 use ike_core::module;
+use ike_core::permissions::{check_permission, PermissionKind};
 use ike_core::promise::base_promise;
 use ike_core::throw;
 use smol::block_on;
@@ -16,13 +17,20 @@ use tokio::task::spawn_blocking;
 pub mod dir;
 pub mod files;
 
-pub fn resolve_path_from_args(args: &[JsValue], ctx: &mut Context) -> JsResult<JsString> {
+// Every fs function goes through here, so this is also where permissions are checked
+pub fn resolve_path_from_args(
+    args: &[JsValue],
+    permission: PermissionKind,
+    ctx: &mut Context,
+) -> JsResult<JsString> {
     let path = args.first();
     if path.is_none() || path.unwrap().is_undefined() {
         throw!(err, "Expected a path in fs function");
     }
     let path = path.unwrap();
     let path = path.to_string(ctx)?;
+    check_permission(permission, Some(&path.to_std_string_escaped()), ctx)?;
+
     Ok(path)
 }
 
@@ -109,7 +117,7 @@ impl FileSystem {
 }
 
 pub fn remove_sync_ex(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
-    let str_path = resolve_path_from_args(args, ctx)?;
+    let str_path = resolve_path_from_args(args, PermissionKind::Write, ctx)?;
     let str_path = &str_path.to_std_string().unwrap();
     let path = Path::new(&str_path);
     let recursive = get_recursive_flag(args, ctx)?;
@@ -121,7 +129,7 @@ pub fn remove_sync_ex(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsRes
 }
 
 pub fn remove_async_ex(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
-    let str_path = resolve_path_from_args(args, ctx)?;
+    let str_path = resolve_path_from_args(args, PermissionKind::Write, ctx)?;
     let str_path = &str_path.to_std_string().unwrap();
     let path = Path::new(&str_path);
     let recursive = get_recursive_flag(args, ctx)?;
@@ -154,7 +162,7 @@ pub fn remove_async_ex(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsRe
 }
 
 pub fn exists_sync_ex(_: &JsValue, args: &[JsValue], ctx: &mut Context) -> JsResult<JsValue> {
-    let str_path = resolve_path_from_args(args, ctx)?;
+    let str_path = resolve_path_from_args(args, PermissionKind::Read, ctx)?;
     let str_path = &str_path.to_std_string().unwrap();
     let path = Path::new(&str_path);
 
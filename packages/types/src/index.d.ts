@@ -137,6 +137,13 @@ interface Ike {
    */
   os: Os;
 
+  /**
+   * Returns the CPU architecture Ike was compiled for.
+   *
+   * @example 'x86_64'
+   */
+  arch: string;
+
   /**
    * Returns the version of the Ike runtime.
    *
@@ -188,14 +195,53 @@ interface Ike {
   path: typeof import('@std/path');
 
   /**
-   * Environment variables.
+   * Environment variables of the current process.
+   *
+   * Changes are applied to the real process environment, so they are visible
+   * to child processes. Variables can also be accessed as properties.
+   *
+   * @example
+   * ```ts
+   * Ike.env.set("MODE", "production");
+   * console.log(Ike.env.get("MODE"));
+   * console.log(Ike.env.PATH);
+   * ```
+   */
+  env: Env;
+
+  /**
+   * Inspect and drop permissions at runtime.
+   *
+   * @example
+   * ```ts
+   * if (Ike.permissions.query({ name: "read", path: "./data" }) === "granted") {
+   *   // ...
+   * }
+   * ```
+   */
+  permissions: Permissions;
+
+  /**
+   * Errors thrown by the runtime.
    *
    * @example
    * ```ts
-   * console.log(Ike.env.Path);
+   * try {
+   *   Ike.readTextFileSync("secret.txt");
+   * } catch (err) {
+   *   if (err instanceof Ike.errors.PermissionDenied) {
+   *     console.log("run again with --allow-read");
+   *   }
+   * }
    * ```
    */
-  env: Record<string, string>;
+  errors: {
+    /**
+     * Thrown when the script doesn't have a permission required by the operation.
+     * The message names the flag that grants it, e.g. `--allow-read`.
+     */
+    PermissionDenied: typeof PermissionDenied;
+  };
 
   /**
    * Transpile TypeScript code to JavaScript.
@@ -207,6 +253,94 @@ interface Ike {
   transpile(loader: Loader, sourceText: string): string;
 }
 
+type PermissionState = 'granted' | 'denied' | 'prompt';
+
+type PermissionDescriptor =
+  | { name: 'read'; path?: string }
+  | { name: 'write'; path?: string }
+  | { name: 'net'; host?: string }
+  | { name: 'env'; variable?: string }
+  | { name: 'run'; command?: string };
+
+interface Permissions {
+  /**
+   * Returns the current state of a permission.
+   *
+   * Omitting the resource (e.g. `path`) queries access to every resource of that kind,
+   * which is denied once any of them was revoked.
+   *
+   * @param descriptor Permission to query.
+   * @returns PermissionState
+   */
+  query(descriptor: PermissionDescriptor): PermissionState;
+
+  /**
+   * Requests a permission, prompting the user if stdin is a TTY and the permission
+   * wasn't decided yet.
+   *
+   * @param descriptor Permission to request.
+   * @returns PermissionState
+   */
+  request(descriptor: PermissionDescriptor): PermissionState;
+
+  /**
+   * Revokes a permission for the rest of the process. Revoked permissions can't be
+   * requested again. Under `ike test` a revocation only lasts until the end of the
+   * test file.
+   *
+   * @param descriptor Permission to revoke.
+   * @returns PermissionState
+   */
+  revoke(descriptor: PermissionDescriptor): PermissionState;
+}
+
+declare class PermissionDenied extends Error {
+  name: 'PermissionDenied';
+}
+
+interface Env {
+  /**
+   * Returns the value of an environment variable.
+   *
+   * @param key Name of the variable.
+   * @returns string | undefined
+   */
+  get(key: string): string | undefined;
+
+  /**
+   * Sets an environment variable for the current process.
+   *
+   * @param key Name of the variable.
+   * @param value Value of the variable.
+   * @throws TypeError if the name is empty or contains `=` or NUL characters.
+   */
+  set(key: string, value: string): void;
+
+  /**
+   * Removes an environment variable from the current process.
+   *
+   * @param key Name of the variable.
+   */
+  delete(key: string): void;
+
+  /**
+   * Checks if an environment variable is set.
+   *
+   * @param key Name of the variable.
+   * @returns boolean
+   */
+  has(key: string): boolean;
+
+  /**
+   * Returns a snapshot of all environment variables.
+   *
+   * @returns Record<string, string>
+   */
+  toObject(): Record<string, string>;
+
+  [key: string]: any;
+}
+
 type Loader = 'js' | 'mjs' | 'ts' | 'mts' | 'cjs' | 'cts' | 'jsx' | 'tsx';
 
 declare global {
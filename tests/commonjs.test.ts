@@ -0,0 +1,32 @@
+import { describe, expect, it } from '@std/test';
+import math, { add, sub, version } from './commonjs/math.cjs';
+import { fromB, loaded } from './commonjs/a.cjs';
+import paths from './commonjs/paths.cjs';
+import { greet } from './commonjs/pkg/index.js';
+
+describe('commonjs', () => {
+  it('expect named exports to be detected', () => {
+    expect(add(1, 2)).toBe(3);
+    expect(sub(3, 2)).toBe(1);
+    expect(version).toBe('1.0.0');
+  });
+
+  it('expect module.exports to be the default export', () => {
+    expect(math.add).toBe(add);
+  });
+
+  it('expect cycles to see partially loaded exports', () => {
+    expect(loaded).toBeTrue();
+    expect(fromB).toBeFalse();
+  });
+
+  it('expect __dirname, __filename and require.cache', () => {
+    expect(paths.name).toBe('paths.cjs');
+    expect(paths.filename.startsWith(paths.dirname)).toBeTrue();
+    expect(paths.cached).toBeTrue();
+  });
+
+  it('expect package.json type and json files to be respected', () => {
+    expect(greet('ike')).toBe('hello, ike');
+  });
+});
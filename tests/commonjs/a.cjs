@@ -0,0 +1,4 @@
+exports.loaded = false;
+const b = require('./b.cjs');
+exports.fromB = b.sawA;
+exports.loaded = true;
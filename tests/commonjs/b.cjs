@@ -0,0 +1,3 @@
+// a.cjs is still loading, so this sees its exports as they are so far
+const a = require('./a.cjs');
+exports.sawA = a.loaded;
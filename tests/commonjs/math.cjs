@@ -0,0 +1,3 @@
+exports.add = (a, b) => a + b;
+module.exports.sub = (a, b) => a - b;
+Object.defineProperty(exports, 'version', { value: '1.0.0', enumerable: true });
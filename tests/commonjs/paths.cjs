@@ -0,0 +1,8 @@
+const { basename } = require('@std/path');
+
+module.exports = {
+  dirname: __dirname,
+  filename: __filename,
+  name: basename(__filename),
+  cached: require.cache[__filename] === module,
+};
@@ -0,0 +1,50 @@
+import { describe, expect, it } from '@std/test';
+
+describe('Ike.env', () => {
+  it('expect set and get to work', () => {
+    Ike.env.set('IKE_TEST_ENV', 'hello');
+    expect(Ike.env.get('IKE_TEST_ENV')).toBe('hello');
+  });
+
+  it('expect has to return correct value', () => {
+    Ike.env.set('IKE_TEST_HAS', '1');
+    expect(Ike.env.has('IKE_TEST_HAS')).toBeTrue();
+    expect(Ike.env.has('IKE_TEST_MISSING')).toBeFalse();
+  });
+
+  it('expect delete to remove the variable', () => {
+    Ike.env.set('IKE_TEST_DELETE', '1');
+    Ike.env.delete('IKE_TEST_DELETE');
+    expect(Ike.env.get('IKE_TEST_DELETE')).toBeUndefined();
+  });
+
+  it('expect property access to reflect changes', () => {
+    Ike.env.IKE_TEST_PROPERTY = 'value';
+    expect(Ike.env.IKE_TEST_PROPERTY).toBe('value');
+    expect(Ike.env.get('IKE_TEST_PROPERTY')).toBe('value');
+    expect('IKE_TEST_PROPERTY' in Ike.env).toBeTrue();
+
+    delete Ike.env.IKE_TEST_PROPERTY;
+    expect(Ike.env.IKE_TEST_PROPERTY).toBeUndefined();
+  });
+
+  it('expect toObject to return a snapshot', () => {
+    Ike.env.set('IKE_TEST_SNAPSHOT', 'before');
+    const snapshot = Ike.env.toObject();
+    Ike.env.set('IKE_TEST_SNAPSHOT', 'after');
+
+    expect(snapshot.IKE_TEST_SNAPSHOT).toBe('before');
+    expect(Object.keys(Ike.env)).toInclude('IKE_TEST_SNAPSHOT');
+  });
+
+  it('expect to convert to a string like other objects', () => {
+    expect(String(Ike.env)).toBe('[object Object]');
+    expect(`${Ike.env}`).toBe('[object Object]');
+    expect(Ike.env.hasOwnProperty).toBe(Object.prototype.hasOwnProperty);
+  });
+
+  it('expect to throw on invalid names', () => {
+    expect(() => Ike.env.set('', 'value')).toThrow();
+    expect(() => Ike.env.set('A=B', 'value')).toThrow();
+  });
+});
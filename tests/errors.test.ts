@@ -0,0 +1,38 @@
+import { describe, expect, it } from '@std/test';
+import { checkUser } from './errors/user.ts';
+
+const thrown = (fn: () => void): any => {
+  try {
+    fn();
+  } catch (error) {
+    return error;
+  }
+};
+
+describe('thrown errors', () => {
+  it('expect to record where they were thrown in the original source', () => {
+    const error = thrown(() => checkUser({ name: '' }));
+
+    expect(error.fileName.endsWith('user.ts')).toBe(true);
+    expect(error.lineNumber).toBe(7);
+    expect(error.columnNumber).toBe(5);
+  });
+
+  it('expect to keep the location of the first throw when rethrown', () => {
+    const error = thrown(() => {
+      try {
+        checkUser({ name: '' });
+      } catch (error) {
+        throw error;
+      }
+    });
+
+    expect(error.lineNumber).toBe(7);
+  });
+
+  it('expect to leave thrown primitives alone', () => {
+    expect(thrown(() => {
+      throw 'boom';
+    })).toBe('boom');
+  });
+});
@@ -0,0 +1,9 @@
+interface User {
+  name: string;
+}
+
+export const checkUser = (user: User): void => {
+  if (!user.name) {
+    throw new TypeError('Missing name');
+  }
+};
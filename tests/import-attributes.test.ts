@@ -0,0 +1,44 @@
+import { describe, expect, it } from '@std/test';
+import config from './attributes/config.json' with { type: 'json' };
+import configByExtension from './attributes/config.json';
+import message from './attributes/message.txt' with { type: 'text' };
+import bytes from './attributes/message.txt' with { type: 'bytes' };
+import manifest from './attributes/config.toml' with { type: 'toml' };
+
+describe('import attributes', () => {
+  it('expect json imports to export the parsed value', () => {
+    expect(config.name).toBe('ike');
+    expect(config.features).toHaveLength(2);
+    expect(config.nested.enabled).toBeTrue();
+  });
+
+  it('expect json files to be imported without the attribute', () => {
+    expect(configByExtension).toBe(config);
+  });
+
+  it('expect text imports to export a string', () => {
+    expect(message).toBe('hello from a text file\n');
+  });
+
+  it('expect bytes imports to export a Uint8Array', () => {
+    expect(bytes).toBeInstanceOf(Uint8Array);
+    expect(new TextDecoder().decode(bytes)).toBe(message);
+  });
+
+  it('expect toml imports to export the parsed table', () => {
+    expect(manifest.package.name).toBe('attributes');
+    expect(manifest.limits.retries).toBe(3);
+  });
+
+  it('expect dynamic imports of json files to work', async () => {
+    const { default: data } = await import('./attributes/config.json');
+    expect(data).toBe(config);
+  });
+
+  it('expect dynamic imports to pass the type attribute', async () => {
+    const { default: text } = await import('./attributes/message.txt', {
+      with: { type: 'text' },
+    });
+    expect(text).toBe(message);
+  });
+});
@@ -0,0 +1,44 @@
+import { describe, expect, it } from '@std/test';
+import { Buffer } from 'node:buffer';
+
+describe('node:buffer', () => {
+  it('expect strings to round trip through encodings', () => {
+    const buffer = Buffer.from('hello');
+    expect(buffer.toString()).toBe('hello');
+    expect(buffer.toString('hex')).toBe('68656c6c6f');
+    expect(buffer.toString('base64')).toBe('aGVsbG8=');
+    expect(Buffer.from('68656c6c6f', 'hex').toString()).toBe('hello');
+    expect(Buffer.from('aGVsbG8=', 'base64').toString()).toBe('hello');
+  });
+
+  it('expect Buffer to be a Uint8Array', () => {
+    const buffer = Buffer.alloc(4, 1);
+    expect(buffer).toBeInstanceOf(Uint8Array);
+    expect(Buffer.isBuffer(buffer)).toBeTrue();
+    expect(Buffer.isBuffer(new Uint8Array(4))).toBeFalse();
+    expect(buffer[3]).toBe(1);
+  });
+
+  it('expect slice to share memory', () => {
+    const buffer = Buffer.from('hello');
+    buffer.slice(0, 1)[0] = 0x6a;
+    expect(buffer.toString()).toBe('jello');
+  });
+
+  it('expect concat, compare and byteLength to work', () => {
+    const joined = Buffer.concat([Buffer.from('ab'), Buffer.from('cd')]);
+    expect(joined.toString()).toBe('abcd');
+    expect(joined.equals(Buffer.from('abcd'))).toBeTrue();
+    expect(Buffer.compare(Buffer.from('a'), Buffer.from('b'))).toBe(-1);
+    expect(Buffer.byteLength('ž')).toBe(2);
+  });
+
+  it('expect integers to be read and written', () => {
+    const buffer = Buffer.alloc(4);
+    buffer.writeUInt16LE(0x1234, 0);
+    buffer.writeUInt16BE(0x1234, 2);
+    expect(buffer.readUInt16LE(0)).toBe(0x1234);
+    expect(buffer.readUInt16BE(2)).toBe(0x1234);
+    expect(buffer.toString('hex')).toBe('34121234');
+  });
+});
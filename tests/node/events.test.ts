@@ -0,0 +1,47 @@
+import { describe, expect, it } from '@std/test';
+import EventEmitter, { once } from 'node:events';
+
+describe('node:events', () => {
+  it('expect listeners to receive emitted arguments', () => {
+    const emitter = new EventEmitter();
+    let sum = 0;
+    emitter.on('add', (a: number, b: number) => (sum += a + b));
+
+    expect(emitter.emit('add', 1, 2)).toBeTrue();
+    expect(emitter.emit('missing')).toBeFalse();
+    expect(sum).toBe(3);
+  });
+
+  it('expect once listeners to run a single time', () => {
+    const emitter = new EventEmitter();
+    let calls = 0;
+    emitter.once('tick', () => calls++);
+
+    emitter.emit('tick');
+    emitter.emit('tick');
+    expect(calls).toBe(1);
+    expect(emitter.listenerCount('tick')).toBe(0);
+  });
+
+  it('expect off to remove once listeners by the original function', () => {
+    const emitter = new EventEmitter();
+    const listener = () => {};
+    emitter.once('tick', listener);
+    emitter.off('tick', listener);
+
+    expect(emitter.listenerCount('tick')).toBe(0);
+  });
+
+  it('expect unhandled error events to throw', () => {
+    const emitter = new EventEmitter();
+    expect(() => emitter.emit('error', new Error('boom'))).toThrow();
+  });
+
+  it('expect once helper to resolve with the event arguments', async () => {
+    const emitter = new EventEmitter();
+    queueMicrotask(() => emitter.emit('ready', 42));
+
+    const [value] = await once(emitter, 'ready');
+    expect(value).toBe(42);
+  });
+});
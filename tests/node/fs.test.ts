@@ -0,0 +1,49 @@
+import { afterAll, describe, expect, it } from '@std/test';
+import fs, { existsSync, mkdirSync, readFileSync, rmSync } from 'node:fs';
+import { readFile } from 'node:fs/promises';
+
+describe('node:fs', () => {
+  it('expect readFileSync to return a Buffer or a string', () => {
+    expect(readFileSync('tests/hello.txt', 'utf8')).toBe('hello');
+    expect(readFileSync('tests/hello.txt', { encoding: 'utf8' })).toBe('hello');
+    expect(readFileSync('tests/hello.txt').toString('hex')).toBe('68656c6c6f');
+  });
+
+  it('expect readFile callback to receive the content', async () => {
+    const content = await new Promise((resolve, reject) => {
+      fs.readFile('tests/hello.txt', 'utf8', (err: Error | null, data?: string) =>
+        err ? reject(err) : resolve(data),
+      );
+    });
+    expect(content).toBe('hello');
+  });
+
+  it('expect promises to read files', async () => {
+    expect(await readFile('tests/hello.txt', 'utf8')).toBe('hello');
+  });
+
+  it('expect mkdirSync and rmSync to manage directories', () => {
+    mkdirSync('tests/node-fs-dir/nested', { recursive: true });
+    expect(existsSync('tests/node-fs-dir/nested')).toBeTrue();
+
+    rmSync('tests/node-fs-dir', { recursive: true });
+    expect(existsSync('tests/node-fs-dir')).toBeFalse();
+  });
+
+  it('expect rmSync to throw ENOENT unless forced', () => {
+    let code;
+    try {
+      rmSync('tests/node-fs-missing');
+    } catch (err: any) {
+      code = err.code;
+    }
+    expect(code).toBe('ENOENT');
+
+    rmSync('tests/node-fs-missing', { force: true });
+    expect(existsSync('tests/node-fs-missing')).toBeFalse();
+  });
+
+  afterAll(() => {
+    rmSync('tests/node-fs-dir', { recursive: true, force: true });
+  });
+});
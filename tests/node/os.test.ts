@@ -0,0 +1,37 @@
+import { describe, expect, it } from '@std/test';
+import os from 'node:os';
+
+describe('node:os', () => {
+  it('expect platform to use node names', () => {
+    expect(os.platform()).toBeOneOf([
+      'linux',
+      'darwin',
+      'win32',
+      'freebsd',
+      'openbsd',
+      'netbsd',
+      'android',
+      'ios',
+      'dragonfly',
+      'solaris',
+    ]);
+  });
+
+  it('expect EOL to match the platform', () => {
+    expect(os.EOL).toBe(Ike.isWindows() ? '\r\n' : '\n');
+  });
+
+  it('expect homedir to come from the environment', () => {
+    const home = Ike.isWindows() ? Ike.env.get('USERPROFILE') : Ike.env.get('HOME');
+    expect(os.homedir()).toBe(home ?? '');
+  });
+
+  it('expect tmpdir to be a string without a trailing slash', () => {
+    expect(os.tmpdir()).toBeString();
+    expect(os.tmpdir().length === 1 || !os.tmpdir().endsWith('/')).toBeTrue();
+  });
+
+  it('expect endianness to be reported', () => {
+    expect(os.endianness()).toBeOneOf(['LE', 'BE']);
+  });
+});
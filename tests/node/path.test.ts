@@ -0,0 +1,16 @@
+import { describe, expect, it } from '@std/test';
+import path, { basename, join, posix } from 'node:path';
+
+describe('node:path', () => {
+  it('expect the @std/path functions to be re-exported', () => {
+    expect(join('a', 'b', '../c')).toBe('a/c');
+    expect(basename('/tmp/file.txt', '.txt')).toBe('file');
+    expect(path.extname('index.ts')).toBe('.ts');
+  });
+
+  it('expect posix and win32 namespaces to exist', () => {
+    expect(posix.sep).toBe('/');
+    expect(path.win32.sep).toBe('\\');
+    expect(posix.join('a', 'b')).toBe('a/b');
+  });
+});
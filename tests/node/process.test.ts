@@ -0,0 +1,34 @@
+import { describe, expect, it } from '@std/test';
+import process, { cwd, env, nextTick } from 'node:process';
+
+describe('node:process', () => {
+  it('expect process to mirror the Ike global', () => {
+    expect(process.pid).toBe(Ike.pid);
+    expect(cwd()).toBe(Ike.cwd());
+    expect(process.version).toBe(`v${Ike.version}`);
+  });
+
+  it('expect env to be shared with Ike.env', () => {
+    env.IKE_NODE_PROCESS = 'yes';
+    expect(Ike.env.get('IKE_NODE_PROCESS')).toBe('yes');
+    delete env.IKE_NODE_PROCESS;
+  });
+
+  it('expect nextTick to run the callback asynchronously', async () => {
+    let ran = false;
+    await new Promise<void>((resolve) =>
+      nextTick(() => {
+        ran = true;
+        resolve();
+      }),
+    );
+    expect(ran).toBeTrue();
+  });
+
+  it('expect process to be an event emitter', () => {
+    let received;
+    process.once('custom', (value: string) => (received = value));
+    process.emit('custom', 'hi');
+    expect(received).toBe('hi');
+  });
+});
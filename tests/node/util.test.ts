@@ -0,0 +1,36 @@
+import { describe, expect, it } from '@std/test';
+import util, { format, isDeepStrictEqual, promisify, types } from 'node:util';
+
+describe('node:util', () => {
+  it('expect format to substitute placeholders', () => {
+    expect(format('%s is %d', 'answer', 42)).toBe('answer is 42');
+  });
+
+  it('expect promisify to wrap callback functions', async () => {
+    const add = (a: number, b: number, cb: (err: unknown, value?: number) => void) =>
+      cb(null, a + b);
+    const fail = (cb: (err: unknown) => void) => cb(new Error('boom'));
+
+    expect(await promisify(add)(1, 2)).toBe(3);
+    expect(async () => await promisify(fail)()).toThrow();
+  });
+
+  it('expect callbackify to wrap async functions', async () => {
+    const double = util.callbackify(async (n: number) => n * 2);
+    const value = await new Promise((resolve) => double(2, (_: unknown, v: number) => resolve(v)));
+    expect(value).toBe(4);
+  });
+
+  it('expect isDeepStrictEqual to compare structures', () => {
+    expect(isDeepStrictEqual({ a: [1, { b: 2 }] }, { a: [1, { b: 2 }] })).toBeTrue();
+    expect(isDeepStrictEqual({ a: 1 }, { a: '1' })).toBeFalse();
+    expect(isDeepStrictEqual(new Map([[1, 2]]), new Map([[1, 2]]))).toBeTrue();
+  });
+
+  it('expect types to detect values', () => {
+    expect(types.isPromise(Promise.resolve())).toBeTrue();
+    expect(types.isDate(new Date())).toBeTrue();
+    expect(types.isTypedArray(new Uint8Array())).toBeTrue();
+    expect(types.isRegExp('a')).toBeFalse();
+  });
+});
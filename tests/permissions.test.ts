@@ -0,0 +1,57 @@
+import { describe, expect, it } from '@std/test';
+
+describe('Ike.errors', () => {
+  it('expect PermissionDenied to be defined', () => {
+    expect(Ike.errors.PermissionDenied).toBeFunction();
+  });
+
+  it('expect PermissionDenied to be an error', () => {
+    const error = new Ike.errors.PermissionDenied('denied');
+
+    expect(error).toBeInstanceOf(Error);
+    expect(error.name).toBe('PermissionDenied');
+    expect(error.message).toBe('denied');
+  });
+});
+
+describe('Ike.permissions', () => {
+  it('expect query to return the state of a permission', () => {
+    Ike.permissions.revoke({ name: 'read', path: 'tests/__queried__' });
+
+    expect(
+      Ike.permissions.query({ name: 'read', path: 'tests/__queried__' }),
+    ).toBe('denied');
+    expect(Ike.permissions.query({ name: 'read' })).toBe('denied');
+  });
+
+  it('expect query to throw on unknown permissions', () => {
+    // @ts-expect-error
+    expect(() => Ike.permissions.query({ name: 'ffi' })).toThrow();
+  });
+
+  it('expect revoke to deny access for the rest of the file', () => {
+    const state = Ike.permissions.revoke({
+      name: 'read',
+      path: 'tests/__revoked__',
+    });
+
+    expect(state).toBe('denied');
+    expect(
+      Ike.permissions.query({ name: 'read', path: 'tests/__revoked__/file' }),
+    ).toBe('denied');
+    expect(
+      Ike.permissions.request({ name: 'read', path: 'tests/__revoked__' }),
+    ).toBe('denied');
+  });
+
+  it('expect revoked permissions to throw PermissionDenied', () => {
+    Ike.permissions.revoke({ name: 'env', variable: 'IKE_REVOKED_VAR' });
+
+    expect(() => Ike.env.get('IKE_REVOKED_VAR')).toThrow();
+    try {
+      Ike.env.get('IKE_REVOKED_VAR');
+    } catch (err) {
+      expect(err).toBeInstanceOf(Ike.errors.PermissionDenied);
+    }
+  });
+});
@@ -0,0 +1,5 @@
+import { greet } from 'greet';
+
+export function shout(name: string): string {
+  return greet(name).toUpperCase();
+}
@@ -0,0 +1,23 @@
+import { describe, expect, it } from '@std/test';
+import { greet } from 'greet';
+import { shout } from 'greet/shout';
+import { legacy } from 'legacy';
+import { name } from 'resolve-fixture/self';
+
+describe('bare imports', () => {
+  it('expect path dependencies to resolve to the package main', () => {
+    expect(greet('ike')).toBe('Hello, ike!');
+  });
+
+  it('expect subpath imports to follow the exports map', () => {
+    expect(shout('ike')).toBe('HELLO, IKE!');
+  });
+
+  it('expect packages with a package.json to resolve node-style', () => {
+    expect(legacy).toBeTrue();
+  });
+
+  it('expect packages to import their own exports by name', () => {
+    expect(name).toBe('resolve-fixture');
+  });
+});
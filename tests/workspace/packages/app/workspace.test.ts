@@ -0,0 +1,8 @@
+import { describe, expect, it } from '@std/test';
+import { capitalize } from 'strings';
+
+describe('workspaces', () => {
+  it('expect members to import each other by name', () => {
+    expect(capitalize('ike')).toBe('Ike');
+  });
+});